@@ -3,12 +3,9 @@ use czkawka_core::tools::big_file::{BigFile, BigFileParameters, SearchMode};
 use czkawka_core::{common::model::FileEntry, common::tool_data::CommonData};
 use rayon::prelude::*;
 use serde::Serialize;
-use tauri::{AppHandle, Emitter};
 
 use crate::{
-	scaner::{set_scaner_common_settings, spawn_scaner_thread},
-	settings::Settings,
-	state::get_stop_flag_and_progress_tx,
+	scaner::set_scaner_common_settings, settings::Settings, sink::ResultSink,
 };
 
 #[derive(Serialize, Clone)]
@@ -18,50 +15,44 @@ struct ScanResult {
 	message: String,
 }
 
-pub fn scan_big_files(app: AppHandle, settings: Settings) {
-	spawn_scaner_thread(move || {
-		let (stop_flag, progress_tx) = get_stop_flag_and_progress_tx(&app);
+pub fn scan_big_files<S: ResultSink>(sink: &S, settings: Settings) {
+	let (stop_flag, progress_tx) = sink.stop_flag_and_progress_tx();
 
-		let search_mode = match settings.biggest_files_sub_method.as_ref() {
-			"SmallestFiles" => SearchMode::SmallestFiles,
-			_ => SearchMode::BiggestFiles,
-		};
-		let mut scaner = BigFile::new(BigFileParameters::new(
-			settings.biggest_files_sub_number_of_files as usize,
-			search_mode,
-		));
+	let search_mode = match settings.biggest_files_sub_method.as_ref() {
+		"SmallestFiles" => SearchMode::SmallestFiles,
+		_ => SearchMode::BiggestFiles,
+	};
+	let mut scaner = BigFile::new(BigFileParameters::new(
+		settings.biggest_files_sub_number_of_files as usize,
+		search_mode,
+	));
 
-		set_scaner_common_settings(&mut scaner, settings);
+	set_scaner_common_settings(&mut scaner, settings);
 
-		// v10 API: use Search::search(stop_flag, progress_sender)
-		scaner.search(&stop_flag, Some(&progress_tx));
+	// v10 API: use Search::search(stop_flag, progress_sender)
+	scaner.search(&stop_flag, Some(&progress_tx));
 
-		let mut list = scaner.get_big_files().clone();
-		let mut message = scaner.get_text_messages().create_messages_text();
+	let mut list = scaner.get_big_files().clone();
+	let mut message = scaner.get_text_messages().create_messages_text();
 
-		message = format!("Found {} files\n{}", list.len(), message);
+	message = format!("Found {} files\n{}", list.len(), message);
 
-		if search_mode == SearchMode::BiggestFiles {
-			list.par_sort_unstable_by_key(|fe| u64::MAX - fe.size);
-		} else {
-			list.par_sort_unstable_by_key(|fe| fe.size);
-		}
+	if search_mode == SearchMode::BiggestFiles {
+		list.par_sort_unstable_by_key(|fe| u64::MAX - fe.size);
+	} else {
+		list.par_sort_unstable_by_key(|fe| fe.size);
+	}
 
-		app.emit(
-			"scan-result",
-			ScanResult {
-				cmd: "scan_big_files",
-				list,
-				message,
-			},
-		)
-		.unwrap();
-
-		set_scaner_state(app, scaner);
+	sink.emit_result(ScanResult {
+		cmd: "scan_big_files",
+		list,
+		message,
 	});
+
+	sink.set_scaner_state(scaner);
 }
 
-crate::gen_set_scaner_state_fn!(
+crate::impl_scaner_state!(
 	big_files_state,
 	czkawka_core::tools::big_file::BigFile
 );
@@ -0,0 +1,220 @@
+use std::{fs, io::Write, path::PathBuf, process::ExitCode, thread};
+
+use chrono::Local;
+
+use czkawka_core::common::{
+	config_cache_path::set_config_cache_path, get_all_available_threads,
+	set_number_of_threads,
+};
+use czkawka_tauri::{
+	scaner::{Tool, run_tool},
+	schedule::{SCHEDULER_TICK, Scheduler},
+	settings::Settings,
+	sink::MemorySink,
+	sweep::{SWEEP_TOOLS, sweep},
+};
+
+const USAGE: &str = "\
+Run a Czkawka tool without the webview.
+Progress is written to stderr as JSON lines, the scan result to stdout.
+
+Usage: czkawka-tauri-cli <TOOL> [OPTIONS]
+       czkawka-tauri-cli schedule --config-dir <DIR> [OPTIONS]
+
+Tools:
+  duplicate_files, empty_folders, big_files, empty_files, temporary_files,
+  similar_images, similar_videos, music_duplicates, invalid_symlinks,
+  broken_files, bad_extensions
+  sweep  runs duplicate_files, big_files, empty_files, temporary_files and
+         bad_extensions on a single walk of the directories
+  schedule  keeps running and starts the schedules saved in the config dir
+            when they are due, printing a summary of each run to stdout
+
+Options:
+  -s, --settings <FILE>   Read settings from a JSON file (same shape as the app)
+  -i, --include <DIR>     Included directory, replaces the defaults (repeatable)
+  -r, --reference <DIR>   Included directory used as reference (repeatable)
+  -e, --exclude <DIR>     Excluded directory, added to the defaults (repeatable)
+  -t, --threads <NUM>     Number of threads, defaults to all available
+  -c, --config-dir <DIR>  Config dir of the app, holding the schedules
+  -h, --help              Print this help
+";
+
+enum Command {
+	Tool(Tool),
+	Sweep,
+	Schedule,
+}
+
+struct Args {
+	command: Command,
+	settings_file: Option<PathBuf>,
+	included: Vec<String>,
+	referenced: Vec<String>,
+	excluded: Vec<String>,
+	threads: Option<usize>,
+	config_dir: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+	let args = match parse_args(std::env::args().skip(1)) {
+		Ok(Some(args)) => args,
+		Ok(None) => {
+			print!("{}", USAGE);
+			return ExitCode::SUCCESS;
+		}
+		Err(err) => {
+			eprintln!("{}\n\n{}", err, USAGE);
+			return ExitCode::from(2);
+		}
+	};
+
+	let settings = match load_settings(&args) {
+		Ok(settings) => settings,
+		Err(err) => {
+			eprintln!("{}", err);
+			return ExitCode::from(2);
+		}
+	};
+
+	let _ = set_config_cache_path("czkawka", "czkawka");
+	set_number_of_threads(
+		args.threads.unwrap_or_else(get_all_available_threads),
+	);
+
+	let sink = match &args.config_dir {
+		Some(config_dir) => MemorySink::with_config_dir(config_dir.clone()),
+		None => MemorySink::new(),
+	};
+	let progress_rx = sink.progress_rx();
+
+	// The sink keeps a sender alive, so this thread lives until the process exits
+	std::thread::spawn(move || {
+		let mut stderr = std::io::stderr();
+		for progress in progress_rx {
+			let data = progress.process();
+			if let Ok(line) = serde_json::to_string(&data) {
+				let _ = writeln!(stderr, "{}", line);
+			}
+		}
+	});
+
+	match args.command {
+		Command::Tool(tool) => run_tool(&sink, tool, settings),
+		Command::Sweep => sweep(&sink, &SWEEP_TOOLS, settings),
+		Command::Schedule => {
+			let mut scheduler = Scheduler::new(Local::now());
+			loop {
+				thread::sleep(SCHEDULER_TICK);
+				scheduler.tick(&sink, Local::now());
+				for (event, payload) in sink.take_events() {
+					if event == "schedule-run" {
+						println!("{}", payload);
+					}
+				}
+			}
+		}
+	}
+
+	for result in sink.results() {
+		println!("{}", result);
+	}
+
+	ExitCode::SUCCESS
+}
+
+fn parse_args(
+	mut raw: impl Iterator<Item = String>,
+) -> Result<Option<Args>, String> {
+	let mut command = None;
+	let mut settings_file = None;
+	let mut included = vec![];
+	let mut referenced = vec![];
+	let mut excluded = vec![];
+	let mut threads = None;
+	let mut config_dir = None;
+
+	while let Some(arg) = raw.next() {
+		let mut value = |name: &str| {
+			raw.next()
+				.ok_or_else(|| format!("Missing value for `{}`", name))
+		};
+		match arg.as_str() {
+			"-h" | "--help" => return Ok(None),
+			"-s" | "--settings" => {
+				settings_file = Some(PathBuf::from(value(&arg)?))
+			}
+			"-i" | "--include" => included.push(value(&arg)?),
+			"-r" | "--reference" => referenced.push(value(&arg)?),
+			"-e" | "--exclude" => excluded.push(value(&arg)?),
+			"-c" | "--config-dir" => {
+				config_dir = Some(PathBuf::from(value(&arg)?))
+			}
+			"-t" | "--threads" => {
+				let v = value(&arg)?;
+				threads =
+					Some(v.parse::<usize>().map_err(|_| {
+						format!("Invalid thread number `{}`", v)
+					})?);
+			}
+			_ if arg.starts_with('-') => {
+				return Err(format!("Unknown option `{}`", arg));
+			}
+			"sweep" if command.is_none() => command = Some(Command::Sweep),
+			"schedule" if command.is_none() => {
+				command = Some(Command::Schedule)
+			}
+			_ if command.is_none() => {
+				command = Some(Command::Tool(
+					Tool::from_cmd(&arg)
+						.ok_or_else(|| format!("Unknown tool `{}`", arg))?,
+				));
+			}
+			_ => return Err(format!("Unexpected argument `{}`", arg)),
+		}
+	}
+
+	let Some(command) = command else {
+		return Err("Missing tool".to_string());
+	};
+	if matches!(command, Command::Schedule) && config_dir.is_none() {
+		return Err("`schedule` needs `--config-dir`".to_string());
+	}
+
+	Ok(Some(Args {
+		command,
+		settings_file,
+		included,
+		referenced,
+		excluded,
+		threads,
+		config_dir,
+	}))
+}
+
+fn load_settings(args: &Args) -> Result<Settings, String> {
+	let mut settings = match &args.settings_file {
+		Some(path) => {
+			let content = fs::read_to_string(path).map_err(|err| {
+				format!("Failed to read `{}`: {}", path.display(), err)
+			})?;
+			serde_json::from_str::<Settings>(&content).map_err(|err| {
+				format!("Failed to parse `{}`: {}", path.display(), err)
+			})?
+		}
+		None => Settings::default(),
+	};
+
+	if !args.included.is_empty() || !args.referenced.is_empty() {
+		settings.included_directories = args.included.clone();
+		settings.included_directories_referenced = args.referenced.clone();
+	}
+	settings
+		.included_directories
+		.extend(args.referenced.iter().cloned());
+	settings
+		.excluded_directories
+		.extend(args.excluded.iter().cloned());
+
+	Ok(settings)
+}
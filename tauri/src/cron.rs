@@ -0,0 +1,163 @@
+use std::str::FromStr;
+
+use chrono::{
+	DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone,
+	Timelike,
+};
+
+// How far `next_after` looks ahead, enough for "every 29th of February"
+const MAX_LOOKAHEAD_DAYS: i64 = 366 * 8;
+
+// A five field cron expression (minute, hour, day of month, month, day of
+// week) in local time. Fields take numbers, `*`, ranges, lists and steps,
+// e.g. `*/15 1-5 * * 1,3,5`. Day names and month names are not supported.
+// Like in cron, when both day fields are restricted either one has to match.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cron {
+	minutes: u64,
+	hours: u64,
+	days: u64,
+	months: u64,
+	weekdays: u64,
+	any_day: bool,
+	any_weekday: bool,
+}
+
+impl FromStr for Cron {
+	type Err = String;
+
+	fn from_str(expression: &str) -> Result<Self, Self::Err> {
+		let expression = match expression.trim() {
+			"@hourly" => "0 * * * *",
+			"@daily" | "@midnight" => "0 0 * * *",
+			"@weekly" => "0 0 * * 0",
+			"@monthly" => "0 0 1 * *",
+			"@yearly" | "@annually" => "0 0 1 1 *",
+			expression => expression,
+		};
+		let fields: Vec<_> = expression.split_whitespace().collect();
+		let [minutes, hours, days, months, weekdays] = fields[..] else {
+			return Err(format!(
+				"Expected 5 fields in `{}`, found {}",
+				expression,
+				fields.len()
+			));
+		};
+
+		let mut weekdays_bits = parse_field(weekdays, 0, 7)?;
+		// Both 0 and 7 are Sunday
+		if weekdays_bits & (1 << 7) != 0 {
+			weekdays_bits = (weekdays_bits | 1) & !(1 << 7);
+		}
+
+		Ok(Cron {
+			minutes: parse_field(minutes, 0, 59)?,
+			hours: parse_field(hours, 0, 23)?,
+			days: parse_field(days, 1, 31)?,
+			months: parse_field(months, 1, 12)?,
+			weekdays: weekdays_bits,
+			any_day: days.starts_with('*'),
+			any_weekday: weekdays.starts_with('*'),
+		})
+	}
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+	let invalid = || format!("Invalid cron field `{}`", field);
+	let mut bits = 0;
+
+	for part in field.split(',') {
+		let (range, step) = match part.split_once('/') {
+			Some((range, step)) => {
+				(range, Some(step.parse::<u32>().map_err(|_| invalid())?))
+			}
+			None => (part, None),
+		};
+		let (low, high) = if range == "*" {
+			(min, max)
+		} else if let Some((low, high)) = range.split_once('-') {
+			(
+				low.parse().map_err(|_| invalid())?,
+				high.parse().map_err(|_| invalid())?,
+			)
+		} else {
+			let value = range.parse().map_err(|_| invalid())?;
+			// `5/10` means from 5 to the end in steps of 10
+			(
+				value,
+				if step.is_some() {
+					max
+				} else {
+					value
+				},
+			)
+		};
+		let step = step.unwrap_or(1);
+		if low < min || high > max || low > high || step == 0 {
+			return Err(invalid());
+		}
+		for value in (low..=high).step_by(step as usize) {
+			bits |= 1 << value;
+		}
+	}
+
+	Ok(bits)
+}
+
+impl Cron {
+	// The first matching minute strictly after `time`
+	pub fn next_after(&self, time: DateTime<Local>) -> Option<DateTime<Local>> {
+		let mut next = time.naive_local().with_second(0)?.with_nanosecond(0)?
+			+ Duration::minutes(1);
+		let limit = next + Duration::days(MAX_LOOKAHEAD_DAYS);
+
+		while next < limit {
+			if !has(self.months, next.month()) {
+				let (year, month) = if next.month() == 12 {
+					(next.year() + 1, 1)
+				} else {
+					(next.year(), next.month() + 1)
+				};
+				next = start_of(NaiveDate::from_ymd_opt(year, month, 1)?);
+				continue;
+			}
+			if !self.matches_day(next.date()) {
+				next = start_of(next.date().succ_opt()?);
+				continue;
+			}
+			if !has(self.hours, next.hour()) {
+				next = next.with_minute(0)? + Duration::hours(1);
+				continue;
+			}
+			if has(self.minutes, next.minute()) {
+				// Minutes skipped by a daylight saving change never happen
+				if let Some(time) = Local.from_local_datetime(&next).earliest()
+				{
+					return Some(time);
+				}
+			}
+			next += Duration::minutes(1);
+		}
+
+		None
+	}
+
+	fn matches_day(&self, date: NaiveDate) -> bool {
+		let day = has(self.days, date.day());
+		let weekday = has(self.weekdays, date.weekday().num_days_from_sunday());
+		match (self.any_day, self.any_weekday) {
+			(true, true) => true,
+			(true, false) => weekday,
+			(false, true) => day,
+			(false, false) => day || weekday,
+		}
+	}
+}
+
+fn has(bits: u64, value: u32) -> bool {
+	bits & (1 << value) != 0
+}
+
+fn start_of(date: NaiveDate) -> NaiveDateTime {
+	date.and_hms_opt(0, 0, 0).unwrap()
+}
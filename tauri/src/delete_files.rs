@@ -1,9 +1,28 @@
-use std::{fs, path::Path};
+use std::{
+	collections::{HashMap, HashSet},
+	fs::{self, File},
+	io::{self, BufReader, Read},
+	path::{Path, PathBuf},
+	sync::atomic::{AtomicBool, Ordering},
+};
 
-use czkawka_core::common::remove_folder_if_contains_only_empty_folders;
+use czkawka_core::common::{
+	progress_data::CurrentStage, remove_folder_if_contains_only_empty_folders,
+};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Emitter};
+
+use crate::{
+	job::{JobHandle, JobId, run_job_with},
+	journal::{
+		Action, JournalEntry, OperationId, PlannedAction, record_operation,
+	},
+	preflight,
+	progress::{Counters, with_stage_progress},
+	protected_paths::ProtectedPaths,
+	quarantine,
+	sink::ResultSink,
+};
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -11,32 +30,192 @@ pub struct Options {
 	paths: Vec<String>,
 	move_deleted_files_to_trash: bool,
 	is_empty_folders_tool: bool,
+	// Compare every file with the file kept of its group in the last Duplicate
+	// Files result before deleting it
+	#[serde(default)]
+	verify_duplicates: bool,
+	// Move the files into the quarantine instead of deleting them, they can
+	// be restored from there until it is purged
+	#[serde(default)]
+	quarantine: bool,
+	// Defaults to the `quarantine` folder of the data dir
+	#[serde(default)]
+	quarantine_dir: Option<String>,
+	// Only check what would be deleted
+	#[serde(default)]
+	dry_run: bool,
 }
 
 #[derive(Serialize, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 struct DeleteFilesResult {
+	job_id: JobId,
+	// Set when the job was cancelled before every file was deleted
+	cancelled: bool,
 	success_paths: Vec<String>,
 	errors: Vec<String>,
+	// Files left alone because their content differs from the kept file
+	mismatched_paths: Vec<String>,
+	// Journal entry to undo the deletion with
+	operation_id: Option<OperationId>,
+	// Set by a dry run instead of deleting anything
+	planned: Option<Vec<PlannedAction>>,
+	#[serde(skip)]
+	journal: Vec<JournalEntry>,
+}
+
+// Deletes in the background as a job, which reports progress and can be
+// cancelled between files
+pub fn delete_files<S: ResultSink>(sink: &S, options: Options) -> JobId {
+	let job =
+		sink.with_state(|state| state.jobs.start_operation("delete_files"));
+	let job_id = job.id;
+	let sink = sink.clone();
+	std::thread::spawn(move || run_delete_files(&sink, job, options));
+	job_id
 }
 
-pub fn delete_files(app: AppHandle, options: Options) {
-	std::thread::spawn(move || {
-		let result = delete_files_impl(options);
-		app.emit("delete-files-result", result).unwrap();
+pub fn run_delete_files<S: ResultSink>(
+	sink: &S,
+	job: JobHandle,
+	options: Options,
+) {
+	let job_id = job.id;
+	let dry_run = options.dry_run;
+	let quarantine_dir = options.quarantine.then(|| {
+		quarantine::quarantine_dir(sink, options.quarantine_dir.as_deref())
+	});
+	let protected = ProtectedPaths::load(sink);
+	let mut result = run_job_with(sink, job, |job_sink| {
+		let (stop_flag, progress_tx) = job_sink.stop_flag_and_progress_tx();
+		let quarantine = match &quarantine_dir {
+			Some(dir) => match dir.clone().and_then(|dir| {
+				// Its files are purged later, so it is refused where they could
+				// not be deleted
+				protected.check(&dir)?;
+				let batch = quarantine::new_batch(&dir, !dry_run)?;
+				Ok((dir, batch))
+			}) {
+				Ok(quarantine) => Some(quarantine),
+				Err(err) => {
+					return DeleteFilesResult {
+						errors: vec![format!(
+							"Failed to prepare the quarantine, reason: {}",
+							err
+						)],
+						..DeleteFilesResult::default()
+					};
+				}
+			},
+			None => None,
+		};
+		let kept = options
+			.verify_duplicates
+			.then(|| kept_files(sink, &options.paths));
+		with_stage_progress(
+			&progress_tx,
+			CurrentStage::DeletingFiles,
+			(0, 0),
+			(options.paths.len(), 0),
+			|counters| {
+				let ctx = Context {
+					kept: kept.as_ref(),
+					quarantine: quarantine
+						.as_ref()
+						.map(|(dir, batch)| (dir.as_path(), batch.as_path())),
+					protected: &protected,
+					stop_flag: &stop_flag,
+					counters,
+				};
+				delete_files_impl(options, ctx)
+			},
+		)
 	});
+	result.job_id = job_id;
+	if dry_run {
+		result.planned =
+			Some(result.journal.drain(..).map(Into::into).collect());
+	} else {
+		result.operation_id =
+			record_operation(sink, "delete_files", &result.journal);
+	}
+	sink.emit_event("delete-files-result", result);
+}
+
+// The file each path is compared with: the reference file of its group, or
+// else the first file of the group that is not deleted too
+fn kept_files<S: ResultSink>(
+	sink: &S,
+	paths: &[String],
+) -> HashMap<String, Result<PathBuf, String>> {
+	let deleted: HashSet<_> = paths.iter().map(PathBuf::from).collect();
+	sink.with_state(|state| {
+		let Some(stored) = &state.duplicate_groups else {
+			return HashMap::new();
+		};
+		let mut kept = HashMap::new();
+		for (reference, entries) in &stored.groups {
+			let members: Vec<_> =
+				reference.iter().chain(entries).map(|e| &e.path).collect();
+			let in_group: Vec<_> = paths
+				.iter()
+				.filter(|path| {
+					members.iter().any(|m| m.as_path() == Path::new(path))
+				})
+				.collect();
+			if in_group.is_empty() {
+				continue;
+			}
+			let keep = members
+				.iter()
+				.find(|path| !deleted.contains(path.as_path()))
+				.map(|path| path.to_path_buf())
+				.ok_or_else(|| {
+					"every file of its duplicate group would be deleted"
+						.to_string()
+				});
+			for path in in_group {
+				kept.insert(path.clone(), keep.clone());
+			}
+		}
+		kept
+	})
 }
 
-fn delete_files_impl(options: Options) -> DeleteFilesResult {
+struct Context<'a> {
+	kept: Option<&'a HashMap<String, Result<PathBuf, String>>>,
+	// The quarantine folder and the folder of this batch in it, when
+	// quarantining
+	quarantine: Option<(&'a Path, &'a Path)>,
+	protected: &'a ProtectedPaths,
+	stop_flag: &'a AtomicBool,
+	counters: &'a Counters,
+}
+
+fn delete_files_impl(options: Options, ctx: Context) -> DeleteFilesResult {
+	let Context {
+		kept,
+		quarantine,
+		protected,
+		stop_flag,
+		counters,
+	} = ctx;
 	let Options {
 		paths,
 		move_deleted_files_to_trash,
 		is_empty_folders_tool,
+		dry_run,
+		..
 	} = options;
 
 	paths
 		.par_iter()
 		.fold(DeleteFilesResult::default, |mut result, path_str| {
+			if stop_flag.load(Ordering::Relaxed) {
+				result.cancelled = true;
+				return result;
+			}
+			counters.entries.fetch_add(1, Ordering::Relaxed);
 			let path = Path::new(path_str);
 
 			if !path.exists() {
@@ -44,7 +223,64 @@ fn delete_files_impl(options: Options) -> DeleteFilesResult {
 				return result;
 			}
 
-			let fs_result = if is_empty_folders_tool {
+			if let Err(err) = protected.check(path) {
+				result
+					.errors
+					.push(format!("`{}` Failed, reason: {}", path_str, err));
+				return result;
+			}
+
+			if let Some(kept) = kept {
+				let same = match kept.get(path_str) {
+					Some(Ok(kept_path)) => {
+						files_equal(path, kept_path).map_err(|e| e.to_string())
+					}
+					Some(Err(reason)) => Err(reason.clone()),
+					None => Err("it is not in the last Duplicate Files result"
+						.to_string()),
+				};
+				match same {
+					Ok(true) => {}
+					Ok(false) => {
+						result.mismatched_paths.push(path_str.clone());
+						return result;
+					}
+					Err(err) => {
+						result.errors.push(format!(
+							"`{}` Failed, reason: {}",
+							path_str, err
+						));
+						return result;
+					}
+				}
+			}
+
+			if let Some((dir, batch)) = quarantine {
+				let quarantined = if dry_run {
+					check_quarantine(path, batch)
+				} else {
+					quarantine::quarantine_file(dir, batch, path)
+				};
+				match quarantined {
+					Ok(dest) => {
+						result.journal.push(JournalEntry::new(
+							Action::Quarantine,
+							path,
+							Some(dest),
+						));
+						result.success_paths.push(path_str.clone());
+					}
+					Err(err) => result.errors.push(format!(
+						"`{}` Failed, reason: {}",
+						path_str, err
+					)),
+				}
+				return result;
+			}
+
+			let fs_result = if dry_run {
+				check_delete(path, is_empty_folders_tool)
+			} else if is_empty_folders_tool {
 				remove_folder_if_contains_only_empty_folders(
 					path,
 					move_deleted_files_to_trash,
@@ -56,7 +292,15 @@ fn delete_files_impl(options: Options) -> DeleteFilesResult {
 			};
 
 			match fs_result {
-				Ok(_) => result.success_paths.push(path_str.clone()),
+				Ok(_) => {
+					let action = if move_deleted_files_to_trash {
+						Action::Trash
+					} else {
+						Action::Delete
+					};
+					result.journal.push(JournalEntry::new(action, path, None));
+					result.success_paths.push(path_str.clone());
+				}
 				Err(err) => result
 					.errors
 					.push(format!("`{}` Failed, reason: {}", path_str, err)),
@@ -65,8 +309,58 @@ fn delete_files_impl(options: Options) -> DeleteFilesResult {
 			result
 		})
 		.reduce(DeleteFilesResult::default, |mut acc, mut x| {
+			acc.cancelled |= x.cancelled;
 			acc.success_paths.append(&mut x.success_paths);
 			acc.errors.append(&mut x.errors);
+			acc.mismatched_paths.append(&mut x.mismatched_paths);
+			acc.journal.append(&mut x.journal);
 			acc
 		})
 }
+
+fn check_delete(
+	path: &Path,
+	is_empty_folders_tool: bool,
+) -> Result<(), String> {
+	preflight::check_removable(path)?;
+	if is_empty_folders_tool && has_files(path) {
+		return Err(format!("`{}` contains files", path.to_string_lossy()));
+	}
+	Ok(())
+}
+
+fn check_quarantine(path: &Path, batch: &Path) -> Result<PathBuf, String> {
+	preflight::check_removable(path)?;
+	preflight::check_writable(batch)?;
+	quarantine::quarantined_path(batch, path)
+}
+
+fn has_files(dir: &Path) -> bool {
+	fs::read_dir(dir).is_ok_and(|entries| {
+		entries.filter_map(Result::ok).any(|entry| {
+			let path = entry.path();
+			!path.is_dir() || has_files(&path)
+		})
+	})
+}
+
+fn files_equal(a: &Path, b: &Path) -> io::Result<bool> {
+	if fs::metadata(a)?.len() != fs::metadata(b)?.len() {
+		return Ok(false);
+	}
+	let mut a = BufReader::new(File::open(a)?);
+	let mut b = BufReader::new(File::open(b)?);
+	let mut buffer_a = vec![0; 64 * 1024];
+	let mut buffer_b = vec![0; 64 * 1024];
+	loop {
+		let read = a.read(&mut buffer_a)?;
+		if read == 0 {
+			// Same length, so the other file ends here too
+			return Ok(true);
+		}
+		b.read_exact(&mut buffer_b[..read])?;
+		if buffer_a[..read] != buffer_b[..read] {
+			return Ok(false);
+		}
+	}
+}
@@ -1,3 +1,5 @@
+use std::sync::atomic::Ordering;
+
 use czkawka_core::common::traits::Search;
 use czkawka_core::{
 	common::model::{CheckingMethod, HashType},
@@ -9,167 +11,185 @@ use czkawka_core::{
 };
 use rayon::prelude::*;
 use serde::Serialize;
-use tauri::{AppHandle, Emitter};
 
 use crate::{
-	scaner::{set_scaner_common_settings, spawn_scaner_thread},
+	scaner::set_scaner_common_settings,
 	settings::Settings,
-	state::get_stop_flag_and_progress_tx,
+	sink::ResultSink,
+	state::{AppState, ScanerState},
 };
 
+pub type DuplicateGroup = (Option<DuplicateEntry>, Vec<DuplicateEntry>);
+
+// The groups of the last Duplicate Files scan, kept for the operations that
+// work on them after the scan
+pub struct DuplicateGroups {
+	pub settings: Settings,
+	pub groups: Vec<DuplicateGroup>,
+}
+
+pub fn store_duplicate_groups<S: ResultSink>(
+	sink: &S,
+	settings: Settings,
+	groups: &[DuplicateGroup],
+) {
+	let groups = groups.to_vec();
+	sink.set_scaner_state(DuplicateGroups { settings, groups });
+}
+
+impl ScanerState for DuplicateGroups {
+	fn store(self, state: &mut AppState) {
+		// A watch keeps the groups of an older scan up to date
+		if let Some(stop_flag) = state.duplicate_watch.take() {
+			stop_flag.store(true, Ordering::Relaxed);
+		}
+		state.duplicate_groups = Some(self);
+	}
+}
+
 #[derive(Serialize, Clone)]
 struct ScanResult {
 	cmd: &'static str,
-	list: Vec<(Option<DuplicateEntry>, Vec<DuplicateEntry>)>,
+	list: Vec<DuplicateGroup>,
 	message: String,
 }
 
-pub fn scan_duplicate_files(app: AppHandle, settings: Settings) {
-	spawn_scaner_thread(move || {
-		let (stop_flag, progress_tx) = get_stop_flag_and_progress_tx(&app);
-
-		let hash_type =
-			match settings.duplicates_sub_available_hash_type.as_ref() {
-				"CRC32" => HashType::Crc32,
-				"XXH3" => HashType::Xxh3,
-				_ => HashType::Blake3,
-			};
-		let check_method = match settings.duplicates_sub_check_method.as_ref() {
-			"Size" => CheckingMethod::Size,
-			"Name" => CheckingMethod::Name,
-			"SizeAndName" => CheckingMethod::SizeName,
-			_ => CheckingMethod::Hash,
-		};
-		let mut scaner = DuplicateFinder::new(DuplicateFinderParameters::new(
-			check_method,
-			hash_type,
-			settings.duplicate_hide_hard_links,
-			settings.duplicate_use_prehash,
-			settings.duplicate_minimal_hash_cache_size as u64,
-			settings.duplicate_minimal_prehash_cache_size as u64,
-			settings.duplicates_sub_name_case_sensitive,
-		));
-
-		scaner.set_delete_outdated_cache(
-			settings.duplicate_delete_outdated_entries,
-		);
-		set_scaner_common_settings(&mut scaner, settings);
-
-		// v10 API: use Search::search(stop_flag, progress_sender)
-		scaner.search(&stop_flag, Some(&progress_tx));
-
-		let mut message = scaner.get_text_messages().create_messages_text();
-		let mut list;
-
-		if scaner.get_use_reference() {
-			match scaner.get_params().check_method {
-				CheckingMethod::Hash => {
-					list = scaner
-						.get_files_with_identical_hashes_referenced()
+pub fn scan_duplicate_files<S: ResultSink>(sink: &S, settings: Settings) {
+	let (stop_flag, progress_tx) = sink.stop_flag_and_progress_tx();
+
+	let hash_type = match settings.duplicates_sub_available_hash_type.as_ref() {
+		"CRC32" => HashType::Crc32,
+		"XXH3" => HashType::Xxh3,
+		_ => HashType::Blake3,
+	};
+	let check_method = match settings.duplicates_sub_check_method.as_ref() {
+		"Size" => CheckingMethod::Size,
+		"Name" => CheckingMethod::Name,
+		"SizeAndName" => CheckingMethod::SizeName,
+		_ => CheckingMethod::Hash,
+	};
+	let mut scaner = DuplicateFinder::new(DuplicateFinderParameters::new(
+		check_method,
+		hash_type,
+		settings.duplicate_hide_hard_links,
+		settings.duplicate_use_prehash,
+		settings.duplicate_minimal_hash_cache_size as u64,
+		settings.duplicate_minimal_prehash_cache_size as u64,
+		settings.duplicates_sub_name_case_sensitive,
+	));
+
+	scaner
+		.set_delete_outdated_cache(settings.duplicate_delete_outdated_entries);
+	let scan_settings = settings.clone();
+	set_scaner_common_settings(&mut scaner, settings);
+
+	// v10 API: use Search::search(stop_flag, progress_sender)
+	scaner.search(&stop_flag, Some(&progress_tx));
+
+	let mut message = scaner.get_text_messages().create_messages_text();
+	let mut list;
+
+	if scaner.get_use_reference() {
+		match scaner.get_params().check_method {
+			CheckingMethod::Hash => {
+				list = scaner
+					.get_files_with_identical_hashes_referenced()
+					.values()
+					.flatten()
+					.cloned()
+					.map(|(original, other)| (Some(original), other))
+					.collect::<Vec<_>>();
+			}
+			CheckingMethod::Name
+			| CheckingMethod::Size
+			| CheckingMethod::SizeName => {
+				let values: Vec<_> = match scaner.get_params().check_method {
+					CheckingMethod::Name => scaner
+						.get_files_with_identical_name_referenced()
 						.values()
-						.flatten()
 						.cloned()
-						.map(|(original, other)| (Some(original), other))
-						.collect::<Vec<_>>();
-				}
-				CheckingMethod::Name
-				| CheckingMethod::Size
-				| CheckingMethod::SizeName => {
-					let values: Vec<_> = match scaner.get_params().check_method
-					{
-						CheckingMethod::Name => scaner
-							.get_files_with_identical_name_referenced()
-							.values()
-							.cloned()
-							.collect(),
-						CheckingMethod::Size => scaner
-							.get_files_with_identical_size_referenced()
-							.values()
-							.cloned()
-							.collect(),
-						CheckingMethod::SizeName => scaner
-							.get_files_with_identical_size_names_referenced()
-							.values()
-							.cloned()
-							.collect(),
-						_ => unreachable!("Invalid check method."),
-					};
-					list = values
-						.into_iter()
-						.map(|(original, other)| (Some(original), other))
-						.collect::<Vec<_>>();
-				}
-				_ => unreachable!("Invalid check method."),
+						.collect(),
+					CheckingMethod::Size => scaner
+						.get_files_with_identical_size_referenced()
+						.values()
+						.cloned()
+						.collect(),
+					CheckingMethod::SizeName => scaner
+						.get_files_with_identical_size_names_referenced()
+						.values()
+						.cloned()
+						.collect(),
+					_ => unreachable!("Invalid check method."),
+				};
+				list = values
+					.into_iter()
+					.map(|(original, other)| (Some(original), other))
+					.collect::<Vec<_>>();
 			}
-		} else {
-			match scaner.get_params().check_method {
-				CheckingMethod::Hash => {
-					list = scaner
-						.get_files_sorted_by_hash()
+			_ => unreachable!("Invalid check method."),
+		}
+	} else {
+		match scaner.get_params().check_method {
+			CheckingMethod::Hash => {
+				list = scaner
+					.get_files_sorted_by_hash()
+					.values()
+					.flatten()
+					.cloned()
+					.map(|items| (None, items))
+					.collect::<Vec<_>>();
+			}
+			CheckingMethod::Name
+			| CheckingMethod::Size
+			| CheckingMethod::SizeName => {
+				let values: Vec<_> = match scaner.get_params().check_method {
+					CheckingMethod::Name => scaner
+						.get_files_sorted_by_names()
+						.values()
+						.cloned()
+						.collect(),
+					CheckingMethod::Size => scaner
+						.get_files_sorted_by_size()
+						.values()
+						.cloned()
+						.collect(),
+					CheckingMethod::SizeName => scaner
+						.get_files_sorted_by_size_name()
 						.values()
-						.flatten()
 						.cloned()
-						.map(|items| (None, items))
-						.collect::<Vec<_>>();
-				}
-				CheckingMethod::Name
-				| CheckingMethod::Size
-				| CheckingMethod::SizeName => {
-					let values: Vec<_> = match scaner.get_params().check_method
-					{
-						CheckingMethod::Name => scaner
-							.get_files_sorted_by_names()
-							.values()
-							.cloned()
-							.collect(),
-						CheckingMethod::Size => scaner
-							.get_files_sorted_by_size()
-							.values()
-							.cloned()
-							.collect(),
-						CheckingMethod::SizeName => scaner
-							.get_files_sorted_by_size_name()
-							.values()
-							.cloned()
-							.collect(),
-						_ => unreachable!("Invalid check method."),
-					};
-					list = values
-						.into_iter()
-						.map(|items| (None, items))
-						.collect::<Vec<_>>();
-				}
-				_ => unreachable!("Invalid check method."),
+						.collect(),
+					_ => unreachable!("Invalid check method."),
+				};
+				list = values
+					.into_iter()
+					.map(|items| (None, items))
+					.collect::<Vec<_>>();
 			}
+			_ => unreachable!("Invalid check method."),
 		}
+	}
 
-		for (_, vec) in &mut list {
-			vec.par_sort_unstable_by(|a, b| {
-				split_path_compare(a.path.as_path(), b.path.as_path())
-			});
-		}
+	for (_, vec) in &mut list {
+		vec.par_sort_unstable_by(|a, b| {
+			split_path_compare(a.path.as_path(), b.path.as_path())
+		});
+	}
 
-		message = format!(
-			"Found {} similar duplicates files\n{}",
-			list.len(),
-			message
-		);
-
-		app.emit(
-			"scan-result",
-			ScanResult {
-				cmd: "scan_duplicate_files",
-				list,
-				message,
-			},
-		)
-		.unwrap();
-
-		set_scaner_state(app, scaner);
+	message =
+		format!("Found {} similar duplicates files\n{}", list.len(), message);
+
+	store_duplicate_groups(sink, scan_settings, &list);
+
+	sink.emit_result(ScanResult {
+		cmd: "scan_duplicate_files",
+		list,
+		message,
 	});
+
+	sink.set_scaner_state(scaner);
 }
 
-crate::gen_set_scaner_state_fn!(
+crate::impl_scaner_state!(
 	duplication_state,
 	czkawka_core::tools::duplicate::DuplicateFinder
 );
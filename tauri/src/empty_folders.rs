@@ -5,12 +5,9 @@ use czkawka_core::{
 };
 use rayon::prelude::*;
 use serde::Serialize;
-use tauri::{AppHandle, Emitter};
 
 use crate::{
-	scaner::{set_scaner_common_settings, spawn_scaner_thread},
-	settings::Settings,
-	state::get_stop_flag_and_progress_tx,
+	scaner::set_scaner_common_settings, settings::Settings, sink::ResultSink,
 };
 
 #[derive(Serialize, Clone)]
@@ -26,54 +23,47 @@ struct ScanResult {
 	message: String,
 }
 
-pub fn scan_empty_folders(app: AppHandle, settings: Settings) {
-	spawn_scaner_thread(move || {
-		let (stop_flag, progress_tx) = get_stop_flag_and_progress_tx(&app);
+pub fn scan_empty_folders<S: ResultSink>(sink: &S, settings: Settings) {
+	let (stop_flag, progress_tx) = sink.stop_flag_and_progress_tx();
 
-		let mut scaner = EmptyFolder::new();
+	let mut scaner = EmptyFolder::new();
 
-		set_scaner_common_settings(&mut scaner, settings);
+	set_scaner_common_settings(&mut scaner, settings);
 
-		// v10 API: use Search::search(stop_flag, progress_sender)
-		scaner.search(&stop_flag, Some(&progress_tx));
+	// v10 API: use Search::search(stop_flag, progress_sender)
+	scaner.search(&stop_flag, Some(&progress_tx));
 
-		let mut raw_list = scaner
-			.get_empty_folder_list()
-			.values()
-			.cloned()
-			.collect::<Vec<_>>();
-		let mut message = scaner.get_text_messages().create_messages_text();
+	let mut raw_list = scaner
+		.get_empty_folder_list()
+		.values()
+		.cloned()
+		.collect::<Vec<_>>();
+	let mut message = scaner.get_text_messages().create_messages_text();
 
-		raw_list.par_sort_unstable_by(|a, b| {
-			split_path_compare(a.path.as_path(), b.path.as_path())
-		});
-
-		message =
-			format!("Found {} empty folders\n{}", raw_list.len(), message);
+	raw_list.par_sort_unstable_by(|a, b| {
+		split_path_compare(a.path.as_path(), b.path.as_path())
+	});
 
-		let list = raw_list
-			.into_iter()
-			.map(|item| CustomFolderEntry {
-				path: item.path.to_string_lossy().to_string(),
-				modified_date: item.modified_date,
-			})
-			.collect::<Vec<_>>();
+	message = format!("Found {} empty folders\n{}", raw_list.len(), message);
 
-		app.emit(
-			"scan-result",
-			ScanResult {
-				cmd: "scan_empty_folders",
-				list,
-				message,
-			},
-		)
-		.unwrap();
+	let list = raw_list
+		.into_iter()
+		.map(|item| CustomFolderEntry {
+			path: item.path.to_string_lossy().to_string(),
+			modified_date: item.modified_date,
+		})
+		.collect::<Vec<_>>();
 
-		set_scaner_state(app, scaner);
+	sink.emit_result(ScanResult {
+		cmd: "scan_empty_folders",
+		list,
+		message,
 	});
+
+	sink.set_scaner_state(scaner);
 }
 
-crate::gen_set_scaner_state_fn!(
+crate::impl_scaner_state!(
 	empty_folders_state,
 	czkawka_core::tools::empty_folder::EmptyFolder
 );
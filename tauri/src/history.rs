@@ -0,0 +1,275 @@
+use std::{
+	collections::BTreeMap,
+	path::Path,
+	sync::{Arc, Mutex},
+};
+
+use crossbeam_channel::{Sender, unbounded};
+use czkawka_core::common::progress_data::ProgressData;
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{scaner::Tool, settings::Settings, sink::ResultSink};
+
+const HISTORY_DB: &str = "history.db";
+
+pub type HistoryId = i64;
+
+// One scan in the history. Tools that do not group their results count
+// entries in `groups_found`. `wasted_bytes` is what deleting all but the
+// biggest file of every group (all but the reference file, when there is one)
+// would free, and the size of temporary files.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+	pub id: HistoryId,
+	pub tool: Tool,
+	pub started_at: u64,
+	pub finished_at: u64,
+	pub files_checked: u64,
+	pub bytes_checked: u64,
+	pub groups_found: u64,
+	pub wasted_bytes: u64,
+	pub message: String,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryDetails {
+	#[serde(flatten)]
+	pub entry: HistoryEntry,
+	pub settings: Settings,
+}
+
+// Counters of a scan as last reported by its progress. czkawka only reports
+// every few hundred milliseconds, so very short scans can show lower numbers
+// than they checked.
+#[derive(Default)]
+pub(crate) struct ProgressStats {
+	// Highest entries and bytes checked of every stage
+	stages: Mutex<BTreeMap<u8, (usize, u64)>>,
+}
+
+impl ProgressStats {
+	// Returns a sender that records everything sent to it before passing it
+	// on to `progress_tx`
+	pub(crate) fn track(
+		self: &Arc<Self>,
+		progress_tx: Sender<ProgressData>,
+	) -> Sender<ProgressData> {
+		let (tx, rx) = unbounded::<ProgressData>();
+		let stats = self.clone();
+		std::thread::spawn(move || {
+			for data in rx {
+				{
+					let mut stages = stats.stages.lock().unwrap();
+					let stage =
+						stages.entry(data.current_stage_idx).or_default();
+					stage.0 = stage.0.max(data.entries_checked);
+					stage.1 = stage.1.max(data.bytes_checked);
+				}
+				if progress_tx.send(data).is_err() {
+					return;
+				}
+			}
+		});
+		tx
+	}
+
+	// Files found by the collecting stage and bytes read by all stages
+	fn totals(&self) -> (u64, u64) {
+		let stages = self.stages.lock().unwrap();
+		let files = stages.get(&0).map_or(0, |stage| stage.0 as u64);
+		let bytes = stages.values().map(|stage| stage.1).sum();
+		(files, bytes)
+	}
+}
+
+fn open(data_dir: &Path) -> rusqlite::Result<Connection> {
+	let conn = Connection::open(data_dir.join(HISTORY_DB))?;
+	conn.execute(
+		"CREATE TABLE IF NOT EXISTS scans (
+			id INTEGER PRIMARY KEY AUTOINCREMENT,
+			tool TEXT NOT NULL,
+			settings TEXT NOT NULL,
+			started_at INTEGER NOT NULL,
+			finished_at INTEGER NOT NULL,
+			files_checked INTEGER NOT NULL,
+			bytes_checked INTEGER NOT NULL,
+			groups_found INTEGER NOT NULL,
+			wasted_bytes INTEGER NOT NULL,
+			message TEXT NOT NULL
+		)",
+		[],
+	)?;
+	Ok(conn)
+}
+
+fn open_for<S: ResultSink>(sink: &S) -> Result<Connection, String> {
+	let data_dir = sink.data_dir().ok_or("No data dir to keep history in")?;
+	std::fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+	open(&data_dir).map_err(|e| e.to_string())
+}
+
+pub(crate) fn record_scan(
+	data_dir: &Path,
+	settings: &Settings,
+	result: &Value,
+	stats: &ProgressStats,
+	started_at: u64,
+	finished_at: u64,
+) -> Result<HistoryId, String> {
+	let tool = result
+		.get("cmd")
+		.and_then(Value::as_str)
+		.and_then(Tool::from_cmd)
+		.ok_or("Result without a known cmd")?;
+	let (groups_found, wasted_bytes) = count_result(tool, result);
+	let (files_checked, bytes_checked) = stats.totals();
+	let message = result["message"]
+		.as_str()
+		.and_then(|message| message.lines().next())
+		.unwrap_or_default();
+	let settings =
+		serde_json::to_string(settings).map_err(|e| e.to_string())?;
+
+	std::fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+	let conn = open(data_dir).map_err(|e| e.to_string())?;
+	conn.execute(
+		"INSERT INTO scans (tool, settings, started_at, finished_at,
+			files_checked, bytes_checked, groups_found, wasted_bytes, message)
+			VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+		params![
+			tool.cmd(),
+			settings,
+			started_at,
+			finished_at,
+			files_checked,
+			bytes_checked,
+			groups_found,
+			wasted_bytes,
+			message
+		],
+	)
+	.map_err(|e| e.to_string())?;
+	Ok(conn.last_insert_rowid())
+}
+
+fn count_result(tool: Tool, result: &Value) -> (u64, u64) {
+	let Some(list) = result["list"].as_array() else {
+		return (0, 0);
+	};
+	let size = |entry: &Value| entry["size"].as_u64().unwrap_or(0);
+
+	let wasted = match tool {
+		Tool::DuplicateFiles
+		| Tool::SimilarImages
+		| Tool::SimilarVideos
+		| Tool::MusicDuplicates => list
+			.iter()
+			.map(|group| {
+				let entries =
+					group[1].as_array().map_or(&[][..], Vec::as_slice);
+				let total: u64 = entries.iter().map(size).sum();
+				if group[0].is_null() {
+					total - entries.iter().map(size).max().unwrap_or(0)
+				} else {
+					total
+				}
+			})
+			.sum(),
+		Tool::TemporaryFiles => list.iter().map(size).sum(),
+		_ => 0,
+	};
+	(list.len() as u64, wasted)
+}
+
+const ENTRY_COLUMNS: &str = "id, tool, started_at, finished_at, files_checked,
+	bytes_checked, groups_found, wasted_bytes, message";
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<HistoryEntry> {
+	let tool: String = row.get(1)?;
+	Ok(HistoryEntry {
+		id: row.get(0)?,
+		tool: Tool::from_cmd(&tool).ok_or_else(|| {
+			rusqlite::Error::InvalidColumnType(
+				1,
+				"tool".to_string(),
+				rusqlite::types::Type::Text,
+			)
+		})?,
+		started_at: row.get(2)?,
+		finished_at: row.get(3)?,
+		files_checked: row.get(4)?,
+		bytes_checked: row.get(5)?,
+		groups_found: row.get(6)?,
+		wasted_bytes: row.get(7)?,
+		message: row.get(8)?,
+	})
+}
+
+// Newest scans first, optionally only those of one tool
+pub fn list_history<S: ResultSink>(
+	sink: &S,
+	tool: Option<Tool>,
+	limit: Option<usize>,
+) -> Result<Vec<HistoryEntry>, String> {
+	let conn = open_for(sink)?;
+	let mut stmt = conn
+		.prepare(&format!(
+			"SELECT {} FROM scans WHERE ?1 IS NULL OR tool = ?1
+				ORDER BY id DESC LIMIT ?2",
+			ENTRY_COLUMNS
+		))
+		.map_err(|e| e.to_string())?;
+	let limit = limit.map_or(-1, |limit| limit as i64);
+	stmt.query_map(params![tool.map(Tool::cmd), limit], entry_from_row)
+		.and_then(|rows| rows.collect())
+		.map_err(|e| e.to_string())
+}
+
+pub fn get_history_entry<S: ResultSink>(
+	sink: &S,
+	id: HistoryId,
+) -> Result<Option<HistoryDetails>, String> {
+	let conn = open_for(sink)?;
+	let details = conn
+		.query_row(
+			&format!(
+				"SELECT {}, settings FROM scans WHERE id = ?",
+				ENTRY_COLUMNS
+			),
+			params![id],
+			|row| Ok((entry_from_row(row)?, row.get::<_, String>(9)?)),
+		)
+		.optional()
+		.map_err(|e| e.to_string())?;
+
+	details
+		.map(|(entry, settings)| {
+			Ok(HistoryDetails {
+				entry,
+				settings: serde_json::from_str(&settings)
+					.map_err(|e| e.to_string())?,
+			})
+		})
+		.transpose()
+}
+
+// Returns how many entries were deleted
+pub fn delete_history_entries<S: ResultSink>(
+	sink: &S,
+	ids: &[HistoryId],
+) -> Result<usize, String> {
+	let mut conn = open_for(sink)?;
+	let tx = conn.transaction().map_err(|e| e.to_string())?;
+	let mut deleted = 0;
+	for id in ids {
+		deleted += tx
+			.execute("DELETE FROM scans WHERE id = ?", params![id])
+			.map_err(|e| e.to_string())?;
+	}
+	tx.commit().map_err(|e| e.to_string())?;
+	Ok(deleted)
+}
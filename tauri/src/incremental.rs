@@ -0,0 +1,159 @@
+use std::{collections::HashMap, fs, path::Path, sync::atomic::Ordering};
+
+use czkawka_core::common::{progress_data::CurrentStage, split_path_compare};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::{
+	duplicate_files::{DuplicateGroup, store_duplicate_groups},
+	persist::PersistSink,
+	scaner::{Tool, set_scan_directories},
+	settings::Settings,
+	sink::ResultSink,
+	sweep::{HashCache, find_duplicates, with_progress},
+	walk::{WalkFilters, WalkSnapshot, walk_incremental},
+};
+
+const SNAPSHOT_PATH: &str = "incremental/duplicate_files.json";
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Snapshot {
+	// Settings deciding which folders and files get walked
+	walk_key: Value,
+	hash_type: String,
+	folders: WalkSnapshot,
+	hashes: HashCache,
+}
+
+#[derive(Serialize, Clone)]
+struct ScanResult {
+	cmd: &'static str,
+	list: Vec<DuplicateGroup>,
+	message: String,
+}
+
+fn walk_key(settings: &Settings) -> Value {
+	json!({
+		"includedDirectories": settings.included_directories,
+		"excludedDirectories": settings.excluded_directories,
+		"excludedItems": settings.excluded_items,
+		"recursiveSearch": settings.recursive_search,
+	})
+}
+
+fn load_snapshot(path: &Path) -> Option<Snapshot> {
+	let data = fs::read(path).ok()?;
+	serde_json::from_slice(&data).ok()
+}
+
+fn save_snapshot(path: &Path, snapshot: &Snapshot) -> Result<(), String> {
+	fs::create_dir_all(path.parent().unwrap()).map_err(|e| e.to_string())?;
+	let tmp_path = path.with_extension("json.tmp");
+	let data = serde_json::to_vec(snapshot).map_err(|e| e.to_string())?;
+	fs::write(&tmp_path, data).map_err(|e| e.to_string())?;
+	fs::rename(&tmp_path, path).map_err(|e| e.to_string())
+}
+
+// Duplicate Files scan that starts from the snapshot of the previous rescan:
+// unchanged folders are not read again and unchanged files are not hashed
+// again. Without a data dir every rescan is a full one.
+pub fn rescan_duplicate_files<S: ResultSink>(sink: &S, settings: Settings) {
+	set_scan_directories(sink, &[Tool::DuplicateFiles], &settings);
+	let sink = &PersistSink::new(sink.clone(), settings.clone());
+	let (stop_flag, progress_tx) = sink.stop_flag_and_progress_tx();
+	let filters = WalkFilters::from_settings(&settings);
+	let walk_key = walk_key(&settings);
+	let hash_type = settings.duplicates_sub_available_hash_type.clone();
+
+	let snapshot_path = sink.data_dir().map(|dir| dir.join(SNAPSHOT_PATH));
+	let previous = snapshot_path
+		.as_deref()
+		.and_then(load_snapshot)
+		.filter(|snapshot| snapshot.walk_key == walk_key);
+	let (folders, mut hashes) = match previous {
+		Some(snapshot) if snapshot.hash_type == hash_type => {
+			(snapshot.folders, snapshot.hashes)
+		}
+		Some(snapshot) => (snapshot.folders, HashCache::new()),
+		None => (WalkSnapshot::new(), HashCache::new()),
+	};
+
+	let walk = with_progress(
+		&progress_tx,
+		CurrentStage::CollectingFiles,
+		0,
+		(0, 0),
+		|counters| {
+			walk_incremental(&filters, &folders, &stop_flag, &counters.entries)
+		},
+	);
+	drop(folders);
+
+	let Some(walk) = walk else {
+		sink.emit_result(ScanResult {
+			cmd: "scan_duplicate_files",
+			list: vec![],
+			message: "Scan stopped".to_string(),
+		});
+		return;
+	};
+
+	// Hashes of files that were removed or changed since the last rescan
+	let current: HashMap<_, _> = walk
+		.walk
+		.files
+		.iter()
+		.map(|file| (&file.path, (file.size, file.modified_date)))
+		.collect();
+	hashes.retain(|path, cached| {
+		current.get(path) == Some(&(cached.size, cached.modified_date))
+	});
+	drop(current);
+
+	let mut warnings = walk.walk.warnings;
+	let mut list = find_duplicates(
+		&walk.walk.files,
+		&filters,
+		&settings,
+		&stop_flag,
+		&progress_tx,
+		&mut warnings,
+		&mut hashes,
+	);
+	list.sort_by(|(_, a), (_, b)| {
+		split_path_compare(a[0].path.as_path(), b[0].path.as_path())
+	});
+
+	if let Some(snapshot_path) = snapshot_path {
+		let snapshot = Snapshot {
+			walk_key,
+			hash_type,
+			folders: walk.snapshot,
+			hashes,
+		};
+		if let Err(e) = save_snapshot(&snapshot_path, &snapshot) {
+			warnings.push(format!("Failed to save rescan snapshot: {}", e));
+		}
+	}
+
+	let message = format!(
+		"Found {} similar duplicates files\nRead {} changed folders, reused {} unchanged folders{}\n{}",
+		list.len(),
+		walk.read_folders,
+		walk.reused_folders,
+		if stop_flag.load(Ordering::Relaxed) {
+			" (stopped)"
+		} else {
+			""
+		},
+		warnings.join("\n")
+	);
+
+	store_duplicate_groups(sink, settings, &list);
+	sink.emit_result(ScanResult {
+		cmd: "scan_duplicate_files",
+		list,
+		message,
+	});
+}
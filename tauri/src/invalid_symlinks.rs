@@ -5,12 +5,9 @@ use czkawka_core::{
 };
 use rayon::prelude::*;
 use serde::Serialize;
-use tauri::{AppHandle, Emitter};
 
 use crate::{
-	scaner::{set_scaner_common_settings, spawn_scaner_thread},
-	settings::Settings,
-	state::get_stop_flag_and_progress_tx,
+	scaner::set_scaner_common_settings, settings::Settings, sink::ResultSink,
 };
 
 #[derive(Serialize, Clone)]
@@ -20,41 +17,35 @@ struct ScanResult {
 	message: String,
 }
 
-pub fn scan_invalid_symlinks(app: AppHandle, settings: Settings) {
-	spawn_scaner_thread(move || {
-		let (stop_flag, progress_tx) = get_stop_flag_and_progress_tx(&app);
+pub fn scan_invalid_symlinks<S: ResultSink>(sink: &S, settings: Settings) {
+	let (stop_flag, progress_tx) = sink.stop_flag_and_progress_tx();
 
-		let mut scaner = InvalidSymlinks::new();
+	let mut scaner = InvalidSymlinks::new();
 
-		set_scaner_common_settings(&mut scaner, settings);
+	set_scaner_common_settings(&mut scaner, settings);
 
-		// v10 API: use Search::search(stop_flag, progress_sender)
-		scaner.search(&stop_flag, Some(&progress_tx));
+	// v10 API: use Search::search(stop_flag, progress_sender)
+	scaner.search(&stop_flag, Some(&progress_tx));
 
-		let mut list = scaner.get_invalid_symlinks().clone();
-		let mut message = scaner.get_text_messages().create_messages_text();
+	let mut list = scaner.get_invalid_symlinks().clone();
+	let mut message = scaner.get_text_messages().create_messages_text();
 
-		list.par_sort_unstable_by(|a, b| {
-			split_path_compare(a.path.as_path(), b.path.as_path())
-		});
-
-		message = format!("Found {} invalid symlinks\n{}", list.len(), message);
+	list.par_sort_unstable_by(|a, b| {
+		split_path_compare(a.path.as_path(), b.path.as_path())
+	});
 
-		app.emit(
-			"scan-result",
-			ScanResult {
-				cmd: "scan_invalid_symlinks",
-				list,
-				message,
-			},
-		)
-		.unwrap();
+	message = format!("Found {} invalid symlinks\n{}", list.len(), message);
 
-		set_scaner_state(app, scaner);
+	sink.emit_result(ScanResult {
+		cmd: "scan_invalid_symlinks",
+		list,
+		message,
 	});
+
+	sink.set_scaner_state(scaner);
 }
 
-crate::gen_set_scaner_state_fn!(
+crate::impl_scaner_state!(
 	same_invalid_symlinks,
 	czkawka_core::tools::invalid_symlinks::InvalidSymlinks
 );
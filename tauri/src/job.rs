@@ -0,0 +1,313 @@
+use std::{
+	collections::BTreeMap,
+	path::PathBuf,
+	sync::{
+		Arc, Mutex,
+		atomic::{AtomicBool, Ordering},
+	},
+};
+
+use crossbeam_channel::{Sender, unbounded};
+use czkawka_core::common::progress_data::ProgressData;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	progress::{ProgressToSend, process_progress_data},
+	scaner::{Tool, run_tool},
+	settings::Settings,
+	sink::ResultSink,
+	state::{AppState, ScanerState},
+	utils::unix_now,
+};
+
+pub type JobId = u64;
+
+// Finished jobs are kept so their status can still be queried
+const MAX_FINISHED_JOBS: usize = 100;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum JobStatus {
+	Running,
+	Cancelled,
+	Finished,
+}
+
+pub struct JobProgress {
+	pub job_id: Option<JobId>,
+	pub data: ProgressData,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JobProgressToSend {
+	pub job_id: Option<JobId>,
+	#[serde(flatten)]
+	pub progress: ProgressToSend,
+}
+
+impl JobProgress {
+	pub fn process(self) -> JobProgressToSend {
+		JobProgressToSend {
+			job_id: self.job_id,
+			progress: process_progress_data(self.data),
+		}
+	}
+}
+
+#[derive(Clone)]
+pub struct JobHandle {
+	pub id: JobId,
+	pub tools: Vec<Tool>,
+	pub stop_flag: Arc<AtomicBool>,
+	last_progress: Arc<Mutex<Option<ProgressData>>>,
+}
+
+struct Job {
+	handle: JobHandle,
+	// Set for file operations, which run as jobs without tools
+	operation: Option<&'static str>,
+	status: JobStatus,
+	started_at: u64,
+	finished_at: Option<u64>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+	pub id: JobId,
+	pub tools: Vec<Tool>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub operation: Option<&'static str>,
+	pub status: JobStatus,
+	pub started_at: u64,
+	pub finished_at: Option<u64>,
+	pub progress: Option<ProgressToSend>,
+}
+
+#[derive(Default)]
+pub struct Jobs {
+	next_id: JobId,
+	jobs: BTreeMap<JobId, Job>,
+}
+
+impl Jobs {
+	pub fn start(&mut self, tools: Vec<Tool>) -> JobHandle {
+		self.start_with(tools, None)
+	}
+
+	// A job for a file operation such as "move_files", so it can report
+	// progress and be cancelled like a scan
+	pub fn start_operation(&mut self, operation: &'static str) -> JobHandle {
+		self.start_with(vec![], Some(operation))
+	}
+
+	fn start_with(
+		&mut self,
+		tools: Vec<Tool>,
+		operation: Option<&'static str>,
+	) -> JobHandle {
+		self.next_id += 1;
+		let handle = JobHandle {
+			id: self.next_id,
+			tools,
+			stop_flag: Arc::new(AtomicBool::new(false)),
+			last_progress: Arc::new(Mutex::new(None)),
+		};
+		self.jobs.insert(
+			handle.id,
+			Job {
+				handle: handle.clone(),
+				operation,
+				status: JobStatus::Running,
+				started_at: unix_now(),
+				finished_at: None,
+			},
+		);
+		handle
+	}
+
+	pub fn finish(&mut self, id: JobId) {
+		if let Some(job) = self.jobs.get_mut(&id) {
+			job.status = if job.handle.stop_flag.load(Ordering::Relaxed) {
+				JobStatus::Cancelled
+			} else {
+				JobStatus::Finished
+			};
+			job.finished_at = Some(unix_now());
+		}
+		self.prune();
+	}
+
+	pub fn cancel(&mut self, id: JobId) -> bool {
+		match self.jobs.get(&id) {
+			Some(job) if job.status == JobStatus::Running => {
+				job.handle.stop_flag.store(true, Ordering::Relaxed);
+				true
+			}
+			_ => false,
+		}
+	}
+
+	// Stops every running scan, file operations are only cancelled by id
+	pub fn cancel_all(&mut self) {
+		for job in self.jobs.values() {
+			if job.status == JobStatus::Running && job.operation.is_none() {
+				job.handle.stop_flag.store(true, Ordering::Relaxed);
+			}
+		}
+	}
+
+	pub fn info(&self, id: JobId) -> Option<JobInfo> {
+		self.jobs.get(&id).map(job_info)
+	}
+
+	pub fn list(&self) -> Vec<JobInfo> {
+		self.jobs.values().map(job_info).collect()
+	}
+
+	fn prune(&mut self) {
+		let finished: Vec<_> = self
+			.jobs
+			.iter()
+			.filter(|(_, job)| job.status != JobStatus::Running)
+			.map(|(id, _)| *id)
+			.collect();
+		if finished.len() > MAX_FINISHED_JOBS {
+			for id in &finished[..finished.len() - MAX_FINISHED_JOBS] {
+				self.jobs.remove(id);
+			}
+		}
+	}
+}
+
+fn job_info(job: &Job) -> JobInfo {
+	let progress = *job.handle.last_progress.lock().unwrap();
+	JobInfo {
+		id: job.handle.id,
+		tools: job.handle.tools.clone(),
+		operation: job.operation,
+		status: job.status,
+		started_at: job.started_at,
+		finished_at: job.finished_at,
+		progress: progress.map(process_progress_data),
+	}
+}
+
+// czkawka reports progress through a plain `ProgressData` channel, so every
+// scan gets its own one whose messages are tagged and forwarded into the
+// shared channel of `AppState`
+pub fn forward_progress(
+	job_id: Option<JobId>,
+	progress_tx: Sender<JobProgress>,
+	last_progress: Option<Arc<Mutex<Option<ProgressData>>>>,
+) -> Sender<ProgressData> {
+	let (tx, rx) = unbounded::<ProgressData>();
+
+	std::thread::spawn(move || {
+		for data in rx {
+			if let Some(last_progress) = &last_progress {
+				*last_progress.lock().unwrap() = Some(data);
+			}
+			if progress_tx.send(JobProgress { job_id, data }).is_err() {
+				return;
+			}
+		}
+	});
+
+	tx
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JobResult<T> {
+	job_id: JobId,
+	#[serde(flatten)]
+	result: T,
+}
+
+// Runs a scan on behalf of a job: the job's own stop flag is used and its
+// progress and result are tagged with the job id
+#[derive(Clone)]
+pub struct JobSink<S> {
+	sink: S,
+	job: JobHandle,
+}
+
+impl<S: ResultSink> ResultSink for JobSink<S> {
+	fn with_state<R>(&self, f: impl FnOnce(&mut AppState) -> R) -> R {
+		self.sink.with_state(f)
+	}
+
+	fn emit_event<T: Serialize + Clone>(&self, event: &str, payload: T) {
+		self.sink.emit_event(event, payload);
+	}
+
+	fn data_dir(&self) -> Option<PathBuf> {
+		self.sink.data_dir()
+	}
+
+	fn results_dir(&self) -> Option<PathBuf> {
+		self.sink.results_dir()
+	}
+
+	fn config_dir(&self) -> Option<PathBuf> {
+		self.sink.config_dir()
+	}
+
+	fn emit_result<T: Serialize + Clone>(&self, result: T) {
+		self.sink.emit_result(JobResult {
+			job_id: self.job.id,
+			result,
+		});
+	}
+
+	fn set_scaner_state<T: ScanerState>(&self, scaner: T) {
+		self.sink.set_scaner_state(scaner);
+	}
+
+	fn stop_flag_and_progress_tx(
+		&self,
+	) -> (Arc<AtomicBool>, Sender<ProgressData>) {
+		let progress_tx = self.with_state(|state| state.progress_tx.clone());
+		(
+			self.job.stop_flag.clone(),
+			forward_progress(
+				Some(self.job.id),
+				progress_tx,
+				Some(self.job.last_progress.clone()),
+			),
+		)
+	}
+}
+
+pub fn start_job<S: ResultSink>(sink: &S, tools: Vec<Tool>) -> JobHandle {
+	sink.with_state(|state| state.jobs.start(tools))
+}
+
+// Runs every tool of the job one after another
+pub fn run_job<S: ResultSink>(sink: &S, job: JobHandle, settings: Settings) {
+	let tools = job.tools.clone();
+	run_job_with(sink, job, |job_sink| {
+		for tool in tools {
+			run_tool(job_sink, tool, settings.clone());
+		}
+	});
+}
+
+pub fn run_job_with<S: ResultSink, R>(
+	sink: &S,
+	job: JobHandle,
+	f: impl FnOnce(&JobSink<S>) -> R,
+) -> R {
+	let id = job.id;
+	let job_sink = JobSink {
+		sink: sink.clone(),
+		job,
+	};
+
+	let result = f(&job_sink);
+
+	sink.with_state(|state| state.jobs.finish(id));
+	result
+}
@@ -0,0 +1,476 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	protected_paths::ProtectedPaths, sink::ResultSink, utils::unix_now,
+};
+
+const JOURNAL_DB: &str = "journal.db";
+
+pub type OperationId = i64;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum Action {
+	// Moved to the trash
+	Trash,
+	// Deleted permanently, cannot be undone
+	Delete,
+	Move,
+	Copy,
+	Rename,
+	// Moved into the quarantine by delete_files
+	Quarantine,
+}
+
+impl Action {
+	fn as_str(self) -> &'static str {
+		match self {
+			Action::Trash => "trash",
+			Action::Delete => "delete",
+			Action::Move => "move",
+			Action::Copy => "copy",
+			Action::Rename => "rename",
+			Action::Quarantine => "quarantine",
+		}
+	}
+
+	fn parse(action: &str) -> Option<Self> {
+		[
+			Action::Trash,
+			Action::Delete,
+			Action::Move,
+			Action::Copy,
+			Action::Rename,
+			Action::Quarantine,
+		]
+		.into_iter()
+		.find(|a| a.as_str() == action)
+	}
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+	pub action: Action,
+	pub source: PathBuf,
+	pub destination: Option<PathBuf>,
+	pub timestamp: u64,
+	pub undone: bool,
+	// The size and newest modification time of a copy when it was made, an
+	// undo leaves copies changed since then alone
+	#[serde(skip)]
+	pub(crate) fingerprint: Option<(u64, u64)>,
+}
+
+impl JournalEntry {
+	pub(crate) fn new(
+		action: Action,
+		source: impl Into<PathBuf>,
+		destination: Option<PathBuf>,
+	) -> Self {
+		JournalEntry {
+			action,
+			source: source.into(),
+			destination,
+			timestamp: unix_now(),
+			undone: false,
+			fingerprint: None,
+		}
+	}
+}
+
+// The total size and the newest modification time, in nanoseconds, of the
+// files in `path`
+pub(crate) fn fingerprint(path: &Path) -> std::io::Result<(u64, u64)> {
+	let metadata = fs::symlink_metadata(path)?;
+	let modified = metadata
+		.modified()?
+		.duration_since(std::time::UNIX_EPOCH)
+		.map_or(0, |time| time.as_nanos() as u64);
+	if !metadata.is_dir() {
+		return Ok((metadata.len(), modified));
+	}
+	let mut fingerprint = (0, modified);
+	for entry in fs::read_dir(path)? {
+		let (size, modified) = self::fingerprint(&entry?.path())?;
+		fingerprint = (fingerprint.0 + size, fingerprint.1.max(modified));
+	}
+	Ok(fingerprint)
+}
+
+// What a dry run would do
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedAction {
+	pub action: Action,
+	pub source: PathBuf,
+	pub destination: Option<PathBuf>,
+}
+
+impl From<JournalEntry> for PlannedAction {
+	fn from(entry: JournalEntry) -> Self {
+		PlannedAction {
+			action: entry.action,
+			source: entry.source,
+			destination: entry.destination,
+		}
+	}
+}
+
+// One batch of file operations, `kind` is the command that ran it
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationInfo {
+	pub id: OperationId,
+	pub kind: String,
+	pub created_at: u64,
+	pub entries: usize,
+	// Set once every entry has been undone
+	pub undone_at: Option<u64>,
+}
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoResult {
+	pub operation_id: OperationId,
+	pub success_paths: Vec<String>,
+	pub errors: Vec<String>,
+}
+
+fn open<S: ResultSink>(sink: &S) -> Result<Connection, String> {
+	let data_dir = sink.data_dir().ok_or("No data dir to keep a journal in")?;
+	fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+	let conn = Connection::open(data_dir.join(JOURNAL_DB))
+		.map_err(|e| e.to_string())?;
+	conn.execute_batch(
+		"CREATE TABLE IF NOT EXISTS operations (
+			id INTEGER PRIMARY KEY AUTOINCREMENT,
+			kind TEXT NOT NULL,
+			created_at INTEGER NOT NULL,
+			undone_at INTEGER
+		);
+		CREATE TABLE IF NOT EXISTS entries (
+			id INTEGER PRIMARY KEY AUTOINCREMENT,
+			operation_id INTEGER NOT NULL,
+			action TEXT NOT NULL,
+			source TEXT NOT NULL,
+			destination TEXT,
+			timestamp INTEGER NOT NULL,
+			undone INTEGER NOT NULL DEFAULT 0,
+			size INTEGER,
+			modified INTEGER
+		);
+		CREATE INDEX IF NOT EXISTS idx_entries_operation
+			ON entries(operation_id);",
+	)
+	.map_err(|e| e.to_string())?;
+	// Journals written before copies had a fingerprint
+	if conn.prepare("SELECT size FROM entries LIMIT 0").is_err() {
+		conn.execute_batch(
+			"ALTER TABLE entries ADD COLUMN size INTEGER;
+			ALTER TABLE entries ADD COLUMN modified INTEGER;",
+		)
+		.map_err(|e| e.to_string())?;
+	}
+	Ok(conn)
+}
+
+// Writes a batch into the journal. Nothing is recorded without a data dir or
+// without entries, and a failing journal never fails the operation itself.
+pub(crate) fn record_operation<S: ResultSink>(
+	sink: &S,
+	kind: &str,
+	entries: &[JournalEntry],
+) -> Option<OperationId> {
+	if entries.is_empty() || sink.data_dir().is_none() {
+		return None;
+	}
+	let recorded = open(sink).and_then(|mut conn| {
+		let tx = conn.transaction().map_err(|e| e.to_string())?;
+		tx.execute(
+			"INSERT INTO operations (kind, created_at) VALUES (?, ?)",
+			params![kind, unix_now()],
+		)
+		.map_err(|e| e.to_string())?;
+		let id = tx.last_insert_rowid();
+		{
+			let mut stmt = tx
+				.prepare(
+					"INSERT INTO entries
+						(operation_id, action, source, destination, timestamp,
+							size, modified)
+						VALUES (?, ?, ?, ?, ?, ?, ?)",
+				)
+				.map_err(|e| e.to_string())?;
+			for entry in entries {
+				stmt.execute(params![
+					id,
+					entry.action.as_str(),
+					entry.source.to_string_lossy(),
+					entry
+						.destination
+						.as_ref()
+						.map(|path| path.to_string_lossy()),
+					entry.timestamp,
+					entry.fingerprint.map(|(size, _)| size),
+					entry.fingerprint.map(|(_, modified)| modified),
+				])
+				.map_err(|e| e.to_string())?;
+			}
+		}
+		tx.commit().map_err(|e| e.to_string())?;
+		Ok(id)
+	});
+	match recorded {
+		Ok(id) => Some(id),
+		Err(e) => {
+			eprintln!("Failed to record operation in the journal: {}", e);
+			None
+		}
+	}
+}
+
+// Newest operations first
+pub fn list_operations<S: ResultSink>(
+	sink: &S,
+	limit: Option<usize>,
+) -> Result<Vec<OperationInfo>, String> {
+	let conn = open(sink)?;
+	let mut stmt = conn
+		.prepare(
+			"SELECT operations.id, kind, created_at, undone_at, COUNT(entries.id)
+				FROM operations LEFT JOIN entries
+					ON entries.operation_id = operations.id
+				GROUP BY operations.id
+				ORDER BY operations.id DESC LIMIT ?",
+		)
+		.map_err(|e| e.to_string())?;
+	let limit = limit.map_or(-1, |limit| limit as i64);
+	stmt.query_map(params![limit], |row| {
+		Ok(OperationInfo {
+			id: row.get(0)?,
+			kind: row.get(1)?,
+			created_at: row.get(2)?,
+			undone_at: row.get(3)?,
+			entries: row.get(4)?,
+		})
+	})
+	.and_then(|rows| rows.collect())
+	.map_err(|e| e.to_string())
+}
+
+pub fn operation_entries<S: ResultSink>(
+	sink: &S,
+	id: OperationId,
+) -> Result<Vec<JournalEntry>, String> {
+	load_entries(&open(sink)?, id)
+		.map(|entries| entries.into_iter().map(|(_, entry)| entry).collect())
+}
+
+fn load_entries(
+	conn: &Connection,
+	id: OperationId,
+) -> Result<Vec<(i64, JournalEntry)>, String> {
+	let mut stmt = conn
+		.prepare(
+			"SELECT id, action, source, destination, timestamp, undone, size,
+					modified
+				FROM entries WHERE operation_id = ? ORDER BY id",
+		)
+		.map_err(|e| e.to_string())?;
+	stmt.query_map(params![id], |row| {
+		let action: String = row.get(1)?;
+		let source: String = row.get(2)?;
+		let destination: Option<String> = row.get(3)?;
+		let size: Option<u64> = row.get(6)?;
+		let modified: Option<u64> = row.get(7)?;
+		Ok((
+			row.get(0)?,
+			JournalEntry {
+				action: Action::parse(&action).ok_or_else(|| {
+					rusqlite::Error::InvalidColumnType(
+						1,
+						"action".to_string(),
+						rusqlite::types::Type::Text,
+					)
+				})?,
+				source: PathBuf::from(source),
+				destination: destination.map(PathBuf::from),
+				timestamp: row.get(4)?,
+				undone: row.get(5)?,
+				fingerprint: size.zip(modified),
+			},
+		))
+	})
+	.and_then(|rows| rows.collect())
+	.map_err(|e| e.to_string())
+}
+
+pub fn undo_operation<S: ResultSink>(sink: &S, id: OperationId) {
+	let sink = sink.clone();
+	std::thread::spawn(move || {
+		let result = undo_operation_impl(&sink, id);
+		sink.emit_event("undo-result", result);
+	});
+}
+
+// Reverses the entries of an operation, the last one first. Entries that
+// were undone before are skipped, so an undo that partly failed can be tried
+// again.
+fn undo_operation_impl<S: ResultSink>(sink: &S, id: OperationId) -> UndoResult {
+	let mut result = UndoResult {
+		operation_id: id,
+		..UndoResult::default()
+	};
+	let undone = open(sink).and_then(|conn| {
+		let entries = load_entries(&conn, id)?;
+		if entries.is_empty() {
+			return Err(format!("No operation with id {}", id));
+		}
+		let undone_at: Option<u64> = conn
+			.query_row(
+				"SELECT undone_at FROM operations WHERE id = ?",
+				params![id],
+				|row| row.get(0),
+			)
+			.map_err(|e| e.to_string())?;
+		if undone_at.is_some() {
+			return Err(format!("Operation {} was already undone", id));
+		}
+
+		let protected = ProtectedPaths::load(sink);
+		// Every entry is marked as soon as its file is back, a transaction
+		// over the whole undo would forget the files it did restore when a
+		// later step fails, and a retry would restore them a second time
+		for (entry_id, entry) in entries.iter().rev() {
+			if entry.undone {
+				continue;
+			}
+			let source = entry.source.to_string_lossy().to_string();
+			match undo_entry(entry, &protected) {
+				Ok(()) => {
+					conn.execute(
+						"UPDATE entries SET undone = 1 WHERE id = ?",
+						params![entry_id],
+					)
+					.map_err(|e| {
+						format!(
+							"`{}` was restored, but could not be marked as \
+							 undone, reason: {}",
+							source, e
+						)
+					})?;
+					result.success_paths.push(source);
+				}
+				Err(e) => result
+					.errors
+					.push(format!("`{}` Failed, reason: {}", source, e)),
+			}
+		}
+		if result.errors.is_empty() {
+			conn.execute(
+				"UPDATE operations SET undone_at = ? WHERE id = ?",
+				params![unix_now(), id],
+			)
+			.map_err(|e| e.to_string())?;
+		}
+		Ok(())
+	});
+	if let Err(e) = undone {
+		result.errors.push(e);
+	}
+	result
+}
+
+fn undo_entry(
+	entry: &JournalEntry,
+	protected: &ProtectedPaths,
+) -> Result<(), String> {
+	let source = entry.source.as_path();
+	let destination = entry.destination.as_deref();
+
+	match (entry.action, destination) {
+		(Action::Trash, _) => restore_from_trash(source, entry.timestamp),
+		(Action::Delete, _) => Err("it was deleted permanently".to_string()),
+		(Action::Move | Action::Rename, Some(destination)) => {
+			if source.exists() {
+				return Err("the original path exists again".to_string());
+			}
+			if let Some(parent) = source.parent() {
+				fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+			}
+			if entry.action == Action::Rename {
+				fs::rename(destination, source).map_err(|e| e.to_string())
+			} else {
+				crate::move_files::move_item(destination, source)
+					.map_err(|e| e.to_string())
+			}
+		}
+		(Action::Quarantine, Some(destination)) => {
+			crate::quarantine::undo_quarantine(destination)
+		}
+		(Action::Copy, Some(destination)) => {
+			protected.check(destination)?;
+			// The copy is deleted permanently, so only while it is unchanged
+			let current =
+				fingerprint(destination).map_err(|e| e.to_string())?;
+			match entry.fingerprint {
+				Some(fingerprint) if fingerprint == current => {}
+				Some(_) => {
+					return Err(
+						"the copy was changed since it was made".to_string()
+					);
+				}
+				None => {
+					return Err(
+						"the copy cannot be checked for changes".to_string()
+					);
+				}
+			}
+			if destination.is_dir() {
+				fs::remove_dir_all(destination)
+			} else {
+				fs::remove_file(destination)
+			}
+			.map_err(|e| e.to_string())
+		}
+		(_, None) => Err("the journal entry has no destination".to_string()),
+	}
+}
+
+#[cfg(any(
+	target_os = "windows",
+	all(unix, not(target_os = "macos"), not(target_os = "android"))
+))]
+fn restore_from_trash(path: &Path, trashed_at: u64) -> Result<(), String> {
+	if path.exists() {
+		return Err("the original path exists again".to_string());
+	}
+	// The same path may have been trashed more than once, take the item
+	// trashed closest to the recorded time
+	let item = trash::os_limited::list()
+		.map_err(|e| e.to_string())?
+		.into_iter()
+		.filter(|item| item.original_path() == path)
+		.min_by_key(|item| item.time_deleted.abs_diff(trashed_at as i64))
+		.ok_or("it is no longer in the trash")?;
+	trash::os_limited::restore_all([item]).map_err(|e| e.to_string())
+}
+
+#[cfg(not(any(
+	target_os = "windows",
+	all(unix, not(target_os = "macos"), not(target_os = "android"))
+)))]
+fn restore_from_trash(_path: &Path, _trashed_at: u64) -> Result<(), String> {
+	Err(
+		"restoring from the trash is not supported on this platform, \
+		it can be put back from the trash by hand"
+			.to_string(),
+	)
+}
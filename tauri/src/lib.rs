@@ -0,0 +1,40 @@
+pub mod bad_extensions;
+pub mod big_files;
+pub mod broken_files;
+pub mod cron;
+pub mod delete_files;
+pub mod duplicate_files;
+pub mod empty_files;
+pub mod empty_folders;
+pub mod history;
+pub mod image;
+pub mod incremental;
+pub mod invalid_symlinks;
+pub mod job;
+pub mod journal;
+pub mod link_duplicates;
+pub mod move_files;
+pub mod music_duplicates;
+pub mod persist;
+pub mod policy;
+pub mod preflight;
+pub mod progress;
+pub mod protected_paths;
+pub mod quarantine;
+pub mod queue;
+pub mod rename_ext;
+pub mod save_result;
+pub mod scaner;
+pub mod schedule;
+pub mod select;
+pub mod settings;
+pub mod similar_images;
+pub mod similar_videos;
+pub mod sink;
+pub mod state;
+pub mod sweep;
+pub mod temporary_files;
+pub mod thumbnail;
+pub mod utils;
+pub mod walk;
+pub mod watch;
@@ -0,0 +1,340 @@
+use std::{
+	collections::HashSet,
+	fs, io,
+	path::{Path, PathBuf},
+	sync::{
+		Arc,
+		atomic::{AtomicBool, AtomicU64},
+	},
+};
+
+use czkawka_core::{
+	common::model::HashType,
+	tools::duplicate::{DuplicateEntry, hash_calculation},
+};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{protected_paths::ProtectedPaths, sink::ResultSink};
+
+const HASH_BUFFER_SIZE: usize = 256 * 1024;
+
+// What the duplicates are replaced with. Symbolic links also work across
+// filesystems, reflinks are copy-on-write clones sharing the data of the kept
+// file (Btrfs, XFS, APFS), which stay independent files when either changes.
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReplaceMode {
+	#[default]
+	Hardlink,
+	Symlink,
+	Reflink,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Options {
+	// The file to keep of every group, the other files of its group are
+	// replaced according to `mode`
+	keep: Vec<String>,
+	#[serde(default)]
+	mode: ReplaceMode,
+}
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+struct LinkDuplicatesResult {
+	success_paths: Vec<String>,
+	errors: Vec<String>,
+}
+
+pub fn link_duplicates<S: ResultSink>(sink: &S, options: Options) {
+	let sink = sink.clone();
+	std::thread::spawn(move || {
+		let result = link_duplicates_impl(&sink, options);
+		sink.emit_event("link-duplicates-result", result);
+	});
+}
+
+// The files of the stored groups to replace with links to each kept file.
+// Reference files are never replaced, they can only be kept.
+fn plan<S: ResultSink>(
+	sink: &S,
+	keep: Vec<String>,
+	errors: &mut Vec<String>,
+) -> Vec<(PathBuf, Vec<PathBuf>)> {
+	sink.with_state(|state| {
+		let Some(stored) = &state.duplicate_groups else {
+			errors.push("No Duplicate Files result to link".to_string());
+			return vec![];
+		};
+		let mut plan = vec![];
+		// One file is kept per group, two would be linked to each other
+		let mut planned = HashSet::new();
+		for keep_str in keep {
+			let keep_path = PathBuf::from(&keep_str);
+			let group =
+				stored.groups.iter().position(|(reference, entries)| {
+					reference.iter().chain(entries).any(|e| e.path == keep_path)
+				});
+			let Some(index) = group else {
+				errors.push(format!(
+					"`{}` is not in a duplicate group",
+					keep_str
+				));
+				continue;
+			};
+			if !planned.insert(index) {
+				errors.push(format!(
+					"`{}` is in the same group as another kept file",
+					keep_str
+				));
+				continue;
+			}
+			let (_, entries) = &stored.groups[index];
+			let others = entries
+				.iter()
+				.filter(|entry| entry.path != keep_path)
+				.map(|entry| entry.path.clone())
+				.collect();
+			plan.push((keep_path, others));
+		}
+		plan
+	})
+}
+
+fn link_duplicates_impl<S: ResultSink>(
+	sink: &S,
+	options: Options,
+) -> LinkDuplicatesResult {
+	let Options { keep, mode } = options;
+	let protected = ProtectedPaths::load(sink);
+	let mut errors = vec![];
+	let plan = plan(sink, keep, &mut errors);
+
+	let mut result = plan
+		.par_iter()
+		.fold(
+			LinkDuplicatesResult::default,
+			|mut result, (keep, others)| {
+				let mut buffer = vec![0; HASH_BUFFER_SIZE];
+				let keep_hash = match hash_file(&mut buffer, keep) {
+					Ok(hash) => hash,
+					Err(err) => {
+						result.errors.push(format!(
+							"`{}` Failed, reason: {}",
+							keep.to_string_lossy(),
+							err
+						));
+						return result;
+					}
+				};
+
+				for path in others {
+					let path_str = path.to_string_lossy().to_string();
+					let linked = protected.check(path).and_then(|()| {
+						link_file(&mut buffer, keep, &keep_hash, path, mode)
+					});
+					match linked {
+						Ok(()) => result.success_paths.push(path_str),
+						Err(err) => result.errors.push(format!(
+							"`{}` Failed, reason: {}",
+							path_str, err
+						)),
+					}
+				}
+
+				result
+			},
+		)
+		.reduce(LinkDuplicatesResult::default, |mut acc, mut x| {
+			acc.success_paths.append(&mut x.success_paths);
+			acc.errors.append(&mut x.errors);
+			acc
+		});
+
+	errors.append(&mut result.errors);
+	result.errors = errors;
+	result
+}
+
+fn link_file(
+	buffer: &mut [u8],
+	keep: &Path,
+	keep_hash: &str,
+	path: &Path,
+	mode: ReplaceMode,
+) -> Result<(), String> {
+	let keep_metadata = fs::metadata(keep).map_err(|e| e.to_string())?;
+	let metadata = fs::symlink_metadata(path).map_err(|e| e.to_string())?;
+	if !metadata.is_file() {
+		return Err("it is not a regular file".to_string());
+	}
+	if is_same_file(&keep_metadata, &metadata) {
+		return Ok(());
+	}
+	if mode != ReplaceMode::Symlink
+		&& !is_same_device(&keep_metadata, &metadata)
+	{
+		return Err(format!(
+			"it is not on the same filesystem as `{}`",
+			keep.to_string_lossy()
+		));
+	}
+	// The files may have changed since the scan
+	if hash_file(buffer, path)? != keep_hash {
+		return Err(format!(
+			"its content differs from `{}`",
+			keep.to_string_lossy()
+		));
+	}
+
+	match mode {
+		ReplaceMode::Hardlink => {
+			replace_with(path, |tmp_path| fs::hard_link(keep, tmp_path))
+		}
+		ReplaceMode::Symlink => {
+			let target =
+				std::path::absolute(keep).map_err(|e| e.to_string())?;
+			replace_with(path, |tmp_path| symlink(&target, tmp_path))
+		}
+		ReplaceMode::Reflink => {
+			replace_with(path, |tmp_path| reflink(keep, tmp_path))
+		}
+	}
+}
+
+// Creates the replacement next to `path` and renames it over `path`, so the
+// duplicate is never lost when creating the replacement fails
+pub(crate) fn replace_with(
+	path: &Path,
+	create: impl FnOnce(&Path) -> std::io::Result<()>,
+) -> Result<(), String> {
+	let file_name = path.file_name().ok_or("it has no file name")?;
+	let mut tmp_name = std::ffi::OsString::from(".");
+	tmp_name.push(file_name);
+	tmp_name.push(".czkawka-link");
+	let tmp_path = path.with_file_name(tmp_name);
+
+	create(&tmp_path).map_err(|e| e.to_string())?;
+	fs::rename(&tmp_path, path).map_err(|e| {
+		let _ = fs::remove_file(&tmp_path);
+		e.to_string()
+	})
+}
+
+// Always Blake3, the hash type of the scan may be too weak to trust
+pub(crate) fn hash_file(
+	buffer: &mut [u8],
+	path: &Path,
+) -> Result<String, String> {
+	let entry = DuplicateEntry {
+		path: path.to_path_buf(),
+		modified_date: 0,
+		size: 0,
+		hash: String::new(),
+	};
+	hash_calculation(
+		buffer,
+		&entry,
+		HashType::Blake3,
+		&Arc::new(AtomicU64::new(0)),
+		&Arc::new(AtomicBool::new(false)),
+	)?
+	.ok_or_else(|| "hashing was stopped".to_string())
+}
+
+#[cfg(unix)]
+fn is_same_file(a: &fs::Metadata, b: &fs::Metadata) -> bool {
+	use std::os::unix::fs::MetadataExt;
+	a.dev() == b.dev() && a.ino() == b.ino()
+}
+
+#[cfg(not(unix))]
+fn is_same_file(_a: &fs::Metadata, _b: &fs::Metadata) -> bool {
+	false
+}
+
+#[cfg(unix)]
+pub(crate) fn is_same_device(a: &fs::Metadata, b: &fs::Metadata) -> bool {
+	use std::os::unix::fs::MetadataExt;
+	a.dev() == b.dev()
+}
+
+// Creating the link fails on its own when the volumes differ
+#[cfg(not(unix))]
+pub(crate) fn is_same_device(_a: &fs::Metadata, _b: &fs::Metadata) -> bool {
+	true
+}
+
+#[cfg(unix)]
+pub(crate) fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+	std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+pub(crate) fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+	std::os::windows::fs::symlink_file(target, link)
+}
+
+#[cfg(target_os = "linux")]
+fn reflink(source: &Path, dest: &Path) -> io::Result<()> {
+	use std::os::fd::AsRawFd;
+
+	let source = fs::File::open(source)?;
+	let file = fs::OpenOptions::new()
+		.write(true)
+		.create_new(true)
+		.open(dest)?;
+	let cloned = unsafe {
+		libc::ioctl(file.as_raw_fd(), libc::FICLONE, source.as_raw_fd())
+	};
+	let result = if cloned == -1 {
+		Err(reflink_error(io::Error::last_os_error()))
+	} else {
+		file.set_permissions(source.metadata()?.permissions())
+	};
+	if result.is_err() {
+		drop(file);
+		let _ = fs::remove_file(dest);
+	}
+	result
+}
+
+#[cfg(target_os = "macos")]
+fn reflink(source: &Path, dest: &Path) -> io::Result<()> {
+	use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+	let source = CString::new(source.as_os_str().as_bytes())?;
+	let dest = CString::new(dest.as_os_str().as_bytes())?;
+	if unsafe { libc::clonefile(source.as_ptr(), dest.as_ptr(), 0) } == -1 {
+		return Err(reflink_error(io::Error::last_os_error()));
+	}
+	Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn reflink(_source: &Path, _dest: &Path) -> io::Result<()> {
+	Err(io::Error::new(
+		io::ErrorKind::Unsupported,
+		"reflink copies are not supported on this platform",
+	))
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn reflink_error(err: io::Error) -> io::Error {
+	let unsupported = [
+		libc::EOPNOTSUPP,
+		libc::ENOTSUP,
+		libc::EINVAL,
+		libc::ENOTTY,
+		libc::EXDEV,
+	];
+	match err.raw_os_error() {
+		Some(code) if unsupported.contains(&code) => io::Error::new(
+			io::ErrorKind::Unsupported,
+			format!("the filesystem does not support reflink copies ({})", err),
+		),
+		_ => err,
+	}
+}
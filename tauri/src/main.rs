@@ -1,28 +1,5 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod bad_extensions;
-mod big_files;
-mod broken_files;
-mod delete_files;
-mod duplicate_files;
-mod empty_files;
-mod empty_folders;
-mod image;
-mod invalid_symlinks;
-mod move_files;
-mod music_duplicates;
-mod progress;
-mod rename_ext;
-mod save_result;
-mod scaner;
-mod settings;
-mod similar_images;
-mod similar_videos;
-mod state;
-mod temporary_files;
-mod thumbnail;
-mod utils;
-
 use std::sync::Mutex;
 
 use czkawka_core::common::config_cache_path::set_config_cache_path;
@@ -38,12 +15,31 @@ use std::{
 };
 use tauri::{AppHandle, Emitter, Manager, State};
 
-use crate::{
-	image::{ImageInfo, init_thumbnail_manager},
-	progress::process_progress_data,
+use czkawka_tauri::{
+	delete_files,
+	history::{self, HistoryDetails, HistoryEntry, HistoryId},
+	image::{self, ImageInfo, init_thumbnail_manager},
+	incremental,
+	job::{JobId, JobInfo, run_job, run_job_with, start_job},
+	journal::{self, JournalEntry, OperationId, OperationInfo},
+	link_duplicates, move_files,
+	persist::{self, RestoredResult, SavedResultInfo},
+	policy::{self, Policy},
+	protected_paths,
+	quarantine::{self, QuarantineEntry, QuarantineId},
+	queue::{
+		self, QueueId, QueueItem, cancel_all_queues, run_queue, start_queue,
+	},
+	rename_ext, save_result,
+	scaner::{Tool, spawn_scaner_thread},
+	schedule::{self, Schedule, ScheduleId, ScheduleInfo, ScheduleRun},
+	select::{self, SelectRule},
 	settings::{PlatformSettings, Settings},
+	similar_images,
 	state::AppState,
+	sweep::{SWEEP_TOOLS, sweep},
 	thumbnail::ThumbnailInfo,
+	watch,
 };
 
 static VIDEO_SERVER_PORT: OnceLock<u16> = OnceLock::new();
@@ -171,7 +167,7 @@ fn main() {
 			VIDEO_SERVER_PORT.get_or_init(|| start_video_http_server());
 			#[cfg(feature = "ffmpeg")]
 			if let Ok(resource_dir) = app.path().resource_dir() {
-				utils::set_ffmpeg_path(resource_dir);
+				czkawka_tauri::utils::set_ffmpeg_path(resource_dir);
 			}
 
 			// 初始化缩略图管理器
@@ -184,6 +180,9 @@ fn main() {
 			}
 
 			app.manage(Mutex::new(AppState::default()));
+
+			let handle = app.handle().clone();
+			thread::spawn(move || schedule::run_scheduler(handle));
 			Ok(())
 		})
 		.invoke_handler(tauri::generate_handler![
@@ -191,6 +190,22 @@ fn main() {
 			get_platform_settings,
 			setup_number_of_threads,
 			stop_scan,
+			cancel_job,
+			get_job,
+			list_jobs,
+			list_last_results,
+			load_last_result,
+			scan_queue,
+			pause_queue,
+			resume_queue,
+			cancel_queue,
+			list_history,
+			get_history_entry,
+			delete_history_entries,
+			list_schedules,
+			save_schedule,
+			delete_schedule,
+			get_schedule_history,
 			listen_scan_progress,
 			read_image,
 			read_thumbnail,
@@ -209,10 +224,28 @@ fn main() {
 			scan_invalid_symlinks,
 			scan_broken_files,
 			scan_bad_extensions,
+			scan_sweep,
+			rescan_duplicate_files,
+			watch_duplicates,
+			unwatch_duplicates,
 			move_files,
 			delete_files,
+			link_duplicates,
+			select_in_groups,
+			list_policies,
+			save_policy,
+			delete_policy,
+			apply_policy,
 			save_result,
 			rename_ext,
+			list_operations,
+			get_operation_entries,
+			undo_operation,
+			list_quarantine,
+			restore_quarantined,
+			purge_quarantine,
+			list_protected_paths,
+			save_protected_paths,
 			open_system_path,
 			copy_file_to_clipboard,
 		])
@@ -252,10 +285,128 @@ fn setup_number_of_threads(
 
 #[tauri::command]
 fn stop_scan(state: State<'_, Mutex<AppState>>) {
-	use std::sync::atomic::Ordering;
+	let mut state = state.lock().unwrap();
+	cancel_all_queues(&mut state);
+	state.jobs.cancel_all();
+}
+
+#[tauri::command]
+fn cancel_job(state: State<'_, Mutex<AppState>>, job_id: JobId) -> bool {
+	let mut state = state.lock().unwrap();
+	state.jobs.cancel(job_id)
+}
 
+#[tauri::command]
+fn get_job(
+	state: State<'_, Mutex<AppState>>,
+	job_id: JobId,
+) -> Option<JobInfo> {
 	let state = state.lock().unwrap();
-	state.stop_flag.store(true, Ordering::Relaxed);
+	state.jobs.info(job_id)
+}
+
+#[tauri::command]
+fn list_jobs(state: State<'_, Mutex<AppState>>) -> Vec<JobInfo> {
+	let state = state.lock().unwrap();
+	state.jobs.list()
+}
+
+#[tauri::command]
+fn list_last_results(app: AppHandle) -> Vec<SavedResultInfo> {
+	persist::list_last_results(&app)
+}
+
+#[tauri::command]
+fn load_last_result(
+	app: AppHandle,
+	tool: Tool,
+) -> Result<Option<RestoredResult>, String> {
+	persist::load_last_result(&app, tool)
+}
+
+#[tauri::command]
+fn scan_queue(app: AppHandle, items: Vec<QueueItem>) -> QueueId {
+	let queue = start_queue(&app);
+	let queue_id = queue.id;
+	spawn_scaner_thread(move || {
+		if items.iter().any(|item| {
+			item.tool == Tool::SimilarImages
+				&& item.settings.similar_images_enable_thumbnails
+		}) {
+			similar_images::init_thumbnail_manager(&app);
+		}
+		run_queue(&app, queue, items);
+	});
+	queue_id
+}
+
+#[tauri::command]
+fn pause_queue(state: State<'_, Mutex<AppState>>, queue_id: QueueId) -> bool {
+	let mut state = state.lock().unwrap();
+	state.queues.set_paused(queue_id, true)
+}
+
+#[tauri::command]
+fn resume_queue(state: State<'_, Mutex<AppState>>, queue_id: QueueId) -> bool {
+	let mut state = state.lock().unwrap();
+	state.queues.set_paused(queue_id, false)
+}
+
+#[tauri::command]
+fn cancel_queue(state: State<'_, Mutex<AppState>>, queue_id: QueueId) -> bool {
+	let mut state = state.lock().unwrap();
+	queue::cancel_queue(&mut state, queue_id)
+}
+
+#[tauri::command]
+fn list_history(
+	app: AppHandle,
+	tool: Option<Tool>,
+	limit: Option<usize>,
+) -> Result<Vec<HistoryEntry>, String> {
+	history::list_history(&app, tool, limit)
+}
+
+#[tauri::command]
+fn get_history_entry(
+	app: AppHandle,
+	id: HistoryId,
+) -> Result<Option<HistoryDetails>, String> {
+	history::get_history_entry(&app, id)
+}
+
+#[tauri::command]
+fn delete_history_entries(
+	app: AppHandle,
+	ids: Vec<HistoryId>,
+) -> Result<usize, String> {
+	history::delete_history_entries(&app, &ids)
+}
+
+#[tauri::command]
+fn list_schedules(app: AppHandle) -> Result<Vec<ScheduleInfo>, String> {
+	schedule::list_schedules(&app)
+}
+
+#[tauri::command]
+fn save_schedule(
+	app: AppHandle,
+	schedule: Schedule,
+) -> Result<Schedule, String> {
+	schedule::save_schedule(&app, schedule)
+}
+
+#[tauri::command]
+fn delete_schedule(app: AppHandle, id: ScheduleId) -> Result<bool, String> {
+	schedule::delete_schedule(&app, id)
+}
+
+#[tauri::command]
+fn get_schedule_history(
+	app: AppHandle,
+	limit: Option<usize>,
+) -> Result<Vec<ScheduleRun>, String> {
+	schedule::schedule_history(&app, limit)
 }
 
 #[tauri::command]
@@ -272,11 +423,11 @@ fn listen_scan_progress(app: AppHandle) {
 
 	std::thread::spawn(move || {
 		loop {
-			let Ok(progress_data) = progress_rx.recv() else {
+			let Ok(progress) = progress_rx.recv() else {
 				return;
 			};
 
-			let data = process_progress_data(progress_data);
+			let data = progress.process();
 
 			app.emit("scan-progress", data).unwrap();
 		}
@@ -314,78 +465,226 @@ fn get_thumbnail_cache_stats() -> Result<(usize, u64), String> {
 }
 
 #[tauri::command]
-fn scan_big_files(app: AppHandle, settings: Settings) {
-	big_files::scan_big_files(app, settings);
+fn scan_big_files(app: AppHandle, settings: Settings) -> JobId {
+	spawn_scan_job(app, Tool::BigFiles, settings)
 }
 
 #[tauri::command]
-fn scan_duplicate_files(app: AppHandle, settings: Settings) {
-	duplicate_files::scan_duplicate_files(app, settings);
+fn scan_duplicate_files(app: AppHandle, settings: Settings) -> JobId {
+	spawn_scan_job(app, Tool::DuplicateFiles, settings)
 }
 
 #[tauri::command]
-fn scan_empty_folders(app: AppHandle, settings: Settings) {
-	empty_folders::scan_empty_folders(app, settings);
+fn scan_empty_folders(app: AppHandle, settings: Settings) -> JobId {
+	spawn_scan_job(app, Tool::EmptyFolders, settings)
 }
 
 #[tauri::command]
-fn scan_empty_files(app: AppHandle, settings: Settings) {
-	empty_files::scan_empty_files(app, settings);
+fn scan_empty_files(app: AppHandle, settings: Settings) -> JobId {
+	spawn_scan_job(app, Tool::EmptyFiles, settings)
 }
 
 #[tauri::command]
-fn scan_temporary_files(app: AppHandle, settings: Settings) {
-	temporary_files::scan_temporary_files(app, settings);
+fn scan_temporary_files(app: AppHandle, settings: Settings) -> JobId {
+	spawn_scan_job(app, Tool::TemporaryFiles, settings)
 }
 
 #[tauri::command]
-fn scan_similar_images(app: AppHandle, settings: Settings) {
-	similar_images::scan_similar_images(app, settings);
+fn scan_similar_images(app: AppHandle, settings: Settings) -> JobId {
+	let job = start_job(&app, vec![Tool::SimilarImages]);
+	let job_id = job.id;
+	spawn_scaner_thread(move || {
+		if settings.similar_images_enable_thumbnails {
+			similar_images::init_thumbnail_manager(&app);
+		}
+		run_job(&app, job, settings);
+	});
+	job_id
 }
 
 #[tauri::command]
-fn scan_similar_videos(app: AppHandle, settings: Settings) {
-	similar_videos::scan_similar_videos(app, settings);
+fn scan_similar_videos(app: AppHandle, settings: Settings) -> JobId {
+	spawn_scan_job(app, Tool::SimilarVideos, settings)
 }
 
 #[tauri::command]
-fn scan_music_duplicates(app: AppHandle, settings: Settings) {
-	music_duplicates::scan_music_duplicates(app, settings);
+fn scan_music_duplicates(app: AppHandle, settings: Settings) -> JobId {
+	spawn_scan_job(app, Tool::MusicDuplicates, settings)
 }
 
 #[tauri::command]
-fn scan_invalid_symlinks(app: AppHandle, settings: Settings) {
-	invalid_symlinks::scan_invalid_symlinks(app, settings);
+fn scan_invalid_symlinks(app: AppHandle, settings: Settings) -> JobId {
+	spawn_scan_job(app, Tool::InvalidSymlinks, settings)
 }
 
 #[tauri::command]
-fn scan_broken_files(app: AppHandle, settings: Settings) {
-	broken_files::scan_broken_files(app, settings);
+fn scan_broken_files(app: AppHandle, settings: Settings) -> JobId {
+	spawn_scan_job(app, Tool::BrokenFiles, settings)
 }
 
 #[tauri::command]
-fn scan_bad_extensions(app: AppHandle, settings: Settings) {
-	bad_extensions::scan_bad_extensions(app, settings);
+fn scan_bad_extensions(app: AppHandle, settings: Settings) -> JobId {
+	spawn_scan_job(app, Tool::BadExtensions, settings)
 }
 
 #[tauri::command]
-fn move_files(app: AppHandle, options: move_files::Options) {
-	move_files::move_files(app, options);
+fn rescan_duplicate_files(app: AppHandle, settings: Settings) -> JobId {
+	let job = start_job(&app, vec![Tool::DuplicateFiles]);
+	let job_id = job.id;
+	spawn_scaner_thread(move || {
+		run_job_with(&app, job, |sink| {
+			incremental::rescan_duplicate_files(sink, settings)
+		});
+	});
+	job_id
+}
+
+#[tauri::command]
+fn watch_duplicates(app: AppHandle) -> Result<(), String> {
+	watch::watch_duplicates(&app)
+}
+
+#[tauri::command]
+fn unwatch_duplicates(app: AppHandle) -> bool {
+	watch::unwatch_duplicates(&app)
+}
+
+#[tauri::command]
+fn scan_sweep(
+	app: AppHandle,
+	tools: Vec<Tool>,
+	settings: Settings,
+) -> Result<JobId, String> {
+	if let Some(tool) = tools.iter().find(|tool| !SWEEP_TOOLS.contains(tool)) {
+		return Err(format!("{:?} cannot run in a sweep", tool));
+	}
+	let job = start_job(&app, tools.clone());
+	let job_id = job.id;
+	spawn_scaner_thread(move || {
+		run_job_with(&app, job, |sink| sweep(sink, &tools, settings));
+	});
+	Ok(job_id)
+}
+
+fn spawn_scan_job(app: AppHandle, tool: Tool, settings: Settings) -> JobId {
+	let job = start_job(&app, vec![tool]);
+	let job_id = job.id;
+	spawn_scaner_thread(move || run_job(&app, job, settings));
+	job_id
+}
+
+#[tauri::command]
+fn move_files(app: AppHandle, options: move_files::Options) -> JobId {
+	move_files::move_files(&app, options)
+}
+
+#[tauri::command]
+fn delete_files(app: AppHandle, options: delete_files::Options) -> JobId {
+	delete_files::delete_files(&app, options)
+}
+
+#[tauri::command]
+fn link_duplicates(app: AppHandle, options: link_duplicates::Options) {
+	link_duplicates::link_duplicates(&app, options);
 }
 
 #[tauri::command]
-fn delete_files(app: AppHandle, options: delete_files::Options) {
-	delete_files::delete_files(app, options);
+fn select_in_groups(
+	app: AppHandle,
+	tool: Tool,
+	rule: SelectRule,
+) -> Result<Vec<String>, String> {
+	select::select_in_groups(&app, tool, rule)
+}
+
+#[tauri::command]
+fn list_policies(app: AppHandle) -> Result<Vec<Policy>, String> {
+	policy::list_policies(&app)
+}
+
+#[tauri::command]
+fn save_policy(app: AppHandle, policy: Policy) -> Result<(), String> {
+	policy::save_policy(&app, &policy)
+}
+
+#[tauri::command]
+fn delete_policy(app: AppHandle, name: String) -> Result<bool, String> {
+	policy::delete_policy(&app, &name)
+}
+
+#[tauri::command]
+fn apply_policy(
+	app: AppHandle,
+	tool: Tool,
+	name: String,
+) -> Result<Vec<String>, String> {
+	policy::apply_policy(&app, tool, &name)
 }
 
 #[tauri::command]
 fn save_result(app: AppHandle, options: save_result::Options) {
-	save_result::save_result(app, options);
+	save_result::save_result(&app, options);
+}
+
+#[tauri::command]
+fn rename_ext(app: AppHandle, options: rename_ext::Options) -> JobId {
+	rename_ext::rename_ext(&app, options)
+}
+
+#[tauri::command]
+fn list_operations(
+	app: AppHandle,
+	limit: Option<usize>,
+) -> Result<Vec<OperationInfo>, String> {
+	journal::list_operations(&app, limit)
+}
+
+#[tauri::command]
+fn get_operation_entries(
+	app: AppHandle,
+	id: OperationId,
+) -> Result<Vec<JournalEntry>, String> {
+	journal::operation_entries(&app, id)
+}
+
+#[tauri::command]
+fn undo_operation(app: AppHandle, id: OperationId) {
+	journal::undo_operation(&app, id);
+}
+
+#[tauri::command]
+fn list_quarantine(
+	app: AppHandle,
+	dir: Option<String>,
+) -> Result<Vec<QuarantineEntry>, String> {
+	quarantine::list_quarantine(&app, dir.as_deref())
+}
+
+#[tauri::command]
+fn restore_quarantined(
+	app: AppHandle,
+	ids: Vec<QuarantineId>,
+	dir: Option<String>,
+) {
+	quarantine::restore_quarantined(&app, ids, dir);
+}
+
+#[tauri::command]
+fn purge_quarantine(app: AppHandle, older_than_days: u64, dir: Option<String>) {
+	quarantine::purge_quarantine(&app, older_than_days, dir);
+}
+
+#[tauri::command]
+fn list_protected_paths(app: AppHandle) -> Result<Vec<String>, String> {
+	protected_paths::list_protected_paths(&app)
 }
 
 #[tauri::command]
-fn rename_ext(app: AppHandle, options: rename_ext::Options) {
-	rename_ext::rename_ext(app, options);
+fn save_protected_paths(
+	app: AppHandle,
+	patterns: Vec<String>,
+) -> Result<(), String> {
+	protected_paths::save_protected_paths(&app, &patterns)
 }
 
 #[tauri::command]
@@ -1,11 +1,31 @@
 use std::{
-	fs, io,
+	collections::HashSet,
+	fs,
+	io::{self, Read, Write},
 	path::{Path, PathBuf},
+	sync::{
+		Mutex,
+		atomic::{AtomicBool, Ordering},
+	},
 };
 
+use czkawka_core::common::progress_data::CurrentStage;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Emitter};
+
+use crate::{
+	job::{JobHandle, JobId, run_job_with},
+	journal::{
+		Action, JournalEntry, OperationId, PlannedAction, fingerprint,
+		record_operation,
+	},
+	link_duplicates::{hash_file, symlink},
+	preflight::{self, Refusal},
+	progress::{Counters, with_stage_progress},
+	protected_paths::{ProtectedPaths, resolve},
+	scaner::Tool,
+	sink::ResultSink,
+};
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -15,34 +35,226 @@ pub struct Options {
 	copy_mode: bool,
 	preserve_structure: bool,
 	override_mode: bool,
+	// Overrides `override_mode`, which picks `Overwrite` or `Fail`
+	#[serde(default)]
+	conflict_strategy: Option<ConflictStrategy>,
+	// Overrides `preserve_structure`, which picks `Absolute` or `Flat`
+	#[serde(default)]
+	layout: Option<Layout>,
+	// The tool whose last scan found the paths, `Layout::RelativeToRoot`
+	// keeps them below its included directories. Without one the included
+	// directories of every last scan are used.
+	#[serde(default)]
+	tool: Option<Tool>,
+	// Only check what would be moved or copied
+	#[serde(default)]
+	dry_run: bool,
+}
+
+// What to do when the destination of a file already exists
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictStrategy {
+	Fail,
+	Overwrite,
+	// Adds " (1)", " (2)", ... to the name until it is free
+	Rename,
+	// Overwrites when the source was modified later, skips it otherwise
+	KeepNewer,
+	// Overwrites when the source is larger, skips it otherwise
+	KeepLarger,
+	// Skips the source when both have the same content, fails otherwise
+	SkipIdentical,
+}
+
+// Where under the destination the files go
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum Layout {
+	// Straight into the destination
+	Flat,
+	// Straight into the destination, with a number added to names that are
+	// taken. Always uses `ConflictStrategy::Rename`, any other strategy is
+	// refused.
+	Flatten,
+	// Under the whole path of their parent directory, e.g.
+	// `dest/home/user/photos/a.jpg`
+	Absolute,
+	// Under their path relative to the included directory they were found
+	// in, e.g. `dest/2024/a.jpg` for `/home/user/photos/2024/a.jpg` scanned
+	// from `/home/user/photos`
+	RelativeToRoot,
+}
+
+enum Resolution {
+	Proceed(PathBuf),
+	Skip,
+	Fail(String),
 }
 
 #[derive(Serialize, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 struct MoveFilesResult {
+	job_id: JobId,
+	// Set when the job was cancelled before every file was moved
+	cancelled: bool,
 	success_paths: Vec<String>,
 	errors: Vec<String>,
+	// Left where they are by the conflict strategy
+	skipped_paths: Vec<String>,
+	// Journal entry to undo the move or copy with
+	operation_id: Option<OperationId>,
+	// Set by a dry run instead of moving anything
+	planned: Option<Vec<PlannedAction>>,
+	// Set instead of moving anything when the destination cannot take the
+	// files
+	refusal: Option<Refusal>,
+	#[serde(skip)]
+	journal: Vec<JournalEntry>,
 }
 
-pub fn move_files(app: AppHandle, options: Options) {
-	std::thread::spawn(move || {
-		let result = move_files_impl(options);
-		app.emit("move-files-result", result).unwrap();
+// Moves in the background as a job, which reports progress and can be
+// cancelled between files and while copying a file
+pub fn move_files<S: ResultSink>(sink: &S, options: Options) -> JobId {
+	let job = sink.with_state(|state| state.jobs.start_operation("move_files"));
+	let job_id = job.id;
+	let sink = sink.clone();
+	std::thread::spawn(move || run_move_files(&sink, job, options));
+	job_id
+}
+
+pub fn run_move_files<S: ResultSink>(
+	sink: &S,
+	job: JobHandle,
+	options: Options,
+) {
+	let job_id = job.id;
+	let dry_run = options.dry_run;
+	let protected = ProtectedPaths::load(sink);
+	let roots: Vec<_> = sink.with_state(|state| match options.tool {
+		Some(tool) => state
+			.included_directories
+			.get(&tool)
+			.cloned()
+			.unwrap_or_default(),
+		None => state
+			.included_directories
+			.values()
+			.flatten()
+			.cloned()
+			.collect(),
+	});
+	let mut result = run_job_with(sink, job, |job_sink| {
+		let (stop_flag, progress_tx) = job_sink.stop_flag_and_progress_tx();
+		let bytes_to_check = if dry_run {
+			0
+		} else {
+			options
+				.paths
+				.iter()
+				.map(|path| preflight::size_of(Path::new(path)))
+				.sum()
+		};
+		with_stage_progress(
+			&progress_tx,
+			CurrentStage::MovingFiles,
+			(0, 0),
+			(options.paths.len(), bytes_to_check),
+			|counters| {
+				let ctx = Context {
+					roots: &roots,
+					protected: &protected,
+					stop_flag: &stop_flag,
+					counters,
+				};
+				move_files_impl(options, ctx)
+			},
+		)
 	});
+	result.job_id = job_id;
+	if dry_run {
+		result.planned =
+			Some(result.journal.drain(..).map(Into::into).collect());
+	} else {
+		result.operation_id =
+			record_operation(sink, "move_files", &result.journal);
+	}
+	sink.emit_event("move-files-result", result);
+}
+
+struct Context<'a> {
+	// The included directories of the scan, resolved
+	roots: &'a [PathBuf],
+	protected: &'a ProtectedPaths,
+	stop_flag: &'a AtomicBool,
+	counters: &'a Counters,
 }
 
-fn move_files_impl(options: Options) -> MoveFilesResult {
+fn move_files_impl(options: Options, ctx: Context) -> MoveFilesResult {
+	let Context {
+		roots,
+		protected,
+		stop_flag,
+		counters,
+	} = ctx;
 	let Options {
 		paths,
 		destination,
 		copy_mode,
 		preserve_structure,
 		override_mode,
+		conflict_strategy,
+		layout,
+		dry_run,
+		..
 	} = options;
+	let layout = layout.unwrap_or(if preserve_structure {
+		Layout::Absolute
+	} else {
+		Layout::Flat
+	});
+	let strategy = match (layout, conflict_strategy) {
+		(Layout::Flatten, None | Some(ConflictStrategy::Rename)) => {
+			ConflictStrategy::Rename
+		}
+		(Layout::Flatten, Some(strategy)) => {
+			return MoveFilesResult {
+				errors: vec![format!(
+					"The flatten layout always renames, it cannot be used \
+					with the {:?} conflict strategy",
+					strategy
+				)],
+				..MoveFilesResult::default()
+			};
+		}
+		(_, strategy) => strategy.unwrap_or(if override_mode {
+			ConflictStrategy::Overwrite
+		} else {
+			ConflictStrategy::Fail
+		}),
+	};
+	// A dry run reports the refusal the real run would hit
+	if let Some(refusal) =
+		check_batch(&paths, &destination, copy_mode, layout, roots)
+	{
+		return MoveFilesResult {
+			errors: refusal.messages(),
+			refusal: Some(refusal),
+			..MoveFilesResult::default()
+		};
+	}
+	// Destinations taken by earlier files of the batch, which may not have
+	// been moved yet
+	let claimed = Mutex::new(HashSet::new());
 
 	paths
 		.par_iter()
 		.fold(MoveFilesResult::default, |mut result, source_str| {
+			if stop_flag.load(Ordering::Relaxed) {
+				result.cancelled = true;
+				return result;
+			}
+			counters.entries.fetch_add(1, Ordering::Relaxed);
 			let source_path = PathBuf::from(source_str);
 			let source_name = match source_path.file_name() {
 				Some(file_name) => file_name.to_string_lossy().to_string(),
@@ -54,21 +266,34 @@ fn move_files_impl(options: Options) -> MoveFilesResult {
 					return result;
 				}
 			};
-			let mut dest_path = PathBuf::from(&destination);
-
-			if preserve_structure {
-				if let Some(parent) = source_path.parent() {
-					let relative_path = parent
-						.components()
-						.filter(|c| {
-							matches!(c, std::path::Component::Normal(_))
-						})
-						.collect::<PathBuf>();
-					dest_path.push(relative_path);
+			// A copy leaves the source alone
+			let source_check = if copy_mode {
+				Ok(())
+			} else {
+				protected.check(&source_path)
+			};
+			let mut dest_path = match source_check
+				.and_then(|()| {
+					dest_dir(&destination, &source_path, layout, roots)
+				})
+				.and_then(|dest| protected.check(&dest).map(|()| dest))
+			{
+				Ok(dest_path) => dest_path,
+				Err(err) => {
+					result.errors.push(format!(
+						"`{}` Failed, reason: {}",
+						source_str, err
+					));
+					return result;
 				}
-			}
+			};
 
-			if let Err(err) = fs::create_dir_all(&dest_path) {
+			let dir_result = if dry_run {
+				preflight::check_writable(&dest_path)
+			} else {
+				fs::create_dir_all(&dest_path).map_err(|e| e.to_string())
+			};
+			if let Err(err) = dir_result {
 				result
 					.errors
 					.push(format!("`{}` Failed, reason: {}", source_str, err));
@@ -77,22 +302,60 @@ fn move_files_impl(options: Options) -> MoveFilesResult {
 
 			dest_path.push(&source_name);
 
-			if dest_path.exists() && !override_mode {
-				result.errors.push(format!(
-					"`{}` already exists",
-					dest_path.to_string_lossy()
-				));
-				return result;
-			}
+			let dest_path = match resolve_conflict(
+				&source_path,
+				dest_path,
+				strategy,
+				&claimed,
+			) {
+				Resolution::Proceed(dest_path) => dest_path,
+				Resolution::Skip => {
+					result.skipped_paths.push(source_str.clone());
+					return result;
+				}
+				Resolution::Fail(err) => {
+					result.errors.push(err);
+					return result;
+				}
+			};
 
-			let fs_result = if copy_mode {
-				copy_item(&source_path, &dest_path)
+			let fs_result = if dry_run {
+				check_move(&source_path, copy_mode)
 			} else {
-				move_item(&source_path, &dest_path)
+				let transfer = Transfer {
+					stop_flag,
+					counters,
+				};
+				if copy_mode {
+					transfer.copy_item(&source_path, &dest_path)
+				} else {
+					transfer.move_item(&source_path, &dest_path)
+				}
+				.map_err(|e| e.to_string())
 			};
 
 			match fs_result {
-				Ok(_) => result.success_paths.push(source_str.clone()),
+				Ok(_) => {
+					let action = if copy_mode {
+						Action::Copy
+					} else {
+						Action::Move
+					};
+					let mut entry =
+						JournalEntry::new(action, source_path, Some(dest_path));
+					if copy_mode && !dry_run {
+						entry.fingerprint = entry
+							.destination
+							.as_deref()
+							.and_then(|dest| fingerprint(dest).ok());
+					}
+					result.journal.push(entry);
+					result.success_paths.push(source_str.clone());
+				}
+				// Stopped in the middle of a copy, the partial copy is removed
+				Err(_) if stop_flag.load(Ordering::Relaxed) => {
+					result.cancelled = true;
+				}
 				Err(err) => result
 					.errors
 					.push(format!("`{}` Failed, reason: {}", source_str, err)),
@@ -101,31 +364,376 @@ fn move_files_impl(options: Options) -> MoveFilesResult {
 			result
 		})
 		.reduce(MoveFilesResult::default, |mut acc, mut x| {
+			acc.cancelled |= x.cancelled;
 			acc.success_paths.append(&mut x.success_paths);
 			acc.errors.append(&mut x.errors);
+			acc.skipped_paths.append(&mut x.skipped_paths);
+			acc.journal.append(&mut x.journal);
 			acc
 		})
 }
 
-fn copy_item(source: &Path, dest: &Path) -> Result<(), io::Error> {
-	if source.is_dir() {
-		dircpy::CopyBuilder::new(source, dest)
-			.overwrite(true)
-			.run()?;
-	} else {
-		fs::copy(source, dest)?;
+// Refuses the whole batch before anything is copied when a destination cannot
+// be written to or the copies do not fit, instead of stopping halfway through
+fn check_batch(
+	paths: &[String],
+	destination: &str,
+	copy_mode: bool,
+	layout: Layout,
+	roots: &[PathBuf],
+) -> Option<Refusal> {
+	let mut copies = vec![];
+	let mut dirs = vec![];
+	for source_str in paths {
+		let source = Path::new(source_str);
+		// Reported for the file itself when it is moved
+		let Ok(dir) = dest_dir(destination, source, layout, roots) else {
+			continue;
+		};
+		// Moves to another filesystem are copies too
+		let copied = copy_mode
+			|| preflight::filesystem_of(source).ok()
+				!= preflight::filesystem_of(&dir).ok();
+		if copied {
+			copies.push((source.to_path_buf(), dir.clone()));
+		}
+		dirs.push(dir);
+	}
+	preflight::check_batch(&copies, &dirs)
+}
+
+// The directory `source` goes into
+fn dest_dir(
+	destination: &str,
+	source: &Path,
+	layout: Layout,
+	roots: &[PathBuf],
+) -> Result<PathBuf, String> {
+	let mut dest_path = PathBuf::from(destination);
+	let Some(parent) = source.parent() else {
+		return Ok(dest_path);
+	};
+	match layout {
+		Layout::Flat | Layout::Flatten => {}
+		Layout::Absolute => dest_path.extend(
+			parent
+				.components()
+				.filter(|c| matches!(c, std::path::Component::Normal(_))),
+		),
+		Layout::RelativeToRoot => {
+			// The roots are resolved the same way
+			let source = resolve(source);
+			let parent = source.parent().unwrap_or(parent);
+			// The innermost one when included directories are nested
+			let root = roots
+				.iter()
+				.filter(|root| source.starts_with(root))
+				.max_by_key(|root| root.components().count())
+				.ok_or("it is not in any of the included directories")?;
+			dest_path.push(parent.strip_prefix(root).unwrap_or(Path::new("")));
+		}
+	}
+	Ok(dest_path)
+}
+
+fn resolve_conflict(
+	source: &Path,
+	dest: PathBuf,
+	strategy: ConflictStrategy,
+	claimed: &Mutex<HashSet<PathBuf>>,
+) -> Resolution {
+	{
+		let mut claimed = claimed.lock().unwrap();
+		let is_free = |path: &Path| {
+			fs::symlink_metadata(path).is_err() && !claimed.contains(path)
+		};
+		if is_free(&dest) {
+			claimed.insert(dest.clone());
+			return Resolution::Proceed(dest);
+		}
+		if strategy == ConflictStrategy::Rename {
+			let free = (1..)
+				.map(|n| numbered(&dest, n))
+				.find(|path| is_free(path))
+				.unwrap();
+			claimed.insert(free.clone());
+			return Resolution::Proceed(free);
+		}
+		if !claimed.insert(dest.clone()) {
+			return Resolution::Fail(format!(
+				"`{}` is also the destination of another file",
+				dest.to_string_lossy()
+			));
+		}
 	}
 
+	let failed = |reason: String| {
+		Resolution::Fail(format!(
+			"`{}` Failed, reason: {}",
+			source.to_string_lossy(),
+			reason
+		))
+	};
+	let keep_source_if = |source_wins: bool| {
+		if source_wins {
+			Resolution::Proceed(dest.clone())
+		} else {
+			Resolution::Skip
+		}
+	};
+	let (source_meta, dest_meta) =
+		match (fs::metadata(source), fs::metadata(&dest)) {
+			(Ok(source_meta), Ok(dest_meta)) => (source_meta, dest_meta),
+			(Err(e), _) | (_, Err(e)) => return failed(e.to_string()),
+		};
+
+	match strategy {
+		ConflictStrategy::Fail => Resolution::Fail(format!(
+			"`{}` already exists",
+			dest.to_string_lossy()
+		)),
+		ConflictStrategy::Overwrite => Resolution::Proceed(dest),
+		ConflictStrategy::KeepNewer => {
+			match (source_meta.modified(), dest_meta.modified()) {
+				(Ok(source_time), Ok(dest_time)) => {
+					keep_source_if(source_time > dest_time)
+				}
+				(Err(e), _) | (_, Err(e)) => failed(e.to_string()),
+			}
+		}
+		ConflictStrategy::KeepLarger => {
+			keep_source_if(source_meta.len() > dest_meta.len())
+		}
+		ConflictStrategy::SkipIdentical => {
+			if !source_meta.is_file() || !dest_meta.is_file() {
+				return failed(format!(
+					"`{}` already exists",
+					dest.to_string_lossy()
+				));
+			}
+			let identical = if source_meta.len() == dest_meta.len() {
+				let mut buffer = vec![0; 256 * 1024];
+				hash_file(&mut buffer, source)
+					.and_then(|hash| Ok(hash == hash_file(&mut buffer, &dest)?))
+			} else {
+				Ok(false)
+			};
+			match identical {
+				Ok(true) => Resolution::Skip,
+				Ok(false) => failed(format!(
+					"`{}` already exists with a different content",
+					dest.to_string_lossy()
+				)),
+				Err(e) => failed(e),
+			}
+		}
+		ConflictStrategy::Rename => unreachable!("renamed above"),
+	}
+}
+
+// `name (n).ext` next to `path`
+fn numbered(path: &Path, n: u32) -> PathBuf {
+	let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+	let name = match path.extension() {
+		Some(ext) => format!("{} ({}).{}", stem, n, ext.to_string_lossy()),
+		None => format!("{} ({})", stem, n),
+	};
+	path.with_file_name(name)
+}
+
+fn check_move(source: &Path, copy_mode: bool) -> Result<(), String> {
+	if !source.exists() {
+		return Err("it does not exist".to_string());
+	}
+	if !copy_mode {
+		preflight::check_removable(source)?;
+	}
 	Ok(())
 }
 
-fn move_item(source: &Path, dest: &Path) -> Result<(), io::Error> {
-	if fs::rename(source, dest).is_ok() {
-		return Ok(());
+const COPY_BUFFER_SIZE: usize = 1024 * 1024;
+
+// Copies in chunks, counting the bytes done and checking the stop flag
+// between chunks so a large copy can be cancelled
+struct Transfer<'a> {
+	stop_flag: &'a AtomicBool,
+	counters: &'a Counters,
+}
+
+impl Transfer<'_> {
+	// Symbolic links are copied as links, and the permissions and times of
+	// every file and directory are kept
+	fn copy_item(&self, source: &Path, dest: &Path) -> io::Result<()> {
+		let metadata = fs::symlink_metadata(source)?;
+		if metadata.is_symlink() {
+			if fs::symlink_metadata(dest).is_ok() {
+				fs::remove_file(dest)?;
+			}
+			return symlink(&fs::read_link(source)?, dest);
+		}
+		if metadata.is_file() {
+			return self.copy_file(source, dest);
+		}
+		if !metadata.is_dir() {
+			// Reading a FIFO blocks and reading a device copies its content
+			return Err(io::Error::new(
+				io::ErrorKind::Unsupported,
+				format!(
+					"`{}` is a {}, it cannot be copied",
+					source.to_string_lossy(),
+					special_kind(&metadata.file_type())
+				),
+			));
+		}
+		fs::create_dir_all(dest)?;
+		for entry in fs::read_dir(source)? {
+			let entry = entry?;
+			self.copy_item(&entry.path(), &dest.join(entry.file_name()))?;
+		}
+		// Only once the content is there, it may not be writable
+		fs::set_permissions(dest, metadata.permissions())?;
+		// Opening a directory is not supported everywhere, its times are kept
+		// where it is
+		if let Ok(dir) = fs::File::open(dest) {
+			let _ = dir.set_times(times_of(&metadata)?);
+		}
+		Ok(())
 	}
 
-	copy_item(source, dest)?;
-	fs::remove_file(source)?;
+	fn copy_file(&self, source: &Path, dest: &Path) -> io::Result<()> {
+		let mut reader = fs::File::open(source)?;
+		let mut writer = fs::File::create(dest)?;
+		let copied = (|| {
+			let mut buffer = vec![0; COPY_BUFFER_SIZE];
+			loop {
+				if self.stop_flag.load(Ordering::Relaxed) {
+					return Err(io::Error::new(
+						io::ErrorKind::Interrupted,
+						"the copy was cancelled",
+					));
+				}
+				let read = reader.read(&mut buffer)?;
+				if read == 0 {
+					break;
+				}
+				writer.write_all(&buffer[..read])?;
+				self.counters
+					.bytes
+					.fetch_add(read as u64, Ordering::Relaxed);
+			}
+			let metadata = reader.metadata()?;
+			writer.set_times(times_of(&metadata)?)?;
+			writer.set_permissions(metadata.permissions())?;
+			writer.sync_all()
+		})();
+		if copied.is_err() {
+			drop(writer);
+			let _ = fs::remove_file(dest);
+		}
+		copied
+	}
 
+	// When renaming fails, e.g. because `dest` is on another filesystem, the
+	// source is copied and only removed once the copy is verified
+	fn move_item(&self, source: &Path, dest: &Path) -> io::Result<()> {
+		if fs::rename(source, dest).is_ok() {
+			self.counters
+				.bytes
+				.fetch_add(preflight::size_of(dest), Ordering::Relaxed);
+			return Ok(());
+		}
+
+		// A directory the files were merged into is left as it is
+		let merged = fs::symlink_metadata(dest).is_ok_and(|m| m.is_dir());
+		let copied = self
+			.copy_item(source, dest)
+			.and_then(|()| verify_copy(source, dest));
+		if let Err(err) = copied {
+			if !merged {
+				let _ = remove_item(dest);
+			}
+			return Err(err);
+		}
+		remove_item(source)
+	}
+}
+
+fn times_of(metadata: &fs::Metadata) -> io::Result<fs::FileTimes> {
+	Ok(fs::FileTimes::new()
+		.set_accessed(metadata.accessed()?)
+		.set_modified(metadata.modified()?))
+}
+
+// Checks that every file below `source` has a copy at `dest` with the same
+// size and hash
+fn verify_copy(source: &Path, dest: &Path) -> io::Result<()> {
+	let mismatch = |reason: &str| {
+		io::Error::other(format!(
+			"the copy at `{}` {}, the source was kept",
+			dest.to_string_lossy(),
+			reason
+		))
+	};
+	let source_meta = fs::symlink_metadata(source)?;
+	let dest_meta = fs::symlink_metadata(dest)?;
+	if source_meta.is_symlink() {
+		if fs::read_link(source)? != fs::read_link(dest)? {
+			return Err(mismatch("points somewhere else"));
+		}
+		return Ok(());
+	}
+	if source_meta.is_dir() {
+		for entry in fs::read_dir(source)? {
+			let entry = entry?;
+			verify_copy(&entry.path(), &dest.join(entry.file_name()))?;
+		}
+		return Ok(());
+	}
+	if source_meta.len() != dest_meta.len() {
+		return Err(mismatch("has a different size"));
+	}
+	let mut buffer = vec![0; COPY_BUFFER_SIZE];
+	let source_hash =
+		hash_file(&mut buffer, source).map_err(io::Error::other)?;
+	let dest_hash = hash_file(&mut buffer, dest).map_err(io::Error::other)?;
+	if source_hash != dest_hash {
+		return Err(mismatch("has a different content"));
+	}
 	Ok(())
 }
+
+fn remove_item(path: &Path) -> io::Result<()> {
+	if fs::symlink_metadata(path)?.is_dir() {
+		fs::remove_dir_all(path)
+	} else {
+		fs::remove_file(path)
+	}
+}
+
+#[cfg(unix)]
+fn special_kind(file_type: &fs::FileType) -> &'static str {
+	use std::os::unix::fs::FileTypeExt;
+	if file_type.is_fifo() {
+		"FIFO"
+	} else if file_type.is_socket() {
+		"socket"
+	} else if file_type.is_block_device() || file_type.is_char_device() {
+		"device"
+	} else {
+		"special file"
+	}
+}
+
+#[cfg(not(unix))]
+fn special_kind(_file_type: &fs::FileType) -> &'static str {
+	"special file"
+}
+
+// Used by the journal to move files back, without progress
+pub(crate) fn move_item(source: &Path, dest: &Path) -> Result<(), io::Error> {
+	let transfer = Transfer {
+		stop_flag: &AtomicBool::new(false),
+		counters: &Counters::default(),
+	};
+	transfer.move_item(source, dest)
+}
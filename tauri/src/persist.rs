@@ -0,0 +1,262 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	sync::{Arc, atomic::AtomicBool},
+};
+
+use crossbeam_channel::Sender;
+use czkawka_core::common::progress_data::ProgressData;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+	history::{ProgressStats, record_scan},
+	scaner::Tool,
+	settings::Settings,
+	sink::ResultSink,
+	state::{AppState, ScanerState},
+	utils::unix_now,
+	walk::modified_date,
+};
+
+const RESULTS_DIR: &str = "last_results";
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SavedResult {
+	tool: Tool,
+	saved_at: u64,
+	settings: Settings,
+	result: Value,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedResultInfo {
+	pub tool: Tool,
+	pub saved_at: u64,
+}
+
+// Entries of `result` whose file was changed or removed since the scan carry
+// a `state` field set to "changed" or "missing"
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoredResult {
+	pub tool: Tool,
+	pub saved_at: u64,
+	pub settings: Settings,
+	pub result: Value,
+	pub changed: usize,
+	pub missing: usize,
+}
+
+// Saves every result passing through it, together with the settings of the
+// scan, into the results dir of the wrapped sink and records it in the history
+#[derive(Clone)]
+pub struct PersistSink<S> {
+	sink: S,
+	settings: Arc<Settings>,
+	started_at: u64,
+	stats: Arc<ProgressStats>,
+}
+
+impl<S: ResultSink> PersistSink<S> {
+	pub fn new(sink: S, settings: Settings) -> Self {
+		Self {
+			sink,
+			settings: Arc::new(settings),
+			started_at: unix_now(),
+			stats: Arc::default(),
+		}
+	}
+}
+
+impl<S: ResultSink> ResultSink for PersistSink<S> {
+	fn with_state<R>(&self, f: impl FnOnce(&mut AppState) -> R) -> R {
+		self.sink.with_state(f)
+	}
+
+	fn emit_event<T: Serialize + Clone>(&self, event: &str, payload: T) {
+		self.sink.emit_event(event, payload);
+	}
+
+	fn emit_result<T: Serialize + Clone>(&self, result: T) {
+		if let Some(data_dir) = self.sink.results_dir() {
+			if let Err(e) = save_result(&data_dir, &self.settings, &result) {
+				eprintln!("Failed to save scan result: {}", e);
+			}
+			let recorded = serde_json::to_value(&result)
+				.map_err(|e| e.to_string())
+				.and_then(|result| {
+					record_scan(
+						&data_dir,
+						&self.settings,
+						&result,
+						&self.stats,
+						self.started_at,
+						unix_now(),
+					)
+				});
+			if let Err(e) = recorded {
+				eprintln!("Failed to record scan history: {}", e);
+			}
+		}
+		self.sink.emit_result(result);
+	}
+
+	fn stop_flag_and_progress_tx(
+		&self,
+	) -> (Arc<AtomicBool>, Sender<ProgressData>) {
+		let (stop_flag, progress_tx) = self.sink.stop_flag_and_progress_tx();
+		(stop_flag, self.stats.track(progress_tx))
+	}
+
+	fn set_scaner_state<T: ScanerState>(&self, scaner: T) {
+		self.sink.set_scaner_state(scaner);
+	}
+
+	fn data_dir(&self) -> Option<PathBuf> {
+		self.sink.data_dir()
+	}
+
+	fn results_dir(&self) -> Option<PathBuf> {
+		self.sink.results_dir()
+	}
+
+	fn config_dir(&self) -> Option<PathBuf> {
+		self.sink.config_dir()
+	}
+}
+
+fn result_path(data_dir: &Path, tool: Tool) -> PathBuf {
+	data_dir
+		.join(RESULTS_DIR)
+		.join(format!("{}.json", tool.cmd()))
+}
+
+fn save_result<T: Serialize>(
+	data_dir: &Path,
+	settings: &Settings,
+	result: &T,
+) -> Result<(), String> {
+	let mut result = serde_json::to_value(result).map_err(|e| e.to_string())?;
+	let tool = result
+		.get("cmd")
+		.and_then(Value::as_str)
+		.and_then(Tool::from_cmd)
+		.ok_or("Result without a known cmd")?;
+	// Only meaningful for the run that produced it
+	if let Some(result) = result.as_object_mut() {
+		result.remove("jobId");
+	}
+
+	let saved = SavedResult {
+		tool,
+		saved_at: unix_now(),
+		settings: settings.clone(),
+		result,
+	};
+
+	let path = result_path(data_dir, tool);
+	fs::create_dir_all(path.parent().unwrap()).map_err(|e| e.to_string())?;
+	// Written aside first, so a crash never leaves a truncated file behind
+	let tmp_path = path.with_extension("json.tmp");
+	let data = serde_json::to_vec(&saved).map_err(|e| e.to_string())?;
+	fs::write(&tmp_path, data).map_err(|e| e.to_string())?;
+	fs::rename(&tmp_path, &path).map_err(|e| e.to_string())
+}
+
+fn read_saved(
+	data_dir: &Path,
+	tool: Tool,
+) -> Result<Option<SavedResult>, String> {
+	let path = result_path(data_dir, tool);
+	if !path.exists() {
+		return Ok(None);
+	}
+	let data = fs::read(&path).map_err(|e| e.to_string())?;
+	serde_json::from_slice(&data)
+		.map(Some)
+		.map_err(|e| format!("Invalid saved result {}: {}", path.display(), e))
+}
+
+pub fn list_last_results<S: ResultSink>(sink: &S) -> Vec<SavedResultInfo> {
+	let Some(data_dir) = sink.data_dir() else {
+		return vec![];
+	};
+	Tool::ALL
+		.into_iter()
+		.filter_map(|tool| read_saved(&data_dir, tool).ok().flatten())
+		.map(|saved| SavedResultInfo {
+			tool: saved.tool,
+			saved_at: saved.saved_at,
+		})
+		.collect()
+}
+
+pub fn load_last_result<S: ResultSink>(
+	sink: &S,
+	tool: Tool,
+) -> Result<Option<RestoredResult>, String> {
+	let Some(data_dir) = sink.data_dir() else {
+		return Ok(None);
+	};
+	let Some(mut saved) = read_saved(&data_dir, tool)? else {
+		return Ok(None);
+	};
+
+	let mut changed = 0;
+	let mut missing = 0;
+	if let Some(list) = saved.result.get_mut("list") {
+		mark_entries(list, &mut changed, &mut missing);
+	}
+
+	Ok(Some(RestoredResult {
+		tool: saved.tool,
+		saved_at: saved.saved_at,
+		settings: saved.settings,
+		result: saved.result,
+		changed,
+		missing,
+	}))
+}
+
+// Walks the list whatever its shape (plain entries or groups of entries)
+fn mark_entries(value: &mut Value, changed: &mut usize, missing: &mut usize) {
+	match value {
+		Value::Array(items) => {
+			for item in items {
+				mark_entries(item, changed, missing);
+			}
+		}
+		Value::Object(entry) if entry.contains_key("path") => {
+			match entry_state(entry) {
+				Some("changed") => *changed += 1,
+				Some("missing") => *missing += 1,
+				_ => {}
+			}
+		}
+		_ => {}
+	}
+}
+
+fn entry_state(entry: &mut Map<String, Value>) -> Option<&'static str> {
+	let path = entry.get("path")?.as_str()?;
+	let state = match fs::symlink_metadata(path) {
+		Err(_) => "missing",
+		Ok(metadata) => {
+			let modified = entry.get("modified_date").and_then(Value::as_u64);
+			let size = entry.get("size").and_then(Value::as_u64);
+			if modified.is_some_and(|date| date != modified_date(&metadata))
+				|| (!metadata.is_dir()
+					&& size.is_some_and(|size| size != metadata.len()))
+			{
+				"changed"
+			} else {
+				return None;
+			}
+		}
+	};
+	entry.insert("state".to_string(), state.into());
+	Some(state)
+}
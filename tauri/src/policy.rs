@@ -0,0 +1,117 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	scaner::Tool,
+	select::{SelectRule, select_with_rules},
+	sink::ResultSink,
+};
+
+const POLICIES_DIR: &str = "policies";
+
+// A named list of keep rules, the first rule decides and every later one
+// breaks the ties of the rules before it. Policies are plain JSON files in the
+// `policies` folder of the config dir so they can be shared, e.g.
+// `{"name": "archive", "rules": [{"rule": "keepInDirectories", "directories":
+// ["/archive"]}, {"rule": "keepLargest"}, {"rule": "keepOldest"}]}`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Policy {
+	pub name: String,
+	pub rules: Vec<SelectRule>,
+}
+
+fn policies_dir<S: ResultSink>(sink: &S) -> Result<PathBuf, String> {
+	sink.config_dir()
+		.map(|dir| dir.join(POLICIES_DIR))
+		.ok_or_else(|| "No config dir to keep policies in".to_string())
+}
+
+fn policy_path<S: ResultSink>(sink: &S, name: &str) -> Result<PathBuf, String> {
+	let valid = !name.is_empty()
+		&& !name.starts_with('.')
+		&& !name.contains(['/', '\\', ':']);
+	if !valid {
+		return Err(format!("Invalid policy name `{}`", name));
+	}
+	Ok(policies_dir(sink)?.join(format!("{}.json", name)))
+}
+
+fn read_policy(path: &Path) -> Result<Policy, String> {
+	let data = fs::read(path)
+		.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+	serde_json::from_slice(&data)
+		.map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+// Sorted by name, files that fail to parse are skipped
+pub fn list_policies<S: ResultSink>(sink: &S) -> Result<Vec<Policy>, String> {
+	let entries = match fs::read_dir(policies_dir(sink)?) {
+		Ok(entries) => entries,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+			return Ok(vec![]);
+		}
+		Err(e) => return Err(e.to_string()),
+	};
+	let mut policies: Vec<_> = entries
+		.filter_map(Result::ok)
+		.map(|entry| entry.path())
+		.filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+		.filter_map(|path| read_policy(&path).ok())
+		.collect();
+	policies.sort_by(|a, b| a.name.cmp(&b.name));
+	Ok(policies)
+}
+
+pub fn load_policy<S: ResultSink>(
+	sink: &S,
+	name: &str,
+) -> Result<Policy, String> {
+	let path = policy_path(sink, name)?;
+	if !path.exists() {
+		return Err(format!("No policy named `{}`", name));
+	}
+	read_policy(&path)
+}
+
+// Adds the policy, or replaces the one with the same name
+pub fn save_policy<S: ResultSink>(
+	sink: &S,
+	policy: &Policy,
+) -> Result<(), String> {
+	if policy.rules.is_empty() {
+		return Err("A policy needs at least one rule".to_string());
+	}
+	let path = policy_path(sink, &policy.name)?;
+	fs::create_dir_all(path.parent().unwrap()).map_err(|e| e.to_string())?;
+	let tmp_path = path.with_extension("json.tmp");
+	let data = serde_json::to_vec_pretty(policy).map_err(|e| e.to_string())?;
+	fs::write(&tmp_path, data).map_err(|e| e.to_string())?;
+	fs::rename(&tmp_path, path).map_err(|e| e.to_string())
+}
+
+// Returns whether the policy existed
+pub fn delete_policy<S: ResultSink>(
+	sink: &S,
+	name: &str,
+) -> Result<bool, String> {
+	match fs::remove_file(policy_path(sink, name)?) {
+		Ok(()) => Ok(true),
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+		Err(e) => Err(e.to_string()),
+	}
+}
+
+// The paths the policy selects for removal in the last result of `tool`
+pub fn apply_policy<S: ResultSink>(
+	sink: &S,
+	tool: Tool,
+	name: &str,
+) -> Result<Vec<String>, String> {
+	let policy = load_policy(sink, name)?;
+	select_with_rules(sink, tool, &policy.rules)
+}
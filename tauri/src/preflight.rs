@@ -0,0 +1,241 @@
+use std::{
+	collections::{HashMap, HashSet},
+	fs, io,
+	path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+// Checks a file operation can make without touching the filesystem, for dry
+// runs of delete_files, move_files and rename_ext, and before copies start
+
+// `path` itself when it exists, otherwise the closest parent that does
+pub(crate) fn existing_ancestor(path: &Path) -> Option<&Path> {
+	path.ancestors().find(|path| path.exists())
+}
+
+// Whether entries can be created in and removed from `dir`, or from the closest
+// parent that exists when `dir` still has to be created
+pub(crate) fn check_writable(dir: &Path) -> Result<(), String> {
+	let existing = existing_ancestor(dir)
+		.ok_or_else(|| format!("`{}` does not exist", dir.to_string_lossy()))?;
+	if !existing.is_dir() {
+		return Err(format!(
+			"`{}` is not a directory",
+			existing.to_string_lossy()
+		));
+	}
+	if !is_writable(existing) {
+		return Err(format!(
+			"no permission to write to `{}`",
+			existing.to_string_lossy()
+		));
+	}
+	Ok(())
+}
+
+// Whether the parent directory of `path` allows removing it
+pub(crate) fn check_removable(path: &Path) -> Result<(), String> {
+	match path.parent() {
+		Some(parent) => check_writable(parent),
+		None => Err("it has no parent directory".to_string()),
+	}
+}
+
+#[cfg(unix)]
+fn is_writable(path: &Path) -> bool {
+	use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+	CString::new(path.as_os_str().as_bytes()).is_ok_and(|path| unsafe {
+		libc::access(path.as_ptr(), libc::W_OK) == 0
+	})
+}
+
+// Windows ignores the read-only attribute of directories, only files are
+// checked
+#[cfg(not(unix))]
+fn is_writable(path: &Path) -> bool {
+	fs::metadata(path).is_ok_and(|m| m.is_dir() || !m.permissions().readonly())
+}
+
+// Bytes taken by a file, or by all files below a directory
+pub(crate) fn size_of(path: &Path) -> u64 {
+	let Ok(metadata) = fs::symlink_metadata(path) else {
+		return 0;
+	};
+	if !metadata.is_dir() {
+		return metadata.len();
+	}
+	fs::read_dir(path)
+		.map(|entries| {
+			entries
+				.filter_map(Result::ok)
+				.map(|entry| size_of(&entry.path()))
+				.sum()
+		})
+		.unwrap_or(0)
+}
+
+// Identifies the filesystem `path` is on, or would be created on
+#[cfg(unix)]
+pub(crate) fn filesystem_of(path: &Path) -> io::Result<u64> {
+	use std::os::unix::fs::MetadataExt;
+
+	let existing = existing_ancestor(path).ok_or(io::ErrorKind::NotFound)?;
+	Ok(fs::metadata(existing)?.dev())
+}
+
+// The drive or share of the path
+#[cfg(not(unix))]
+pub(crate) fn filesystem_of(path: &Path) -> io::Result<u64> {
+	use std::hash::{DefaultHasher, Hash, Hasher};
+
+	let mut hasher = DefaultHasher::new();
+	std::path::absolute(path)?
+		.components()
+		.next()
+		.hash(&mut hasher);
+	Ok(hasher.finish())
+}
+
+// Bytes available to the current user on the filesystem of `path`
+#[cfg(unix)]
+pub(crate) fn available_space(path: &Path) -> io::Result<u64> {
+	use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+	let existing = existing_ancestor(path).ok_or(io::ErrorKind::NotFound)?;
+	let existing = CString::new(existing.as_os_str().as_bytes())?;
+	let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+	if unsafe { libc::statvfs(existing.as_ptr(), &mut stat) } == -1 {
+		return Err(io::Error::last_os_error());
+	}
+	Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(windows)]
+pub(crate) fn available_space(path: &Path) -> io::Result<u64> {
+	use std::os::windows::ffi::OsStrExt;
+
+	use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+	let existing = existing_ancestor(path).ok_or(io::ErrorKind::NotFound)?;
+	let wide: Vec<u16> =
+		existing.as_os_str().encode_wide().chain(Some(0)).collect();
+	let mut available = 0;
+	let ok = unsafe {
+		GetDiskFreeSpaceExW(
+			wide.as_ptr(),
+			&mut available,
+			std::ptr::null_mut(),
+			std::ptr::null_mut(),
+		)
+	};
+	if ok == 0 {
+		return Err(io::Error::last_os_error());
+	}
+	Ok(available)
+}
+
+// Why a batch of copies was not started
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Refusal {
+	pub space: Vec<SpaceShortage>,
+	pub not_writable: Vec<NotWritable>,
+}
+
+// A filesystem the copies do not fit on, `destination` is one of the
+// directories on it they go to
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SpaceShortage {
+	pub destination: PathBuf,
+	pub needed: u64,
+	pub available: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NotWritable {
+	pub directory: PathBuf,
+	pub reason: String,
+}
+
+impl Refusal {
+	pub(crate) fn messages(&self) -> Vec<String> {
+		let space = self.space.iter().map(|shortage| {
+			format!(
+				"`{}` Failed, reason: needs {} bytes but only {} are available",
+				shortage.destination.to_string_lossy(),
+				shortage.needed,
+				shortage.available
+			)
+		});
+		let not_writable = self.not_writable.iter().map(|dir| {
+			format!(
+				"`{}` Failed, reason: {}",
+				dir.directory.to_string_lossy(),
+				dir.reason
+			)
+		});
+		space.chain(not_writable).collect()
+	}
+}
+
+// Checks the whole batch up front: that every directory in `dirs` can be
+// written to, and that the `(source, directory)` copies fit on each
+// filesystem together
+pub(crate) fn check_batch(
+	copies: &[(PathBuf, PathBuf)],
+	dirs: &[PathBuf],
+) -> Option<Refusal> {
+	let mut refusal = Refusal::default();
+
+	let mut checked = HashSet::new();
+	for dir in dirs {
+		if !checked.insert(dir) {
+			continue;
+		}
+		if let Err(reason) = check_writable(dir) {
+			refusal.not_writable.push(NotWritable {
+				directory: dir.clone(),
+				reason,
+			});
+		}
+	}
+
+	let mut needed = HashMap::new();
+	for (source, dir) in copies {
+		// A directory without a filesystem does not exist, which
+		// `check_writable` reported
+		let Ok(fs_id) = filesystem_of(dir) else {
+			continue;
+		};
+		needed.entry(fs_id).or_insert_with(|| (dir, 0)).1 += size_of(source);
+	}
+	for (dir, needed) in needed.into_values() {
+		match available_space(dir) {
+			Ok(available) if needed > available => {
+				refusal.space.push(SpaceShortage {
+					destination: dir.clone(),
+					needed,
+					available,
+				})
+			}
+			Ok(_) => {}
+			Err(e) => refusal.not_writable.push(NotWritable {
+				directory: dir.clone(),
+				reason: e.to_string(),
+			}),
+		}
+	}
+	refusal
+		.space
+		.sort_by(|a, b| a.destination.cmp(&b.destination));
+
+	if refusal.space.is_empty() && refusal.not_writable.is_empty() {
+		None
+	} else {
+		Some(refusal)
+	}
+}
@@ -1,5 +1,11 @@
+use std::{
+	sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+	time::Duration,
+};
+
+use crossbeam_channel::Sender;
 use czkawka_core::{
-	common::model::ToolType,
+	common::model::{CheckingMethod, ToolType},
 	common::progress_data::{CurrentStage, ProgressData},
 };
 use humansize::{DECIMAL, format_size};
@@ -13,8 +19,17 @@ pub struct ProgressToSend {
 	pub step_name: String,
 }
 
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
 pub fn process_progress_data(progress_data: ProgressData) -> ProgressToSend {
-	if progress_data.current_stage_idx == 0 {
+	if matches!(
+		progress_data.sstage,
+		CurrentStage::DeletingFiles
+			| CurrentStage::MovingFiles
+			| CurrentStage::RenamingFiles
+	) {
+		progress_file_operation(&progress_data)
+	} else if progress_data.current_stage_idx == 0 {
 		progress_collect_items(
 			&progress_data,
 			progress_data.tool_type != ToolType::EmptyFolders,
@@ -129,6 +144,34 @@ fn progress_default(item: &ProgressData) -> ProgressToSend {
 	}
 }
 
+fn progress_file_operation(item: &ProgressData) -> ProgressToSend {
+	let items_stats =
+		format!("{}/{}", item.entries_checked, item.entries_to_check);
+	let step_name = match item.sstage {
+		CurrentStage::DeletingFiles => format!("Deleted {items_stats} file"),
+		CurrentStage::RenamingFiles => format!("Renamed {items_stats} file"),
+		_ => format!(
+			"Transferred {items_stats} file ({}/{})",
+			format_size(item.bytes_checked, DECIMAL),
+			format_size(item.bytes_to_check, DECIMAL)
+		),
+	};
+	// Bytes tell more about how long a big copy takes than files do
+	let progress = if item.bytes_to_check != 0 {
+		item.bytes_checked as f64 / item.bytes_to_check as f64
+	} else if item.entries_to_check != 0 {
+		item.entries_checked as f64 / item.entries_to_check as f64
+	} else {
+		0.0
+	};
+	let progress = (progress.min(0.99) * 100.0) as i32;
+	ProgressToSend {
+		all_progress: progress,
+		current_progress: progress,
+		step_name,
+	}
+}
+
 // Used when current stage not have enough data to show status, so we show only all_stages
 // Happens if we are searching files and we don't know how many files we need to check
 fn no_current_stage_get_data(item: &ProgressData) -> (i32, i32) {
@@ -157,3 +200,48 @@ fn common_get_data(item: &ProgressData) -> (i32, i32) {
 		((all_stages * 100.0) as i32, 0)
 	}
 }
+
+#[derive(Default)]
+pub(crate) struct Counters {
+	pub entries: AtomicUsize,
+	pub bytes: AtomicU64,
+}
+
+// Reports the counters of a stage every `PROGRESS_INTERVAL` until `f` returns
+pub(crate) fn with_stage_progress<R>(
+	progress_tx: &Sender<ProgressData>,
+	stage: CurrentStage,
+	(stage_idx, max_stage_idx): (u8, u8),
+	(entries_to_check, bytes_to_check): (usize, u64),
+	f: impl FnOnce(&Counters) -> R,
+) -> R {
+	let counters = Counters::default();
+	let done = AtomicBool::new(false);
+
+	let send = || {
+		let _ = progress_tx.send(ProgressData {
+			sstage: stage,
+			checking_method: CheckingMethod::None,
+			current_stage_idx: stage_idx,
+			max_stage_idx,
+			entries_checked: counters.entries.load(Ordering::Relaxed),
+			entries_to_check,
+			bytes_checked: counters.bytes.load(Ordering::Relaxed),
+			bytes_to_check,
+			tool_type: ToolType::None,
+		});
+	};
+
+	std::thread::scope(|scope| {
+		scope.spawn(|| {
+			while !done.load(Ordering::Relaxed) {
+				send();
+				std::thread::sleep(PROGRESS_INTERVAL);
+			}
+		});
+		let result = f(&counters);
+		done.store(true, Ordering::Relaxed);
+		send();
+		result
+	})
+}
@@ -0,0 +1,189 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+use glob::{MatchOptions, Pattern};
+
+use crate::{preflight, sink::ResultSink};
+
+const PROTECTED_PATHS_FILE: &str = "protected_paths.json";
+
+// Glob patterns of paths delete_files, move_files, rename_ext and
+// link_duplicates refuse to touch. A path is protected when a pattern matches
+// it or one of the folders it is in, so `/usr` protects everything below it
+// and `**/.git` every git folder. The reference folders of the last scan of
+// each tool are always protected too.
+
+// External drives are mounted below `/run/media` or `/media`, neither is
+// protected
+#[cfg(target_os = "linux")]
+pub fn default_protected_paths() -> Vec<String> {
+	[
+		"/bin", "/boot", "/dev", "/etc", "/lib", "/lib32", "/lib64", "/proc",
+		"/sbin", "/sys", "/usr", "/var/lib", "/var/log",
+	]
+	.map(String::from)
+	.to_vec()
+}
+
+// The temporary folders are in `/private/var/folders`, so only parts of
+// `/private` are protected
+#[cfg(target_os = "macos")]
+pub fn default_protected_paths() -> Vec<String> {
+	[
+		"/System",
+		"/Library",
+		"/bin",
+		"/sbin",
+		"/usr",
+		"/etc",
+		"/private/etc",
+		"/private/var/db",
+		"/dev",
+		"/Applications/*.app",
+	]
+	.map(String::from)
+	.to_vec()
+}
+
+#[cfg(target_os = "windows")]
+pub fn default_protected_paths() -> Vec<String> {
+	let drive = std::env::var("SystemDrive").unwrap_or("C:".to_string());
+	[
+		"Windows",
+		"Program Files",
+		"Program Files (x86)",
+		"ProgramData",
+	]
+	.map(|dir| format!("{}\\{}", drive, dir))
+	.to_vec()
+}
+
+#[cfg(not(any(
+	target_os = "linux",
+	target_os = "macos",
+	target_os = "windows"
+)))]
+pub fn default_protected_paths() -> Vec<String> {
+	vec![]
+}
+
+fn protected_paths_file<S: ResultSink>(sink: &S) -> Option<PathBuf> {
+	sink.config_dir().map(|dir| dir.join(PROTECTED_PATHS_FILE))
+}
+
+// The saved patterns, or the defaults when none were saved
+pub fn list_protected_paths<S: ResultSink>(
+	sink: &S,
+) -> Result<Vec<String>, String> {
+	let Some(path) = protected_paths_file(sink) else {
+		return Ok(default_protected_paths());
+	};
+	let data = match fs::read(&path) {
+		Ok(data) => data,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+			return Ok(default_protected_paths());
+		}
+		Err(e) => return Err(e.to_string()),
+	};
+	serde_json::from_slice(&data)
+		.map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+// Replaces the saved patterns, an empty list protects nothing but the
+// reference folders
+pub fn save_protected_paths<S: ResultSink>(
+	sink: &S,
+	patterns: &[String],
+) -> Result<(), String> {
+	for pattern in patterns {
+		Pattern::new(pattern).map_err(|e| {
+			format!("Invalid protected path `{}`: {}", pattern, e)
+		})?;
+	}
+	let path = protected_paths_file(sink)
+		.ok_or("No config dir to keep protected paths in")?;
+	fs::create_dir_all(path.parent().unwrap()).map_err(|e| e.to_string())?;
+	let tmp_path = path.with_extension("json.tmp");
+	let data =
+		serde_json::to_vec_pretty(patterns).map_err(|e| e.to_string())?;
+	fs::write(&tmp_path, data).map_err(|e| e.to_string())?;
+	fs::rename(&tmp_path, path).map_err(|e| e.to_string())
+}
+
+pub(crate) struct ProtectedPaths {
+	patterns: Vec<(String, Pattern)>,
+	referenced: Vec<PathBuf>,
+}
+
+impl ProtectedPaths {
+	// A file operation goes on without the saved patterns only when there are
+	// none, a list that cannot be read falls back to the defaults
+	pub(crate) fn load<S: ResultSink>(sink: &S) -> Self {
+		let patterns = list_protected_paths(sink).unwrap_or_else(|e| {
+			eprintln!("Failed to load protected paths: {}", e);
+			default_protected_paths()
+		});
+		let referenced = sink.with_state(|state| {
+			state
+				.referenced_directories
+				.values()
+				.flatten()
+				.cloned()
+				.collect()
+		});
+		ProtectedPaths {
+			patterns: patterns
+				.into_iter()
+				.filter_map(|p| Some((p.clone(), Pattern::new(&p).ok()?)))
+				.collect(),
+			referenced,
+		}
+	}
+
+	pub(crate) fn check(&self, path: &Path) -> Result<(), String> {
+		let path = resolve(path);
+		let options = MatchOptions {
+			case_sensitive: !cfg!(any(
+				target_os = "windows",
+				target_os = "macos"
+			)),
+			require_literal_separator: true,
+			require_literal_leading_dot: false,
+		};
+		for ancestor in path.ancestors() {
+			let pattern = self.patterns.iter().find(|(_, pattern)| {
+				pattern.matches_path_with(ancestor, options)
+			});
+			if let Some((pattern, _)) = pattern {
+				return Err(format!("it is protected by `{}`", pattern));
+			}
+		}
+		if let Some(root) =
+			self.referenced.iter().find(|root| path.starts_with(root))
+		{
+			return Err(format!(
+				"it is in the reference folder `{}`",
+				root.to_string_lossy()
+			));
+		}
+		Ok(())
+	}
+}
+
+// The real location of `path`, so neither `..` nor a link to one of its
+// folders gets around a pattern. The file itself is not followed, a link to a
+// protected file can be removed.
+pub(crate) fn resolve(path: &Path) -> PathBuf {
+	let absolute = std::path::absolute(path).unwrap_or(path.to_path_buf());
+	let Some(parent) = absolute.parent() else {
+		return absolute;
+	};
+	// The folders that do not exist yet, e.g. of a destination, are kept
+	let resolved = preflight::existing_ancestor(parent).and_then(|existing| {
+		let rest = absolute.strip_prefix(existing).ok()?;
+		Some(fs::canonicalize(existing).ok()?.join(rest))
+	});
+	resolved.unwrap_or(absolute)
+}
@@ -0,0 +1,370 @@
+use std::{
+	fs,
+	path::{Component, Path, PathBuf},
+	time::Duration,
+};
+
+use rusqlite::{Connection, params};
+use serde::Serialize;
+
+use crate::{
+	move_files::move_item, protected_paths::ProtectedPaths, sink::ResultSink,
+	utils::unix_now,
+};
+
+const QUARANTINE_DIR: &str = "quarantine";
+const MANIFEST_DB: &str = "manifest.db";
+
+pub type QuarantineId = i64;
+
+// A file delete_files moved into the quarantine instead of deleting it. The
+// quarantine keeps the whole original path below a folder per batch, e.g.
+// `quarantine/1700000000/home/user/a.jpg`.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantineEntry {
+	pub id: QuarantineId,
+	pub original_path: PathBuf,
+	pub quarantined_path: PathBuf,
+	pub quarantined_at: u64,
+	pub size: u64,
+}
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantineResult {
+	pub success_paths: Vec<String>,
+	pub errors: Vec<String>,
+}
+
+// `configured`, or the `quarantine` folder of the data dir
+pub(crate) fn quarantine_dir<S: ResultSink>(
+	sink: &S,
+	configured: Option<&str>,
+) -> Result<PathBuf, String> {
+	match configured {
+		Some(dir) => Ok(PathBuf::from(dir)),
+		None => sink
+			.data_dir()
+			.map(|dir| dir.join(QUARANTINE_DIR))
+			.ok_or_else(|| "No data dir to keep a quarantine in".to_string()),
+	}
+}
+
+fn open(dir: &Path) -> Result<Connection, String> {
+	fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+	let conn =
+		Connection::open(dir.join(MANIFEST_DB)).map_err(|e| e.to_string())?;
+	// The files of a batch are recorded from several threads at once
+	conn.busy_timeout(Duration::from_secs(10))
+		.map_err(|e| e.to_string())?;
+	conn.execute_batch(
+		"CREATE TABLE IF NOT EXISTS entries (
+			id INTEGER PRIMARY KEY AUTOINCREMENT,
+			original_path TEXT NOT NULL,
+			quarantined_path TEXT NOT NULL,
+			quarantined_at INTEGER NOT NULL,
+			size INTEGER NOT NULL
+		);",
+	)
+	.map_err(|e| e.to_string())?;
+	Ok(conn)
+}
+
+// A new folder for the files of one delete_files batch. With `create` unset,
+// for dry runs, only the path it would most likely get is returned.
+pub(crate) fn new_batch(dir: &Path, create: bool) -> Result<PathBuf, String> {
+	let now = unix_now();
+	if !create {
+		return Ok(dir.join(now.to_string()));
+	}
+	fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+	// Another batch may have started within the same second
+	for n in 0.. {
+		let name = match n {
+			0 => now.to_string(),
+			n => format!("{}-{}", now, n),
+		};
+		let batch = dir.join(name);
+		match fs::create_dir(&batch) {
+			Ok(()) => return Ok(batch),
+			Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+			Err(e) => return Err(e.to_string()),
+		}
+	}
+	unreachable!()
+}
+
+// Where `original` is kept in `batch`
+pub(crate) fn quarantined_path(
+	batch: &Path,
+	original: &Path,
+) -> Result<PathBuf, String> {
+	let original = std::path::absolute(original).map_err(|e| e.to_string())?;
+	let mut path = batch.to_path_buf();
+	for component in original.components() {
+		match component {
+			Component::Normal(name) => path.push(name),
+			// The drive, e.g. `C` for `C:\`
+			Component::Prefix(prefix) => path.push(
+				prefix
+					.as_os_str()
+					.to_string_lossy()
+					.replace(|c: char| !c.is_alphanumeric(), ""),
+			),
+			_ => {}
+		}
+	}
+	Ok(path)
+}
+
+// Moves `original` to where it is kept in `batch` and records it in the
+// manifest of `dir`. A file that cannot be recorded is moved back, it could
+// be neither restored nor purged.
+pub(crate) fn quarantine_file(
+	dir: &Path,
+	batch: &Path,
+	original: &Path,
+) -> Result<PathBuf, String> {
+	let dest = quarantined_path(batch, original)?;
+	if let Some(parent) = dest.parent() {
+		fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+	}
+	move_item(original, &dest).map_err(|e| e.to_string())?;
+	let recorded = open(dir).and_then(|conn| {
+		conn.execute(
+			"INSERT INTO entries
+				(original_path, quarantined_path, quarantined_at, size)
+				VALUES (?, ?, ?, ?)",
+			params![
+				original.to_string_lossy(),
+				dest.to_string_lossy(),
+				unix_now(),
+				crate::preflight::size_of(&dest),
+			],
+		)
+		.map_err(|e| e.to_string())
+	});
+	if let Err(e) = recorded {
+		return match move_item(&dest, original) {
+			Ok(()) => {
+				remove_empty_parents(&dest, dir);
+				Err(format!("Failed to record it in the quarantine: {}", e))
+			}
+			Err(moved) => Err(format!(
+				"Failed to record it in the quarantine: {}, it was left at \
+				`{}`: {}",
+				e,
+				dest.to_string_lossy(),
+				moved
+			)),
+		};
+	}
+	Ok(dest)
+}
+
+// Moves a file quarantined by a journaled delete_files batch back and drops
+// its entry. The manifest is in the quarantine folder above the batch.
+pub(crate) fn undo_quarantine(quarantined: &Path) -> Result<(), String> {
+	for dir in quarantined.ancestors().skip(1) {
+		if !dir.join(MANIFEST_DB).is_file() {
+			continue;
+		}
+		let conn = open(dir)?;
+		let path = quarantined.to_string_lossy();
+		let entry =
+			load_entries(&conn, "quarantined_path = ?", params![path])?.pop();
+		if let Some(entry) = entry {
+			return restore(&conn, dir, &entry);
+		}
+	}
+	Err("it is no longer in the quarantine".to_string())
+}
+
+// Oldest entries first
+pub fn list_quarantine<S: ResultSink>(
+	sink: &S,
+	dir: Option<&str>,
+) -> Result<Vec<QuarantineEntry>, String> {
+	let dir = quarantine_dir(sink, dir)?;
+	if !dir.join(MANIFEST_DB).exists() {
+		return Ok(vec![]);
+	}
+	load_entries(&open(&dir)?, "1", params![])
+}
+
+fn load_entries(
+	conn: &Connection,
+	filter: &str,
+	params: impl rusqlite::Params,
+) -> Result<Vec<QuarantineEntry>, String> {
+	let mut stmt = conn
+		.prepare(&format!(
+			"SELECT id, original_path, quarantined_path, quarantined_at, size
+				FROM entries WHERE {} ORDER BY id",
+			filter
+		))
+		.map_err(|e| e.to_string())?;
+	stmt.query_map(params, |row| {
+		let original_path: String = row.get(1)?;
+		let quarantined_path: String = row.get(2)?;
+		Ok(QuarantineEntry {
+			id: row.get(0)?,
+			original_path: PathBuf::from(original_path),
+			quarantined_path: PathBuf::from(quarantined_path),
+			quarantined_at: row.get(3)?,
+			size: row.get(4)?,
+		})
+	})
+	.and_then(|rows| rows.collect())
+	.map_err(|e| e.to_string())
+}
+
+pub fn restore_quarantined<S: ResultSink>(
+	sink: &S,
+	ids: Vec<QuarantineId>,
+	dir: Option<String>,
+) {
+	let sink = sink.clone();
+	std::thread::spawn(move || {
+		let result = restore_impl(&sink, &ids, dir.as_deref());
+		sink.emit_event("quarantine-restore-result", result);
+	});
+}
+
+// Moves the entries back to their original path, which must be free again
+fn restore_impl<S: ResultSink>(
+	sink: &S,
+	ids: &[QuarantineId],
+	dir: Option<&str>,
+) -> QuarantineResult {
+	let mut result = QuarantineResult::default();
+	let restored = quarantine_dir(sink, dir).and_then(|dir| {
+		let conn = open(&dir)?;
+		for &id in ids {
+			let entry = load_entries(&conn, "id = ?", params![id])?.pop();
+			let Some(entry) = entry else {
+				result
+					.errors
+					.push(format!("No quarantined file with id {}", id));
+				continue;
+			};
+			let original = entry.original_path.to_string_lossy().to_string();
+			match restore(&conn, &dir, &entry) {
+				Ok(()) => result.success_paths.push(original),
+				Err(e) => result
+					.errors
+					.push(format!("`{}` Failed, reason: {}", original, e)),
+			}
+		}
+		Ok(())
+	});
+	if let Err(e) = restored {
+		result.errors.push(e);
+	}
+	result
+}
+
+fn restore(
+	conn: &Connection,
+	dir: &Path,
+	entry: &QuarantineEntry,
+) -> Result<(), String> {
+	restore_entry(entry)?;
+	conn.execute("DELETE FROM entries WHERE id = ?", params![entry.id])
+		.map_err(|e| e.to_string())?;
+	remove_empty_parents(&entry.quarantined_path, dir);
+	Ok(())
+}
+
+fn restore_entry(entry: &QuarantineEntry) -> Result<(), String> {
+	if fs::symlink_metadata(&entry.original_path).is_ok() {
+		return Err("the original path exists again".to_string());
+	}
+	if fs::symlink_metadata(&entry.quarantined_path).is_err() {
+		return Err("it is no longer in the quarantine".to_string());
+	}
+	if let Some(parent) = entry.original_path.parent() {
+		fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+	}
+	move_item(&entry.quarantined_path, &entry.original_path)
+		.map_err(|e| e.to_string())
+}
+
+pub fn purge_quarantine<S: ResultSink>(
+	sink: &S,
+	older_than_days: u64,
+	dir: Option<String>,
+) {
+	let sink = sink.clone();
+	std::thread::spawn(move || {
+		let result = purge_impl(&sink, older_than_days, dir.as_deref());
+		sink.emit_event("quarantine-purge-result", result);
+	});
+}
+
+// Deletes the entries quarantined more than `older_than_days` days ago for
+// good. Entries whose file is already gone are only dropped from the
+// manifest.
+fn purge_impl<S: ResultSink>(
+	sink: &S,
+	older_than_days: u64,
+	dir: Option<&str>,
+) -> QuarantineResult {
+	let mut result = QuarantineResult::default();
+	let cutoff = unix_now().saturating_sub(older_than_days * 86400);
+	let protected = ProtectedPaths::load(sink);
+	let purged = quarantine_dir(sink, dir).and_then(|dir| {
+		if !dir.join(MANIFEST_DB).exists() {
+			return Ok(());
+		}
+		let conn = open(&dir)?;
+		let entries =
+			load_entries(&conn, "quarantined_at <= ?", params![cutoff])?;
+		for entry in entries {
+			let path = &entry.quarantined_path;
+			let removed = protected.check(path).and_then(|()| {
+				match fs::symlink_metadata(path) {
+					Ok(metadata) if metadata.is_dir() => {
+						fs::remove_dir_all(path)
+					}
+					Ok(_) => fs::remove_file(path),
+					Err(_) => Ok(()),
+				}
+				.map_err(|e| e.to_string())
+			});
+			let original = entry.original_path.to_string_lossy().to_string();
+			match removed {
+				Ok(()) => {
+					conn.execute(
+						"DELETE FROM entries WHERE id = ?",
+						params![entry.id],
+					)
+					.map_err(|e| e.to_string())?;
+					remove_empty_parents(path, &dir);
+					result.success_paths.push(original);
+				}
+				Err(e) => result
+					.errors
+					.push(format!("`{}` Failed, reason: {}", original, e)),
+			}
+		}
+		Ok(())
+	});
+	if let Err(e) = purged {
+		result.errors.push(e);
+	}
+	result
+}
+
+// Removes the folders left empty above `path`, up to the quarantine itself
+fn remove_empty_parents(path: &Path, dir: &Path) {
+	for parent in path.ancestors().skip(1) {
+		if parent == dir || !parent.starts_with(dir) {
+			break;
+		}
+		if fs::remove_dir(parent).is_err() {
+			break;
+		}
+	}
+}
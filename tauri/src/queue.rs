@@ -0,0 +1,176 @@
+use std::{
+	collections::HashMap,
+	sync::{
+		Arc,
+		atomic::{AtomicBool, Ordering},
+	},
+	time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	job::{JobId, JobInfo, run_job},
+	scaner::Tool,
+	settings::Settings,
+	sink::ResultSink,
+	state::AppState,
+};
+
+pub type QueueId = u64;
+
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueItem {
+	pub tool: Tool,
+	pub settings: Settings,
+}
+
+#[derive(Clone)]
+pub struct QueueHandle {
+	pub id: QueueId,
+	paused: Arc<AtomicBool>,
+	cancelled: Arc<AtomicBool>,
+}
+
+struct Queue {
+	handle: QueueHandle,
+	current_job: Option<JobId>,
+}
+
+#[derive(Default)]
+pub struct Queues {
+	next_id: QueueId,
+	queues: HashMap<QueueId, Queue>,
+}
+
+impl Queues {
+	pub fn start(&mut self) -> QueueHandle {
+		self.next_id += 1;
+		let handle = QueueHandle {
+			id: self.next_id,
+			paused: Arc::new(AtomicBool::new(false)),
+			cancelled: Arc::new(AtomicBool::new(false)),
+		};
+		self.queues.insert(
+			handle.id,
+			Queue {
+				handle: handle.clone(),
+				current_job: None,
+			},
+		);
+		handle
+	}
+
+	// A running scan cannot be suspended, so pausing takes effect before the
+	// next tool of the queue starts
+	pub fn set_paused(&mut self, id: QueueId, paused: bool) -> bool {
+		match self.queues.get(&id) {
+			Some(queue) => {
+				queue.handle.paused.store(paused, Ordering::Relaxed);
+				true
+			}
+			None => false,
+		}
+	}
+
+	pub fn ids(&self) -> Vec<QueueId> {
+		self.queues.keys().copied().collect()
+	}
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueSummary {
+	pub queue_id: QueueId,
+	pub cancelled: bool,
+	pub jobs: Vec<JobInfo>,
+	pub skipped: Vec<Tool>,
+}
+
+pub fn start_queue<S: ResultSink>(sink: &S) -> QueueHandle {
+	sink.with_state(|state| state.queues.start())
+}
+
+// Stops the queue and the job it is currently running
+pub fn cancel_queue(state: &mut AppState, id: QueueId) -> bool {
+	let Some(queue) = state.queues.queues.get(&id) else {
+		return false;
+	};
+	queue.handle.cancelled.store(true, Ordering::Relaxed);
+	if let Some(job_id) = queue.current_job {
+		state.jobs.cancel(job_id);
+	}
+	true
+}
+
+pub fn cancel_all_queues(state: &mut AppState) {
+	for id in state.queues.ids() {
+		cancel_queue(state, id);
+	}
+}
+
+pub fn run_queue<S: ResultSink>(
+	sink: &S,
+	queue: QueueHandle,
+	items: Vec<QueueItem>,
+) {
+	let mut job_ids = vec![];
+	let mut skipped = vec![];
+
+	for item in items {
+		if !wait_while_paused(&queue) {
+			skipped.push(item.tool);
+			continue;
+		}
+
+		// Checked again under the state lock so a cancel cannot slip in
+		// between the check and the job being registered
+		let job = sink.with_state(|state| {
+			if queue.cancelled.load(Ordering::Relaxed) {
+				return None;
+			}
+			let job = state.jobs.start(vec![item.tool]);
+			if let Some(entry) = state.queues.queues.get_mut(&queue.id) {
+				entry.current_job = Some(job.id);
+			}
+			Some(job)
+		});
+		let Some(job) = job else {
+			skipped.push(item.tool);
+			continue;
+		};
+
+		job_ids.push(job.id);
+		run_job(sink, job, item.settings);
+	}
+
+	let summary = sink.with_state(|state| {
+		state.queues.queues.remove(&queue.id);
+		QueueSummary {
+			queue_id: queue.id,
+			cancelled: queue.cancelled.load(Ordering::Relaxed),
+			jobs: job_ids
+				.iter()
+				.filter_map(|id| state.jobs.info(*id))
+				.collect(),
+			skipped,
+		}
+	});
+
+	sink.emit_event("queue-summary", summary);
+}
+
+fn wait_while_paused(queue: &QueueHandle) -> bool {
+	loop {
+		if queue.cancelled.load(Ordering::Relaxed) {
+			return false;
+		}
+		if !queue.paused.load(Ordering::Relaxed) {
+			return true;
+		}
+		std::thread::sleep(PAUSE_POLL_INTERVAL);
+	}
+}
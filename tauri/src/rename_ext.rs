@@ -1,12 +1,35 @@
-use std::{fs, path::PathBuf};
+use std::{
+	collections::HashSet,
+	fs,
+	path::{Path, PathBuf},
+	sync::{
+		Mutex,
+		atomic::{AtomicBool, Ordering},
+	},
+};
 
+use czkawka_core::common::progress_data::CurrentStage;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Emitter};
+
+use crate::{
+	job::{JobHandle, JobId, run_job_with},
+	journal::{
+		Action, JournalEntry, OperationId, PlannedAction, record_operation,
+	},
+	preflight,
+	progress::{Counters, with_stage_progress},
+	protected_paths::ProtectedPaths,
+	sink::ResultSink,
+};
 
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Options {
 	items: Vec<Item>,
+	// Only check what would be renamed
+	#[serde(default)]
+	dry_run: bool,
 }
 
 #[derive(Deserialize)]
@@ -18,23 +41,79 @@ struct Item {
 #[derive(Serialize, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 struct RenameExtResult {
+	job_id: JobId,
+	// Set when the job was cancelled before every file was renamed
+	cancelled: bool,
 	success_paths: Vec<String>,
 	errors: Vec<String>,
+	// Journal entry to undo the renames with
+	operation_id: Option<OperationId>,
+	// Set by a dry run instead of renaming anything
+	planned: Option<Vec<PlannedAction>>,
+	#[serde(skip)]
+	journal: Vec<JournalEntry>,
 }
 
-pub fn rename_ext(app: AppHandle, options: Options) {
-	std::thread::spawn(move || {
-		let result = rename_ext_impl(options);
-		app.emit("rename-ext-result", result).unwrap();
+// Renames in the background as a job, which reports progress and can be
+// cancelled between files
+pub fn rename_ext<S: ResultSink>(sink: &S, options: Options) -> JobId {
+	let job = sink.with_state(|state| state.jobs.start_operation("rename_ext"));
+	let job_id = job.id;
+	let sink = sink.clone();
+	std::thread::spawn(move || run_rename_ext(&sink, job, options));
+	job_id
+}
+
+pub fn run_rename_ext<S: ResultSink>(
+	sink: &S,
+	job: JobHandle,
+	options: Options,
+) {
+	let job_id = job.id;
+	let dry_run = options.dry_run;
+	let protected = ProtectedPaths::load(sink);
+	let mut result = run_job_with(sink, job, |job_sink| {
+		let (stop_flag, progress_tx) = job_sink.stop_flag_and_progress_tx();
+		with_stage_progress(
+			&progress_tx,
+			CurrentStage::RenamingFiles,
+			(0, 0),
+			(options.items.len(), 0),
+			|counters| {
+				rename_ext_impl(options, &protected, &stop_flag, counters)
+			},
+		)
 	});
+	result.job_id = job_id;
+	if dry_run {
+		result.planned =
+			Some(result.journal.drain(..).map(Into::into).collect());
+	} else {
+		result.operation_id =
+			record_operation(sink, "rename_ext", &result.journal);
+	}
+	sink.emit_event("rename-ext-result", result);
 }
 
-fn rename_ext_impl(options: Options) -> RenameExtResult {
-	let Options { items } = options;
+fn rename_ext_impl(
+	options: Options,
+	protected: &ProtectedPaths,
+	stop_flag: &AtomicBool,
+	counters: &Counters,
+) -> RenameExtResult {
+	let Options { items, dry_run } = options;
+	// New names taken by earlier files of the batch, which may not have been
+	// renamed yet
+	let claimed = Mutex::new(HashSet::new());
 
 	items
 		.par_iter()
 		.fold(RenameExtResult::default, |mut result, item| {
+			if stop_flag.load(Ordering::Relaxed) {
+				result.cancelled = true;
+				return result;
+			}
+			counters.entries.fetch_add(1, Ordering::Relaxed);
 			let old_path = PathBuf::from(&item.path);
 			let mut new_path = old_path.clone();
 
@@ -45,8 +124,32 @@ fn rename_ext_impl(options: Options) -> RenameExtResult {
 				return result;
 			}
 
-			match fs::rename(old_path, new_path) {
-				Ok(_) => result.success_paths.push(item.path.clone()),
+			if let Err(err) = protected.check(&old_path) {
+				result
+					.errors
+					.push(format!("`{}` Failed, reason: {}", item.path, err));
+				return result;
+			}
+
+			let fs_result =
+				claim(&old_path, &new_path, &claimed).and_then(|()| {
+					if dry_run {
+						check_rename(&old_path)
+					} else {
+						fs::rename(&old_path, &new_path)
+							.map_err(|e| e.to_string())
+					}
+				});
+
+			match fs_result {
+				Ok(_) => {
+					result.journal.push(JournalEntry::new(
+						Action::Rename,
+						old_path,
+						Some(new_path),
+					));
+					result.success_paths.push(item.path.clone());
+				}
 				Err(err) => result
 					.errors
 					.push(format!("`{}` Failed, reason: {}", item.path, err)),
@@ -55,8 +158,54 @@ fn rename_ext_impl(options: Options) -> RenameExtResult {
 			result
 		})
 		.reduce(RenameExtResult::default, |mut acc, mut x| {
+			acc.cancelled |= x.cancelled;
 			acc.success_paths.append(&mut x.success_paths);
 			acc.errors.append(&mut x.errors);
+			acc.journal.append(&mut x.journal);
 			acc
 		})
 }
+
+// A rename never replaces another file, `fs::rename` would overwrite it
+fn claim(
+	old_path: &Path,
+	new_path: &Path,
+	claimed: &Mutex<HashSet<PathBuf>>,
+) -> Result<(), String> {
+	let mut claimed = claimed.lock().unwrap();
+	if claimed.contains(new_path) {
+		return Err(format!(
+			"`{}` is also the new name of another file",
+			new_path.to_string_lossy()
+		));
+	}
+	// Where names are case insensitive, a new name differing only in case
+	// is the file itself
+	if fs::symlink_metadata(new_path).is_ok() && !same_file(old_path, new_path)
+	{
+		return Err(format!("`{}` already exists", new_path.to_string_lossy()));
+	}
+	claimed.insert(new_path.to_path_buf());
+	Ok(())
+}
+
+#[cfg(unix)]
+fn same_file(a: &Path, b: &Path) -> bool {
+	use std::os::unix::fs::MetadataExt;
+	match (fs::symlink_metadata(a), fs::symlink_metadata(b)) {
+		(Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+		_ => false,
+	}
+}
+
+#[cfg(not(unix))]
+fn same_file(a: &Path, b: &Path) -> bool {
+	a.to_string_lossy().to_lowercase() == b.to_string_lossy().to_lowercase()
+}
+
+fn check_rename(path: &Path) -> Result<(), String> {
+	if fs::symlink_metadata(path).is_err() {
+		return Err("it does not exist".to_string());
+	}
+	preflight::check_removable(path)
+}
@@ -1,10 +1,87 @@
+use std::path::{Path, PathBuf};
+
 use czkawka_core::common::tool_data::CommonData;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+	bad_extensions, big_files, broken_files, duplicate_files, empty_files,
+	empty_folders, invalid_symlinks, music_duplicates,
+	persist::PersistSink,
+	protected_paths::resolve,
 	settings::Settings,
+	similar_images, similar_videos,
+	sink::ResultSink,
+	state::{AppState, ScanerState},
+	temporary_files,
 	utils::{convert_strs_to_path_bufs, split_str_with_comma},
 };
 
+// Serialized with the same names the frontend uses for its tools
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Tool {
+	#[serde(rename = "Duplicate Files")]
+	DuplicateFiles,
+	#[serde(rename = "Empty Folders")]
+	EmptyFolders,
+	#[serde(rename = "Big Files")]
+	BigFiles,
+	#[serde(rename = "Empty Files")]
+	EmptyFiles,
+	#[serde(rename = "Temporary Files")]
+	TemporaryFiles,
+	#[serde(rename = "Similar Images")]
+	SimilarImages,
+	#[serde(rename = "Similar Videos")]
+	SimilarVideos,
+	#[serde(rename = "Music Duplicates")]
+	MusicDuplicates,
+	#[serde(rename = "Invalid Symlinks")]
+	InvalidSymlinks,
+	#[serde(rename = "Broken Files")]
+	BrokenFiles,
+	#[serde(rename = "Bad Extensions")]
+	BadExtensions,
+}
+
+impl Tool {
+	pub const ALL: [Tool; 11] = [
+		Tool::DuplicateFiles,
+		Tool::EmptyFolders,
+		Tool::BigFiles,
+		Tool::EmptyFiles,
+		Tool::TemporaryFiles,
+		Tool::SimilarImages,
+		Tool::SimilarVideos,
+		Tool::MusicDuplicates,
+		Tool::InvalidSymlinks,
+		Tool::BrokenFiles,
+		Tool::BadExtensions,
+	];
+
+	pub fn cmd(self) -> &'static str {
+		match self {
+			Tool::DuplicateFiles => "scan_duplicate_files",
+			Tool::EmptyFolders => "scan_empty_folders",
+			Tool::BigFiles => "scan_big_files",
+			Tool::EmptyFiles => "scan_empty_files",
+			Tool::TemporaryFiles => "scan_temporary_files",
+			Tool::SimilarImages => "scan_similar_images",
+			Tool::SimilarVideos => "scan_similar_videos",
+			Tool::MusicDuplicates => "scan_music_duplicates",
+			Tool::InvalidSymlinks => "scan_invalid_symlinks",
+			Tool::BrokenFiles => "scan_broken_files",
+			Tool::BadExtensions => "scan_bad_extensions",
+		}
+	}
+
+	// Accepts both `scan_duplicate_files` and `duplicate_files`
+	pub fn from_cmd(cmd: &str) -> Option<Tool> {
+		Tool::ALL.into_iter().find(|tool| {
+			tool.cmd() == cmd || tool.cmd().strip_prefix("scan_") == Some(cmd)
+		})
+	}
+}
+
 pub fn spawn_scaner_thread<F: FnOnce() + Send + 'static>(f: F) {
 	std::thread::Builder::new()
 		.spawn(f)
@@ -38,3 +115,80 @@ pub fn set_scaner_common_settings<T: CommonData>(
 	scaner.set_use_cache(settings.use_cache);
 	scaner.set_save_also_as_json(settings.save_also_as_json);
 }
+
+// Replaces the included and reference folders of the last scan of `tools`
+// with those of a scan about to run with `settings`
+pub(crate) fn set_scan_directories<S: ResultSink>(
+	sink: &S,
+	tools: &[Tool],
+	settings: &Settings,
+) {
+	let resolved = |dirs: &[String]| -> Vec<_> {
+		dirs.iter().map(|dir| resolve(Path::new(dir))).collect()
+	};
+	// czkawka ignores the reference folders when every folder is one
+	let all_referenced = settings.included_directories.len()
+		== settings.included_directories_referenced.len();
+	sink.set_scaner_state(ScanDirectories {
+		tools: tools.to_vec(),
+		included: resolved(&settings.included_directories),
+		referenced: if all_referenced {
+			vec![]
+		} else {
+			resolved(&settings.included_directories_referenced)
+		},
+	});
+}
+
+struct ScanDirectories {
+	tools: Vec<Tool>,
+	included: Vec<PathBuf>,
+	referenced: Vec<PathBuf>,
+}
+
+impl ScanerState for ScanDirectories {
+	fn store(self, state: &mut AppState) {
+		for tool in self.tools {
+			state
+				.included_directories
+				.insert(tool, self.included.clone());
+			state
+				.referenced_directories
+				.insert(tool, self.referenced.clone());
+			// A sweep marks its tools again once the directories are set
+			state.swept_tools.remove(&tool);
+		}
+	}
+}
+
+pub fn run_tool<S: ResultSink>(sink: &S, tool: Tool, settings: Settings) {
+	set_scan_directories(sink, &[tool], &settings);
+	let sink = &PersistSink::new(sink.clone(), settings.clone());
+	match tool {
+		Tool::DuplicateFiles => {
+			duplicate_files::scan_duplicate_files(sink, settings)
+		}
+		Tool::EmptyFolders => empty_folders::scan_empty_folders(sink, settings),
+		Tool::BigFiles => big_files::scan_big_files(sink, settings),
+		Tool::EmptyFiles => empty_files::scan_empty_files(sink, settings),
+		Tool::TemporaryFiles => {
+			temporary_files::scan_temporary_files(sink, settings)
+		}
+		Tool::SimilarImages => {
+			similar_images::scan_similar_images(sink, settings)
+		}
+		Tool::SimilarVideos => {
+			similar_videos::scan_similar_videos(sink, settings)
+		}
+		Tool::MusicDuplicates => {
+			music_duplicates::scan_music_duplicates(sink, settings)
+		}
+		Tool::InvalidSymlinks => {
+			invalid_symlinks::scan_invalid_symlinks(sink, settings)
+		}
+		Tool::BrokenFiles => broken_files::scan_broken_files(sink, settings),
+		Tool::BadExtensions => {
+			bad_extensions::scan_bad_extensions(sink, settings)
+		}
+	}
+}
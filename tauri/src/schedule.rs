@@ -0,0 +1,373 @@
+use std::{
+	collections::HashSet,
+	fs::{self, OpenOptions},
+	io::{BufRead, BufReader, Write},
+	path::{Path, PathBuf},
+	sync::{Arc, Mutex, atomic::AtomicBool},
+	thread,
+	time::Duration,
+};
+
+use chrono::{DateTime, Local};
+use crossbeam_channel::Sender;
+use czkawka_core::common::progress_data::ProgressData;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	cron::Cron,
+	job::{JobId, JobStatus, run_job, start_job},
+	scaner::{Tool, spawn_scaner_thread},
+	settings::Settings,
+	sink::ResultSink,
+	state::{AppState, ScanerState},
+};
+
+const SCHEDULES_FILE: &str = "schedules.json";
+const HISTORY_FILE: &str = "schedule_history.jsonl";
+// Results and scan history of the scheduled runs, apart from those of the
+// scans shown in the app
+const RESULTS_DIR: &str = "schedule_results";
+
+// How often the scheduler looks for due schedules
+pub const SCHEDULER_TICK: Duration = Duration::from_secs(15);
+
+// Schedules are read and written by commands and the scheduler thread
+static SCHEDULES_LOCK: Mutex<()> = Mutex::new(());
+
+pub type ScheduleId = u64;
+
+// Runs `tools` with the settings of a preset whenever `cron` matches
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Schedule {
+	#[serde(default)]
+	pub id: ScheduleId,
+	pub name: String,
+	pub tools: Vec<Tool>,
+	pub settings: Settings,
+	pub cron: String,
+	#[serde(default = "enabled")]
+	pub enabled: bool,
+}
+
+fn enabled() -> bool {
+	true
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleInfo {
+	#[serde(flatten)]
+	pub schedule: Schedule,
+	pub next_run_at: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolSummary {
+	pub tool: Tool,
+	pub items: usize,
+	// First line of the scan message, e.g. "Found 3 empty files"
+	pub message: String,
+}
+
+// One line of the history log
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleRun {
+	pub schedule_id: ScheduleId,
+	pub name: String,
+	pub job_id: JobId,
+	pub status: JobStatus,
+	pub started_at: u64,
+	pub finished_at: Option<u64>,
+	pub results: Vec<ToolSummary>,
+}
+
+fn config_path<S: ResultSink>(sink: &S, file: &str) -> Result<PathBuf, String> {
+	sink.config_dir()
+		.map(|dir| dir.join(file))
+		.ok_or_else(|| "No config dir to keep schedules in".to_string())
+}
+
+fn load_schedules(path: &Path) -> Result<Vec<Schedule>, String> {
+	match fs::read(path) {
+		Ok(data) => serde_json::from_slice(&data)
+			.map_err(|e| format!("Failed to parse {}: {}", path.display(), e)),
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+		Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+	}
+}
+
+fn save_schedules(path: &Path, schedules: &[Schedule]) -> Result<(), String> {
+	fs::create_dir_all(path.parent().unwrap()).map_err(|e| e.to_string())?;
+	let tmp_path = path.with_extension("json.tmp");
+	let data = serde_json::to_vec(schedules).map_err(|e| e.to_string())?;
+	fs::write(&tmp_path, data).map_err(|e| e.to_string())?;
+	fs::rename(&tmp_path, path).map_err(|e| e.to_string())
+}
+
+pub fn list_schedules<S: ResultSink>(
+	sink: &S,
+) -> Result<Vec<ScheduleInfo>, String> {
+	let _lock = SCHEDULES_LOCK.lock().unwrap();
+	let schedules = load_schedules(&config_path(sink, SCHEDULES_FILE)?)?;
+	let now = Local::now();
+	Ok(schedules
+		.into_iter()
+		.map(|schedule| {
+			let next_run_at = schedule
+				.cron
+				.parse::<Cron>()
+				.ok()
+				.filter(|_| schedule.enabled)
+				.and_then(|cron| cron.next_after(now))
+				.map(|time| time.timestamp());
+			ScheduleInfo {
+				schedule,
+				next_run_at,
+			}
+		})
+		.collect())
+}
+
+// Adds the schedule when its id is 0, replaces the one with its id otherwise
+pub fn save_schedule<S: ResultSink>(
+	sink: &S,
+	mut schedule: Schedule,
+) -> Result<Schedule, String> {
+	schedule.cron.parse::<Cron>()?;
+	if schedule.tools.is_empty() {
+		return Err("A schedule needs at least one tool".to_string());
+	}
+
+	let _lock = SCHEDULES_LOCK.lock().unwrap();
+	let path = config_path(sink, SCHEDULES_FILE)?;
+	let mut schedules = load_schedules(&path)?;
+	if schedule.id == 0 {
+		schedule.id = schedules.iter().map(|s| s.id).max().unwrap_or(0) + 1;
+		schedules.push(schedule.clone());
+	} else {
+		let existing = schedules
+			.iter_mut()
+			.find(|s| s.id == schedule.id)
+			.ok_or_else(|| format!("No schedule with id {}", schedule.id))?;
+		*existing = schedule.clone();
+	}
+	save_schedules(&path, &schedules)?;
+	Ok(schedule)
+}
+
+// Returns whether the schedule existed
+pub fn delete_schedule<S: ResultSink>(
+	sink: &S,
+	id: ScheduleId,
+) -> Result<bool, String> {
+	let _lock = SCHEDULES_LOCK.lock().unwrap();
+	let path = config_path(sink, SCHEDULES_FILE)?;
+	let mut schedules = load_schedules(&path)?;
+	let count = schedules.len();
+	schedules.retain(|schedule| schedule.id != id);
+	if schedules.len() == count {
+		return Ok(false);
+	}
+	save_schedules(&path, &schedules)?;
+	Ok(true)
+}
+
+// Newest runs first
+pub fn schedule_history<S: ResultSink>(
+	sink: &S,
+	limit: Option<usize>,
+) -> Result<Vec<ScheduleRun>, String> {
+	let path = config_path(sink, HISTORY_FILE)?;
+	let file = match fs::File::open(&path) {
+		Ok(file) => file,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+			return Ok(vec![]);
+		}
+		Err(e) => return Err(e.to_string()),
+	};
+	// A line cut short by a crash is skipped
+	let mut runs: Vec<ScheduleRun> = BufReader::new(file)
+		.lines()
+		.map_while(Result::ok)
+		.filter_map(|line| serde_json::from_str(&line).ok())
+		.collect();
+	runs.reverse();
+	runs.truncate(limit.unwrap_or(usize::MAX));
+	Ok(runs)
+}
+
+fn append_history(path: &Path, run: &ScheduleRun) -> Result<(), String> {
+	fs::create_dir_all(path.parent().unwrap()).map_err(|e| e.to_string())?;
+	let mut line = serde_json::to_string(run).map_err(|e| e.to_string())?;
+	line.push('\n');
+	OpenOptions::new()
+		.create(true)
+		.append(true)
+		.open(path)
+		.and_then(|mut file| file.write_all(line.as_bytes()))
+		.map_err(|e| e.to_string())
+}
+
+// Finds the schedules that became due since the last tick and starts them.
+// Occurrences missed while the app was not running are not caught up, and a
+// schedule whose last run is still going is skipped.
+pub struct Scheduler {
+	checked_until: DateTime<Local>,
+	running: Arc<Mutex<HashSet<ScheduleId>>>,
+}
+
+impl Scheduler {
+	pub fn new(start: DateTime<Local>) -> Self {
+		Scheduler {
+			checked_until: start,
+			running: Arc::default(),
+		}
+	}
+
+	// Returns the ids of the schedules that were started
+	pub fn tick<S: ResultSink>(
+		&mut self,
+		sink: &S,
+		now: DateTime<Local>,
+	) -> Vec<ScheduleId> {
+		let since = std::mem::replace(&mut self.checked_until, now);
+		let schedules = match list_schedules(sink) {
+			Ok(schedules) => schedules,
+			Err(e) => {
+				eprintln!("Failed to load schedules: {}", e);
+				return vec![];
+			}
+		};
+
+		let mut started = vec![];
+		for ScheduleInfo { schedule, .. } in schedules {
+			let due = schedule.enabled
+				&& schedule
+					.cron
+					.parse::<Cron>()
+					.ok()
+					.and_then(|cron| cron.next_after(since))
+					.is_some_and(|next| next <= now);
+			if !due || !self.running.lock().unwrap().insert(schedule.id) {
+				continue;
+			}
+
+			started.push(schedule.id);
+			let sink = sink.clone();
+			let running = self.running.clone();
+			spawn_scaner_thread(move || {
+				let id = schedule.id;
+				run_schedule(&sink, schedule);
+				running.lock().unwrap().remove(&id);
+			});
+		}
+		started
+	}
+}
+
+// Checks the schedules every `SCHEDULER_TICK` for as long as the process runs
+pub fn run_scheduler<S: ResultSink>(sink: S) {
+	let mut scheduler = Scheduler::new(Local::now());
+	loop {
+		thread::sleep(SCHEDULER_TICK);
+		scheduler.tick(&sink, Local::now());
+	}
+}
+
+fn run_schedule<S: ResultSink>(sink: &S, schedule: Schedule) {
+	let summary_sink = SummarySink::new(sink.clone());
+	let job = start_job(&summary_sink, schedule.tools.clone());
+	let job_id = job.id;
+	run_job(&summary_sink, job, schedule.settings);
+
+	let Some(info) = sink.with_state(|state| state.jobs.info(job_id)) else {
+		return;
+	};
+	let run = ScheduleRun {
+		schedule_id: schedule.id,
+		name: schedule.name,
+		job_id,
+		status: info.status,
+		started_at: info.started_at,
+		finished_at: info.finished_at,
+		results: summary_sink.summaries.lock().unwrap().clone(),
+	};
+	match config_path(sink, HISTORY_FILE) {
+		Ok(path) => {
+			if let Err(e) = append_history(&path, &run) {
+				eprintln!("Failed to record schedule run: {}", e);
+			}
+		}
+		Err(e) => eprintln!("Failed to record schedule run: {}", e),
+	}
+	sink.emit_event("schedule-run", run);
+}
+
+// Keeps a summary of every result passing through it. The results of a
+// scheduled run are neither shown nor kept as scaner state, and are saved
+// into `RESULTS_DIR` of the config dir, so they replace nothing of the scans
+// shown in the app.
+#[derive(Clone)]
+struct SummarySink<S> {
+	sink: S,
+	summaries: Arc<Mutex<Vec<ToolSummary>>>,
+}
+
+impl<S: ResultSink> SummarySink<S> {
+	fn new(sink: S) -> Self {
+		Self {
+			sink,
+			summaries: Arc::default(),
+		}
+	}
+}
+
+impl<S: ResultSink> ResultSink for SummarySink<S> {
+	fn with_state<R>(&self, f: impl FnOnce(&mut AppState) -> R) -> R {
+		self.sink.with_state(f)
+	}
+
+	fn emit_event<T: Serialize + Clone>(&self, event: &str, payload: T) {
+		self.sink.emit_event(event, payload);
+	}
+
+	fn emit_result<T: Serialize + Clone>(&self, result: T) {
+		if let Ok(value) = serde_json::to_value(&result) {
+			let tool = value["cmd"].as_str().and_then(Tool::from_cmd);
+			if let Some(tool) = tool {
+				self.summaries.lock().unwrap().push(ToolSummary {
+					tool,
+					items: value["list"].as_array().map_or(0, Vec::len),
+					message: value["message"]
+						.as_str()
+						.and_then(|message| message.lines().next())
+						.unwrap_or_default()
+						.to_string(),
+				});
+			}
+		}
+	}
+
+	fn stop_flag_and_progress_tx(
+		&self,
+	) -> (Arc<AtomicBool>, Sender<ProgressData>) {
+		self.sink.stop_flag_and_progress_tx()
+	}
+
+	fn set_scaner_state<T: ScanerState>(&self, _scaner: T) {}
+
+	fn data_dir(&self) -> Option<PathBuf> {
+		self.sink.data_dir()
+	}
+
+	fn results_dir(&self) -> Option<PathBuf> {
+		self.sink.config_dir().map(|dir| dir.join(RESULTS_DIR))
+	}
+
+	fn config_dir(&self) -> Option<PathBuf> {
+		self.sink.config_dir()
+	}
+}
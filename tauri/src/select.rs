@@ -0,0 +1,212 @@
+use std::{cmp::Ordering, path::PathBuf};
+
+use czkawka_core::tools::{
+	duplicate::DuplicateEntry, same_music::MusicEntry,
+	similar_images::ImagesEntry,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{scaner::Tool, sink::ResultSink, state::AppState};
+
+// Which file of a group to keep, every other file of the group is selected
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "rule")]
+pub enum SelectRule {
+	KeepNewest,
+	KeepOldest,
+	KeepShortestPath,
+	KeepLongestPath,
+	KeepLargest,
+	KeepSmallest,
+	// Keeps the file in the first directory of the list that has one
+	KeepInDirectories { directories: Vec<PathBuf> },
+	// Similar Images only
+	KeepHighestResolution,
+	// Music Duplicates only
+	KeepHighestBitrate,
+}
+
+// A file of a stored group with what the rules look at
+pub(crate) struct Candidate {
+	pub path: PathBuf,
+	pub modified_date: u64,
+	pub size: u64,
+	pub resolution: Option<u64>,
+	pub bitrate: Option<u32>,
+}
+
+// The reference file, when the scan used reference folders, and the files
+// that can be selected
+pub(crate) type CandidateGroup = (Option<Candidate>, Vec<Candidate>);
+
+pub(crate) fn stored_groups<S: ResultSink>(
+	sink: &S,
+	tool: Tool,
+) -> Result<Vec<CandidateGroup>, String> {
+	sink.with_state(|state| groups_of(state, tool))
+		.ok_or_else(|| format!("No {:?} result to select in", tool))
+}
+
+fn groups_of(state: &AppState, tool: Tool) -> Option<Vec<CandidateGroup>> {
+	match tool {
+		Tool::DuplicateFiles => {
+			let stored = state.duplicate_groups.as_ref()?;
+			Some(convert(
+				stored
+					.groups
+					.iter()
+					.map(|(r, e)| (r.as_ref(), e.as_slice())),
+				|entry: &DuplicateEntry| Candidate {
+					path: entry.path.clone(),
+					modified_date: entry.modified_date,
+					size: entry.size,
+					resolution: None,
+					bitrate: None,
+				},
+			))
+		}
+		Tool::SimilarImages => {
+			let scaner = state.similar_images_state.as_ref()?;
+			let candidate = |entry: &ImagesEntry| Candidate {
+				path: entry.path.clone(),
+				modified_date: entry.modified_date,
+				size: entry.size,
+				resolution: Some(entry.width as u64 * entry.height as u64),
+				bitrate: None,
+			};
+			Some(if scaner.get_use_reference() {
+				let groups = scaner.get_similar_images_referenced().iter();
+				convert(groups.map(|(r, e)| (Some(r), e.as_slice())), candidate)
+			} else {
+				let groups = scaner.get_similar_images().iter();
+				convert(groups.map(|e| (None, e.as_slice())), candidate)
+			})
+		}
+		Tool::MusicDuplicates => {
+			let scaner = state.same_music_state.as_ref()?;
+			let candidate = |entry: &MusicEntry| Candidate {
+				path: entry.path.clone(),
+				modified_date: entry.modified_date,
+				size: entry.size,
+				resolution: None,
+				bitrate: Some(entry.bitrate),
+			};
+			Some(if scaner.get_use_reference() {
+				let groups = scaner.get_similar_music_referenced().iter();
+				convert(groups.map(|(r, e)| (Some(r), e.as_slice())), candidate)
+			} else {
+				let groups = scaner.get_duplicated_music_entries().iter();
+				convert(groups.map(|e| (None, e.as_slice())), candidate)
+			})
+		}
+		_ => None,
+	}
+}
+
+fn convert<'a, T: 'a>(
+	groups: impl Iterator<Item = (Option<&'a T>, &'a [T])>,
+	candidate: impl Fn(&T) -> Candidate,
+) -> Vec<CandidateGroup> {
+	groups
+		.map(|(reference, entries)| {
+			(
+				reference.map(&candidate),
+				entries.iter().map(&candidate).collect(),
+			)
+		})
+		.collect()
+}
+
+impl SelectRule {
+	pub(crate) fn check_tool(&self, tool: Tool) -> Result<(), String> {
+		let supported = match self {
+			SelectRule::KeepHighestResolution => tool == Tool::SimilarImages,
+			SelectRule::KeepHighestBitrate => tool == Tool::MusicDuplicates,
+			_ => true,
+		};
+		if !supported {
+			return Err(format!("{:?} does not apply to {:?}", self, tool));
+		}
+		Ok(())
+	}
+
+	// `Less` when `a` is the better file to keep
+	pub(crate) fn compare(&self, a: &Candidate, b: &Candidate) -> Ordering {
+		match self {
+			SelectRule::KeepNewest => b.modified_date.cmp(&a.modified_date),
+			SelectRule::KeepOldest => a.modified_date.cmp(&b.modified_date),
+			SelectRule::KeepShortestPath => path_len(a).cmp(&path_len(b)),
+			SelectRule::KeepLongestPath => path_len(b).cmp(&path_len(a)),
+			SelectRule::KeepLargest => b.size.cmp(&a.size),
+			SelectRule::KeepSmallest => a.size.cmp(&b.size),
+			SelectRule::KeepInDirectories { directories } => {
+				let rank = |candidate: &Candidate| {
+					directories
+						.iter()
+						.position(|dir| candidate.path.starts_with(dir))
+						.unwrap_or(usize::MAX)
+				};
+				rank(a).cmp(&rank(b))
+			}
+			SelectRule::KeepHighestResolution => {
+				b.resolution.cmp(&a.resolution)
+			}
+			SelectRule::KeepHighestBitrate => b.bitrate.cmp(&a.bitrate),
+		}
+	}
+}
+
+fn path_len(candidate: &Candidate) -> usize {
+	candidate.path.as_os_str().len()
+}
+
+// The paths to act on: every file of a group but the one to keep. A reference
+// file is always the one kept.
+pub(crate) fn select(
+	groups: &[CandidateGroup],
+	compare: impl Fn(&Candidate, &Candidate) -> Ordering,
+) -> Vec<String> {
+	let mut selected = vec![];
+	for (reference, entries) in groups {
+		let kept = if reference.is_some() {
+			None
+		} else {
+			entries.iter().min_by(|a, b| compare(a, b)).map(|c| &c.path)
+		};
+		selected.extend(
+			entries
+				.iter()
+				.filter(|entry| Some(&entry.path) != kept)
+				.map(|entry| entry.path.to_string_lossy().to_string()),
+		);
+	}
+	selected
+}
+
+pub fn select_in_groups<S: ResultSink>(
+	sink: &S,
+	tool: Tool,
+	rule: SelectRule,
+) -> Result<Vec<String>, String> {
+	select_with_rules(sink, tool, &[rule])
+}
+
+// Like `select_in_groups`, with the later rules deciding between the files
+// the earlier ones find equally good
+pub fn select_with_rules<S: ResultSink>(
+	sink: &S,
+	tool: Tool,
+	rules: &[SelectRule],
+) -> Result<Vec<String>, String> {
+	for rule in rules {
+		rule.check_tool(tool)?;
+	}
+	let groups = stored_groups(sink, tool)?;
+	Ok(select(&groups, |a, b| {
+		rules
+			.iter()
+			.map(|rule| rule.compare(a, b))
+			.find(|ordering| ordering.is_ne())
+			.unwrap_or(Ordering::Equal)
+	}))
+}
@@ -5,8 +5,8 @@ use czkawka_core::common::items::{
 };
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
 pub struct Settings {
 	pub included_directories: Vec<String>,
 	pub included_directories_referenced: Vec<String>,
@@ -67,6 +67,69 @@ pub struct Settings {
 	pub reverse_path_display: bool,
 }
 
+// Mirrors `getDefaultSettings` in the frontend, merged with the platform
+// defaults the way the preset view does it
+impl Default for Settings {
+	fn default() -> Self {
+		Settings {
+			included_directories: default_included_directories(),
+			included_directories_referenced: vec![],
+			excluded_directories: default_excluded_directories(),
+			excluded_items: default_excluded_items(),
+			allowed_extensions: String::new(),
+			excluded_extensions: String::new(),
+			minimum_file_size: 16,
+			maximum_file_size: i32::MAX / 1000,
+			recursive_search: true,
+			use_cache: true,
+			save_also_as_json: false,
+			duplicate_hide_hard_links: true,
+			duplicate_use_prehash: true,
+			duplicate_minimal_hash_cache_size: 256,
+			duplicate_minimal_prehash_cache_size: 256,
+			duplicate_delete_outdated_entries: true,
+			similar_images_hide_hard_links: true,
+			similar_images_delete_outdated_entries: true,
+			similar_videos_delete_outdated_entries: true,
+			similar_music_delete_outdated_entries: true,
+			similar_images_sub_hash_size: "16".to_string(),
+			similar_images_sub_hash_alg: "Mean".to_string(),
+			similar_images_sub_resize_algorithm: "Lanczos3".to_string(),
+			similar_images_sub_ignore_same_size: false,
+			similar_images_sub_similarity: 10,
+			similar_images_show_image_preview: true,
+			similar_images_enable_thumbnails: true,
+			duplicates_sub_check_method: "Hash".to_string(),
+			duplicates_sub_available_hash_type: "Blake3".to_string(),
+			duplicates_sub_name_case_sensitive: false,
+			biggest_files_sub_method: "BiggestFiles".to_string(),
+			biggest_files_sub_number_of_files: 50,
+			similar_videos_hide_hard_links: true,
+			similar_videos_sub_ignore_same_size: false,
+			similar_videos_sub_similarity: 15,
+			similar_videos_skip_forward_amount: 15,
+			similar_videos_vid_hash_duration: 10,
+			similar_videos_crop_detect: "letterbox".to_string(),
+			similar_music_sub_audio_check_type: "Tags".to_string(),
+			similar_music_sub_approximate_comparison: false,
+			similar_music_compare_fingerprints_only_with_similar_titles: false,
+			similar_music_sub_title: true,
+			similar_music_sub_artist: true,
+			similar_music_sub_year: false,
+			similar_music_sub_bitrate: false,
+			similar_music_sub_genre: false,
+			similar_music_sub_length: false,
+			similar_music_sub_maximum_difference_value: 3.0,
+			similar_music_sub_minimal_fragment_duration_value: 5.0,
+			broken_files_sub_audio: true,
+			broken_files_sub_pdf: false,
+			broken_files_sub_archive: false,
+			broken_files_sub_image: false,
+			reverse_path_display: false,
+		}
+	}
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PlatformSettings {
@@ -9,13 +9,11 @@ use czkawka_core::{
 use image_hasher::{FilterType, HashAlg};
 use rayon::prelude::*;
 use serde::Serialize;
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Manager};
 
 use crate::{
-	image,
-	scaner::{set_scaner_common_settings, spawn_scaner_thread},
-	settings::Settings,
-	state::get_stop_flag_and_progress_tx,
+	image, scaner::set_scaner_common_settings, settings::Settings,
+	sink::ResultSink,
 };
 
 mod similar_folders {
@@ -82,116 +80,108 @@ struct ScanResult {
 	folders: Vec<similar_folders::FolderStat>,
 }
 
-pub fn scan_similar_images(app: AppHandle, settins: Settings) {
-	spawn_scaner_thread(move || {
-		let (stop_flag, progress_tx) = get_stop_flag_and_progress_tx(&app);
-
-		// 如果启用了缩略图，初始化缩略图管理器
-		if settins.similar_images_enable_thumbnails {
-			if let Ok(cache_dir) = app.path().app_cache_dir() {
-				let thumbnail_dir = cache_dir.join("thumbnails");
-				let thumbnail_size = 256; // 固定尺寸，未来可配置
-				if let Err(e) =
-					image::init_thumbnail_manager(thumbnail_dir, thumbnail_size)
-				{
-					eprintln!("Failed to initialize thumbnail manager: {}", e);
-				}
-			}
+// 如果启用了缩略图，初始化缩略图管理器
+pub fn init_thumbnail_manager(app: &AppHandle) {
+	if let Ok(cache_dir) = app.path().app_cache_dir() {
+		let thumbnail_dir = cache_dir.join("thumbnails");
+		let thumbnail_size = 256; // 固定尺寸，未来可配置
+		if let Err(e) =
+			image::init_thumbnail_manager(thumbnail_dir, thumbnail_size)
+		{
+			eprintln!("Failed to initialize thumbnail manager: {}", e);
 		}
+	}
+}
 
-		let hash_alg = match settins.similar_images_sub_hash_alg.as_ref() {
-			"Gradient" => HashAlg::Gradient,
-			"BlockHash" => HashAlg::Blockhash,
-			"VertGradient" => HashAlg::VertGradient,
-			"DoubleGradient" => HashAlg::DoubleGradient,
-			"Median" => HashAlg::Median,
-			_ => HashAlg::Mean,
-		};
-		let resize_algorithm =
-			match settins.similar_images_sub_resize_algorithm.as_ref() {
-				"Gaussian" => FilterType::Gaussian,
-				"CatmullRom" => FilterType::CatmullRom,
-				"Triangle" => FilterType::Triangle,
-				"Nearest" => FilterType::Nearest,
-				_ => FilterType::Lanczos3,
-			};
-		let hash_size = settins
-			.similar_images_sub_hash_size
-			.parse::<u8>()
-			.unwrap_or(16);
-		let mut scaner = SimilarImages::new(SimilarImagesParameters::new(
-			settins.similar_images_sub_similarity as u32,
-			hash_size,
-			hash_alg,
-			resize_algorithm,
-			settins.similar_images_sub_ignore_same_size,
-			settins.similar_images_hide_hard_links,
-		));
-
-		scaner.set_delete_outdated_cache(
-			settins.similar_images_delete_outdated_entries,
-		);
-		set_scaner_common_settings(&mut scaner, settins);
-
-		// v10 API: use Search::search(stop_flag, progress_sender)
-		scaner.search(&stop_flag, Some(&progress_tx));
-
-		let mut message = scaner.get_text_messages().create_messages_text();
-		let mut raw_list: Vec<_> = if scaner.get_use_reference() {
-			scaner
-				.get_similar_images_referenced()
-				.iter()
-				.cloned()
-				.map(|(original, others)| (Some(original), others))
-				.collect()
-		} else {
-			scaner
-				.get_similar_images()
-				.iter()
-				.cloned()
-				.map(|items| (None, items))
-				.collect()
+pub fn scan_similar_images<S: ResultSink>(sink: &S, settins: Settings) {
+	let (stop_flag, progress_tx) = sink.stop_flag_and_progress_tx();
+
+	let hash_alg = match settins.similar_images_sub_hash_alg.as_ref() {
+		"Gradient" => HashAlg::Gradient,
+		"BlockHash" => HashAlg::Blockhash,
+		"VertGradient" => HashAlg::VertGradient,
+		"DoubleGradient" => HashAlg::DoubleGradient,
+		"Median" => HashAlg::Median,
+		_ => HashAlg::Mean,
+	};
+	let resize_algorithm =
+		match settins.similar_images_sub_resize_algorithm.as_ref() {
+			"Gaussian" => FilterType::Gaussian,
+			"CatmullRom" => FilterType::CatmullRom,
+			"Triangle" => FilterType::Triangle,
+			"Nearest" => FilterType::Nearest,
+			_ => FilterType::Lanczos3,
 		};
+	let hash_size = settins
+		.similar_images_sub_hash_size
+		.parse::<u8>()
+		.unwrap_or(16);
+	let mut scaner = SimilarImages::new(SimilarImagesParameters::new(
+		settins.similar_images_sub_similarity as u32,
+		hash_size,
+		hash_alg,
+		resize_algorithm,
+		settins.similar_images_sub_ignore_same_size,
+		settins.similar_images_hide_hard_links,
+	));
+
+	scaner.set_delete_outdated_cache(
+		settins.similar_images_delete_outdated_entries,
+	);
+	set_scaner_common_settings(&mut scaner, settins);
+
+	// v10 API: use Search::search(stop_flag, progress_sender)
+	scaner.search(&stop_flag, Some(&progress_tx));
+
+	let mut message = scaner.get_text_messages().create_messages_text();
+	let mut raw_list: Vec<_> = if scaner.get_use_reference() {
+		scaner
+			.get_similar_images_referenced()
+			.iter()
+			.cloned()
+			.map(|(original, others)| (Some(original), others))
+			.collect()
+	} else {
+		scaner
+			.get_similar_images()
+			.iter()
+			.cloned()
+			.map(|items| (None, items))
+			.collect()
+	};
 
-		for (_, vec_fe) in &mut raw_list {
-			vec_fe.par_sort_unstable_by_key(|e| e.similarity);
-		}
-
-		message = format!(
-			"Found {} similar image files\n{}",
-			raw_list.len(),
-			message
-		);
+	for (_, vec_fe) in &mut raw_list {
+		vec_fe.par_sort_unstable_by_key(|e| e.similarity);
+	}
 
-		let list = raw_list
-			.into_iter()
-			.map(|(ref_item, item)| {
-				(
-					ref_item.map(|v| images_entry_to_custom(v, hash_size)),
-					item.into_iter()
-						.map(|v| images_entry_to_custom(v, hash_size))
-						.collect(),
-				)
-			})
-			.collect::<Vec<_>>();
-
-		let folders =
-			similar_folders::collect_folders(&list, &is_in_reference_path, 2);
-		// dbg!("similar_images list", &list);
-		// dbg!("similar_images folders", &folders);
-		app.emit(
-			"scan-result",
-			ScanResult {
-				cmd: "scan_similar_images",
-				list,
-				message,
-				folders,
-			},
-		)
-		.unwrap();
-
-		set_scaner_state(app, scaner);
+	message =
+		format!("Found {} similar image files\n{}", raw_list.len(), message);
+
+	let list = raw_list
+		.into_iter()
+		.map(|(ref_item, item)| {
+			(
+				ref_item.map(|v| images_entry_to_custom(v, hash_size)),
+				item.into_iter()
+					.map(|v| images_entry_to_custom(v, hash_size))
+					.collect(),
+			)
+		})
+		.collect::<Vec<_>>();
+
+	let folders =
+		similar_folders::collect_folders(&list, &is_in_reference_path, 2);
+	// dbg!("similar_images list", &list);
+	// dbg!("similar_images folders", &folders);
+
+	sink.emit_result(ScanResult {
+		cmd: "scan_similar_images",
+		list,
+		message,
+		folders,
 	});
+
+	sink.set_scaner_state(scaner);
 }
 
 fn images_entry_to_custom(
@@ -213,7 +203,7 @@ fn is_in_reference_path(_folder: &str) -> bool {
 	false
 }
 
-crate::gen_set_scaner_state_fn!(
+crate::impl_scaner_state!(
 	similar_images_state,
 	czkawka_core::tools::similar_images::SimilarImages
 );
@@ -8,13 +8,10 @@ use czkawka_core::{
 };
 use rayon::prelude::*;
 use serde::Serialize;
-use tauri::{AppHandle, Emitter};
 use vid_dup_finder_lib::Cropdetect;
 
 use crate::{
-	scaner::{set_scaner_common_settings, spawn_scaner_thread},
-	settings::Settings,
-	state::get_stop_flag_and_progress_tx,
+	scaner::set_scaner_common_settings, settings::Settings, sink::ResultSink,
 };
 
 #[derive(Serialize, Clone)]
@@ -31,99 +28,90 @@ struct ScanResult {
 	message: String,
 }
 
-pub fn scan_similar_videos(app: AppHandle, settins: Settings) {
-	spawn_scaner_thread(move || {
-		let (stop_flag, progress_tx) = get_stop_flag_and_progress_tx(&app);
-
-		let crop_detect =
-			match settins.similar_videos_crop_detect.to_lowercase().as_str() {
-				"letterbox" => Cropdetect::Letterbox,
-				"motion" => Cropdetect::Motion,
-				"none" => Cropdetect::None,
-				_ => DEFAULT_CROP_DETECT,
-			};
-
-		let skip_forward_amount_u32 =
-			if settins.similar_videos_skip_forward_amount < 0 {
-				0u32
-			} else {
-				settins.similar_videos_skip_forward_amount as u32
-			};
-		let vid_hash_duration_u32 =
-			if settins.similar_videos_vid_hash_duration < 0 {
-				0u32
-			} else {
-				settins.similar_videos_vid_hash_duration as u32
-			};
-
-		let mut scaner = SimilarVideos::new(SimilarVideosParameters::new(
-			settins.similar_videos_sub_similarity,
-			settins.similar_videos_sub_ignore_same_size,
-			settins.similar_videos_hide_hard_links,
-			skip_forward_amount_u32,
-			vid_hash_duration_u32,
-			crop_detect,
-		));
-
-		scaner.set_delete_outdated_cache(
-			settins.similar_videos_delete_outdated_entries,
-		);
-		set_scaner_common_settings(&mut scaner, settins);
-
-		// v10 API: use Search::search(stop_flag, progress_sender)
-		scaner.search(&stop_flag, Some(&progress_tx));
-
-		let mut message = scaner.get_text_messages().create_messages_text();
-		let mut raw_list: Vec<_> = if scaner.get_use_reference() {
-			scaner
-				.get_similar_videos_referenced()
-				.iter()
-				.cloned()
-				.map(|(original, others)| (Some(original), others))
-				.collect()
+pub fn scan_similar_videos<S: ResultSink>(sink: &S, settins: Settings) {
+	let (stop_flag, progress_tx) = sink.stop_flag_and_progress_tx();
+
+	let crop_detect =
+		match settins.similar_videos_crop_detect.to_lowercase().as_str() {
+			"letterbox" => Cropdetect::Letterbox,
+			"motion" => Cropdetect::Motion,
+			"none" => Cropdetect::None,
+			_ => DEFAULT_CROP_DETECT,
+		};
+
+	let skip_forward_amount_u32 =
+		if settins.similar_videos_skip_forward_amount < 0 {
+			0u32
 		} else {
-			scaner
-				.get_similar_videos()
-				.iter()
-				.cloned()
-				.map(|items| (None, items))
-				.collect()
+			settins.similar_videos_skip_forward_amount as u32
 		};
+	let vid_hash_duration_u32 = if settins.similar_videos_vid_hash_duration < 0
+	{
+		0u32
+	} else {
+		settins.similar_videos_vid_hash_duration as u32
+	};
+
+	let mut scaner = SimilarVideos::new(SimilarVideosParameters::new(
+		settins.similar_videos_sub_similarity,
+		settins.similar_videos_sub_ignore_same_size,
+		settins.similar_videos_hide_hard_links,
+		skip_forward_amount_u32,
+		vid_hash_duration_u32,
+		crop_detect,
+	));
+
+	scaner.set_delete_outdated_cache(
+		settins.similar_videos_delete_outdated_entries,
+	);
+	set_scaner_common_settings(&mut scaner, settins);
+
+	// v10 API: use Search::search(stop_flag, progress_sender)
+	scaner.search(&stop_flag, Some(&progress_tx));
+
+	let mut message = scaner.get_text_messages().create_messages_text();
+	let mut raw_list: Vec<_> = if scaner.get_use_reference() {
+		scaner
+			.get_similar_videos_referenced()
+			.iter()
+			.cloned()
+			.map(|(original, others)| (Some(original), others))
+			.collect()
+	} else {
+		scaner
+			.get_similar_videos()
+			.iter()
+			.cloned()
+			.map(|items| (None, items))
+			.collect()
+	};
+
+	for (_, vec_fe) in &mut raw_list {
+		vec_fe.par_sort_unstable_by(|a, b| {
+			split_path_compare(a.path.as_path(), b.path.as_path())
+		});
+	}
 
-		for (_, vec_fe) in &mut raw_list {
-			vec_fe.par_sort_unstable_by(|a, b| {
-				split_path_compare(a.path.as_path(), b.path.as_path())
-			});
-		}
-
-		message = format!(
-			"Found {} similar video files\n{}",
-			raw_list.len(),
-			message
-		);
-
-		let list = raw_list
-			.into_iter()
-			.map(|(ref_item, item)| {
-				(
-					ref_item.map(videos_entry_to_custom),
-					item.into_iter().map(videos_entry_to_custom).collect(),
-				)
-			})
-			.collect::<Vec<_>>();
-
-		app.emit(
-			"scan-result",
-			ScanResult {
-				cmd: "scan_similar_videos",
-				list,
-				message,
-			},
-		)
-		.unwrap();
-
-		set_scaner_state(app, scaner);
+	message =
+		format!("Found {} similar video files\n{}", raw_list.len(), message);
+
+	let list = raw_list
+		.into_iter()
+		.map(|(ref_item, item)| {
+			(
+				ref_item.map(videos_entry_to_custom),
+				item.into_iter().map(videos_entry_to_custom).collect(),
+			)
+		})
+		.collect::<Vec<_>>();
+
+	sink.emit_result(ScanResult {
+		cmd: "scan_similar_videos",
+		list,
+		message,
 	});
+
+	sink.set_scaner_state(scaner);
 }
 
 fn videos_entry_to_custom(value: VideosEntry) -> CustomVideosEntry {
@@ -134,7 +122,7 @@ fn videos_entry_to_custom(value: VideosEntry) -> CustomVideosEntry {
 	}
 }
 
-crate::gen_set_scaner_state_fn!(
+crate::impl_scaner_state!(
 	similar_videos_state,
 	czkawka_core::tools::similar_videos::SimilarVideos
 );
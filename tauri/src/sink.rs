@@ -0,0 +1,162 @@
+use std::{
+	path::PathBuf,
+	sync::{Arc, Mutex, atomic::AtomicBool},
+};
+
+use crossbeam_channel::{Receiver, Sender};
+use czkawka_core::common::progress_data::ProgressData;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+	job::{JobProgress, forward_progress},
+	state::{AppState, ScanerState},
+};
+
+// Where a scan sends its results, progress and scaner state, so the scan
+// logic does not depend on a running Tauri app
+pub trait ResultSink: Clone + Send + Sync + 'static {
+	fn with_state<R>(&self, f: impl FnOnce(&mut AppState) -> R) -> R;
+
+	fn emit_event<T: Serialize + Clone>(&self, event: &str, payload: T);
+
+	fn emit_result<T: Serialize + Clone>(&self, result: T) {
+		self.emit_event("scan-result", result);
+	}
+
+	// Scans that are not started as a job cannot be stopped and report
+	// untagged progress
+	fn stop_flag_and_progress_tx(
+		&self,
+	) -> (Arc<AtomicBool>, Sender<ProgressData>) {
+		let progress_tx = self.with_state(|state| state.progress_tx.clone());
+		(
+			Arc::new(AtomicBool::new(false)),
+			forward_progress(None, progress_tx, None),
+		)
+	}
+
+	fn set_scaner_state<T: ScanerState>(&self, scaner: T) {
+		self.with_state(|state| scaner.store(state));
+	}
+
+	// Where the last scan results are kept, nothing is saved without one
+	fn data_dir(&self) -> Option<PathBuf> {
+		None
+	}
+
+	// Where the results and history of the scans run through this sink are
+	// saved, the data dir unless they must not replace the last scans there
+	fn results_dir(&self) -> Option<PathBuf> {
+		self.data_dir()
+	}
+
+	// Where the configuration outside of presets, e.g. schedules, is kept
+	fn config_dir(&self) -> Option<PathBuf> {
+		None
+	}
+}
+
+impl ResultSink for AppHandle {
+	fn with_state<R>(&self, f: impl FnOnce(&mut AppState) -> R) -> R {
+		let state_mutex = self.state::<Mutex<AppState>>();
+		let mut state = state_mutex.lock().unwrap();
+		f(&mut state)
+	}
+
+	fn emit_event<T: Serialize + Clone>(&self, event: &str, payload: T) {
+		self.emit(event, payload).unwrap();
+	}
+
+	fn data_dir(&self) -> Option<PathBuf> {
+		self.path().app_data_dir().ok()
+	}
+
+	fn config_dir(&self) -> Option<PathBuf> {
+		self.path().app_config_dir().ok()
+	}
+}
+
+// Keeps everything in memory, used by the CLI and tests
+#[derive(Clone, Default)]
+pub struct MemorySink {
+	state: Arc<Mutex<AppState>>,
+	events: Arc<Mutex<Vec<(String, serde_json::Value)>>>,
+	data_dir: Option<PathBuf>,
+	config_dir: Option<PathBuf>,
+}
+
+impl MemorySink {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn with_data_dir(data_dir: PathBuf) -> Self {
+		Self {
+			data_dir: Some(data_dir),
+			..Self::default()
+		}
+	}
+
+	pub fn with_config_dir(config_dir: PathBuf) -> Self {
+		Self {
+			config_dir: Some(config_dir),
+			..Self::default()
+		}
+	}
+
+	pub fn with_dirs(data_dir: PathBuf, config_dir: PathBuf) -> Self {
+		Self {
+			data_dir: Some(data_dir),
+			config_dir: Some(config_dir),
+			..Self::default()
+		}
+	}
+
+	pub fn events(&self) -> Vec<(String, serde_json::Value)> {
+		self.events.lock().unwrap().clone()
+	}
+
+	// Removes the events, for processes that run for a long time
+	pub fn take_events(&self) -> Vec<(String, serde_json::Value)> {
+		std::mem::take(&mut self.events.lock().unwrap())
+	}
+
+	pub fn results(&self) -> Vec<serde_json::Value> {
+		self.events
+			.lock()
+			.unwrap()
+			.iter()
+			.filter(|(event, _)| event == "scan-result")
+			.map(|(_, payload)| payload.clone())
+			.collect()
+	}
+
+	pub fn progress_rx(&self) -> Receiver<JobProgress> {
+		self.with_state(|state| state.progress_rx.clone())
+	}
+}
+
+impl ResultSink for MemorySink {
+	fn with_state<R>(&self, f: impl FnOnce(&mut AppState) -> R) -> R {
+		let mut state = self.state.lock().unwrap();
+		f(&mut state)
+	}
+
+	fn emit_event<T: Serialize + Clone>(&self, event: &str, payload: T) {
+		let payload = serde_json::to_value(payload)
+			.expect("Failed to serialize event payload");
+		self.events
+			.lock()
+			.unwrap()
+			.push((event.to_string(), payload));
+	}
+
+	fn data_dir(&self) -> Option<PathBuf> {
+		self.data_dir.clone()
+	}
+
+	fn config_dir(&self) -> Option<PathBuf> {
+		self.config_dir.clone()
+	}
+}
@@ -1,26 +1,37 @@
-use std::sync::{Arc, Mutex, atomic::AtomicBool};
+use std::{
+	collections::{HashMap, HashSet},
+	path::PathBuf,
+	sync::{Arc, atomic::AtomicBool},
+};
 
 use crossbeam_channel::{Receiver, Sender, unbounded};
-use czkawka_core::{
-	common::progress_data::ProgressData,
-	tools::{
-		bad_extensions::BadExtensions, big_file::BigFile,
-		broken_files::BrokenFiles, duplicate::DuplicateFinder,
-		empty_files::EmptyFiles, empty_folder::EmptyFolder,
-		invalid_symlinks::InvalidSymlinks, same_music::SameMusic,
-		similar_images::SimilarImages, similar_videos::SimilarVideos,
-		temporary::Temporary,
-	},
+use czkawka_core::tools::{
+	bad_extensions::BadExtensions, big_file::BigFile,
+	broken_files::BrokenFiles, duplicate::DuplicateFinder,
+	empty_files::EmptyFiles, empty_folder::EmptyFolder,
+	invalid_symlinks::InvalidSymlinks, same_music::SameMusic,
+	similar_images::SimilarImages, similar_videos::SimilarVideos,
+	temporary::Temporary,
+};
+
+use crate::{
+	duplicate_files::DuplicateGroups,
+	job::{JobProgress, Jobs},
+	queue::Queues,
+	scaner::Tool,
 };
-use tauri::{AppHandle, Manager};
 
 pub struct AppState {
 	pub is_number_of_threads_setup: bool,
 	pub is_progress_thread_setup: bool,
-	pub stop_flag: Arc<AtomicBool>,
-	pub progress_tx: Sender<ProgressData>,
-	pub progress_rx: Receiver<ProgressData>,
+	pub jobs: Jobs,
+	pub queues: Queues,
+	pub progress_tx: Sender<JobProgress>,
+	pub progress_rx: Receiver<JobProgress>,
 	pub duplication_state: Option<DuplicateFinder>,
+	pub duplicate_groups: Option<DuplicateGroups>,
+	// Stop flag of the watch keeping `duplicate_groups` up to date
+	pub duplicate_watch: Option<Arc<AtomicBool>>,
 	pub empty_folders_state: Option<EmptyFolder>,
 	pub empty_files_state: Option<EmptyFiles>,
 	pub temporary_files_state: Option<Temporary>,
@@ -31,6 +42,15 @@ pub struct AppState {
 	pub same_invalid_symlinks: Option<InvalidSymlinks>,
 	pub broken_files_state: Option<BrokenFiles>,
 	pub bad_extensions_state: Option<BadExtensions>,
+	// Included folders of the last scan of each tool, which move_files can
+	// keep the structure below
+	pub included_directories: HashMap<Tool, Vec<PathBuf>>,
+	// Reference folders of the last scan of each tool, which file operations
+	// refuse to touch
+	pub referenced_directories: HashMap<Tool, Vec<PathBuf>>,
+	// Tools whose last results come from a sweep, which has no scaner
+	// save_result could save them with
+	pub swept_tools: HashSet<Tool>,
 }
 
 impl Default for AppState {
@@ -40,10 +60,13 @@ impl Default for AppState {
 		AppState {
 			is_number_of_threads_setup: false,
 			is_progress_thread_setup: false,
-			stop_flag: Arc::new(AtomicBool::new(false)),
+			jobs: Jobs::default(),
+			queues: Queues::default(),
 			progress_tx,
 			progress_rx,
 			duplication_state: None,
+			duplicate_groups: None,
+			duplicate_watch: None,
 			empty_folders_state: None,
 			empty_files_state: None,
 			temporary_files_state: None,
@@ -54,32 +77,26 @@ impl Default for AppState {
 			same_invalid_symlinks: None,
 			broken_files_state: None,
 			bad_extensions_state: None,
+			included_directories: HashMap::new(),
+			referenced_directories: HashMap::new(),
+			swept_tools: HashSet::new(),
 		}
 	}
 }
 
-pub fn get_stop_flag_and_progress_tx(
-	app: &AppHandle,
-) -> (Arc<AtomicBool>, Sender<ProgressData>) {
-	use std::sync::atomic::Ordering;
-
-	let state_mutex = app.state::<Mutex<AppState>>();
-	let state = state_mutex.lock().unwrap();
-	state.stop_flag.store(false, Ordering::Relaxed);
-
-	(state.stop_flag.clone(), state.progress_tx.clone())
+// Implemented for every czkawka scaner, and whatever else a scan keeps in
+// `AppState`, so a sink knows which field holds the last result of that tool
+pub trait ScanerState: Send + 'static {
+	fn store(self, state: &mut AppState);
 }
 
 #[macro_export]
-macro_rules! gen_set_scaner_state_fn {
+macro_rules! impl_scaner_state {
 	($name:ident, $scaner:path) => {
-		fn set_scaner_state(app: tauri::AppHandle, scaner: $scaner) {
-			use tauri::Manager;
-
-			let state_mutex =
-				app.state::<std::sync::Mutex<$crate::state::AppState>>();
-			let mut state = state_mutex.lock().unwrap();
-			state.$name = Some(scaner);
+		impl $crate::state::ScanerState for $scaner {
+			fn store(self, state: &mut $crate::state::AppState) {
+				state.$name = Some(self);
+			}
 		}
 	};
 }
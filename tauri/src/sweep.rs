@@ -0,0 +1,839 @@
+use std::{
+	collections::{BTreeMap, BTreeSet, HashMap},
+	fs::File,
+	hash::{DefaultHasher, Hasher},
+	io::Read,
+	path::{Path, PathBuf},
+	sync::{
+		Arc,
+		atomic::{AtomicBool, AtomicU64, Ordering},
+	},
+};
+
+use crossbeam_channel::Sender;
+use czkawka_core::{
+	common::{
+		model::{FileEntry, HashType},
+		progress_data::{CurrentStage, ProgressData},
+		split_path_compare,
+	},
+	tools::{
+		bad_extensions::BadFileEntry,
+		duplicate::{DuplicateEntry, hash_calculation},
+		temporary::TemporaryFileEntry,
+	},
+};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	duplicate_files::{DuplicateGroup, store_duplicate_groups},
+	persist::PersistSink,
+	progress::{Counters, with_stage_progress},
+	scaner::{Tool, set_scan_directories},
+	settings::Settings,
+	sink::ResultSink,
+	state::{AppState, ScanerState},
+	walk::{WalkFilters, walk},
+};
+
+// Tools that can work on the file list of a shared walk
+pub const SWEEP_TOOLS: [Tool; 5] = [
+	Tool::DuplicateFiles,
+	Tool::BigFiles,
+	Tool::EmptyFiles,
+	Tool::TemporaryFiles,
+	Tool::BadExtensions,
+];
+
+// Same list czkawka uses for the temporary files tool
+const TEMP_EXTENSIONS: &[&str] = &[
+	"#",
+	"thumbs.db",
+	".bak",
+	"~",
+	".tmp",
+	".temp",
+	".ds_store",
+	".crdownload",
+	".part",
+	".cache",
+	".dmp",
+	".download",
+	".partial",
+];
+
+// Extensions that may hold any content
+const BAD_EXTENSIONS_DISABLED: &[&str] =
+	&["file", "cache", "bak", "data", "tmp"];
+
+// czkawka's workarounds for contents `infer` and `mime_guess` recognize
+// differently, as ("real_content_extension", "current_file_extension")
+const BAD_EXTENSIONS_WORKAROUNDS: &[(&str, &str)] = &[
+	// Wine/Windows
+	("der", "cat"),
+	("exe", "acm"),
+	("exe", "ax"),
+	("exe", "bck"),
+	("exe", "com"),
+	("exe", "cpl"),
+	("exe", "dll16"),
+	("exe", "dll"),
+	("exe", "drv16"),
+	("exe", "drv"),
+	("exe", "ds"),
+	("exe", "efi"),
+	("exe", "exe16"),
+	("exe", "fon"), // Type of font or something else
+	("exe", "mod16"),
+	("exe", "msstyles"),
+	("exe", "mui"),
+	("exe", "mun"),
+	("exe", "orig"),
+	("exe", "ps1xml"),
+	("exe", "rll"),
+	("exe", "rs"),
+	("exe", "scr"),
+	("exe", "signed"),
+	("exe", "sys"),
+	("exe", "tlb"),
+	("exe", "tsp"),
+	("exe", "vdm"),
+	("exe", "vxd"),
+	("exe", "winmd"),
+	("gz", "loggz"),
+	("xml", "adml"),
+	("xml", "admx"),
+	("xml", "camp"),
+	("xml", "cdmp"),
+	("xml", "cdxml"),
+	("xml", "dgml"),
+	("xml", "diagpkg"),
+	("xml", "gmmp"),
+	("xml", "library-ms"),
+	("xml", "man"),
+	("xml", "manifest"),
+	("xml", "msc"),
+	("xml", "mum"),
+	("xml", "resx"),
+	("zip", "wmz"),
+	// Games specific extensions - cannot be used here common extensions like zip
+	("gz", "h3m"),     // Heroes 3
+	("zip", "hashdb"), // Gog
+	("c2", "zip"),     // King of the Dark Age
+	("c2", "bmp"),     // King of the Dark Age
+	("c2", "avi"),     // King of the Dark Age
+	("c2", "exe"),     // King of the Dark Age
+	// Raw images
+	("tif", "nef"),
+	("tif", "dng"),
+	("tif", "arw"),
+	// Other
+	("der", "keystore"),  // Godot/Android keystore
+	("exe", "pyd"),       // Python/Mingw
+	("gz", "blend"),      // Blender
+	("gz", "crate"),      // Cargo
+	("gz", "svgz"),       // Archive svg
+	("gz", "tgz"),        // Archive
+	("heic", "heif"),     // Image
+	("heif", "heic"),     // Image
+	("html", "dtd"),      // Mingw
+	("html", "ent"),      // Mingw
+	("html", "md"),       // Markdown
+	("html", "svelte"),   // Svelte
+	("jpg", "jfif"),      // Photo format
+	("m4v", "mp4"),       // m4v and mp4 are interchangeable
+	("mobi", "azw3"),     // Ebook format
+	("mpg", "vob"),       // Weddings in parts have usually vob extension
+	("obj", "bin"),       // Multiple apps, Czkawka, Nvidia, Windows
+	("obj", "o"),         // Compilators
+	("odp", "otp"),       // LibreOffice
+	("ods", "ots"),       // Libreoffice
+	("odt", "ott"),       // Libreoffice
+	("ogg", "ogv"),       // Audio format
+	("pem", "key"),       // curl, openssl
+	("png", "kpp"),       // Krita presets
+	("pptx", "ppsx"),     // Powerpoint
+	("sh", "bash"),       // Linux
+	("sh", "guess"),      // GNU
+	("sh", "lua"),        // Lua
+	("sh", "js"),         // Javascript
+	("sh", "pl"),         // Gnome/Linux
+	("sh", "pm"),         // Gnome/Linux
+	("sh", "py"),         // Python
+	("sh", "pyx"),        // Python
+	("sh", "rs"),         // Rust
+	("sh", "sample"),     // Git
+	("xml", "bsp"),       // Quartus
+	("xml", "cbp"),       // CodeBlocks config
+	("xml", "cfg"),       // Multiple apps - Godot
+	("xml", "cmb"),       // Cambalache
+	("xml", "conf"),      // Multiple apps - Python
+	("xml", "config"),    // Multiple apps - QT Creator
+	("xml", "dae"),       // 3D models
+	("xml", "docbook"),   //
+	("xml", "fb2"),       //
+	("xml", "filters"),   // Visual studio
+	("xml", "gir"),       // GTK
+	("xml", "glade"),     // Glade
+	("xml", "iml"),       // Intelij Idea
+	("xml", "kdenlive"),  // KDenLive
+	("xml", "lang"),      // ?
+	("xml", "nuspec"),    // Nuget
+	("xml", "policy"),    // SystemD
+	("xml", "qsys"),      // Quartus
+	("xml", "sopcinfo"),  // Quartus
+	("xml", "svg"),       // SVG
+	("xml", "ui"),        // Cambalache, Glade
+	("xml", "user"),      // Qtcreator
+	("xml", "vbox"),      // VirtualBox
+	("xml", "vbox-prev"), // VirtualBox
+	("xml", "vcproj"),    // VisualStudio
+	("xml", "vcxproj"),   // VisualStudio
+	("xml", "xba"),       // Libreoffice
+	("xml", "xcd"),       // Libreoffice files
+	("zip", "apk"),       // Android apk
+	("zip", "cbr"),       // Comics
+	("zip", "dat"),       // Multiple - python, brave
+	("zip", "doc"),       // Word
+	("zip", "docx"),      // Word
+	("zip", "epub"),      // Ebook format
+	("zip", "jar"),       // Java
+	("zip", "kra"),       // Krita
+	("zip", "kgm"),       // Krita
+	("zip", "nupkg"),     // Nuget packages
+	("zip", "odg"),       // Libreoffice
+	("zip", "pptx"),      // Powerpoint
+	("zip", "whl"),       // Python packages
+	("zip", "xlsx"),      // Excel
+	("zip", "xpi"),       // Firefox extensions
+	("zip", "zcos"),      // Scilab
+	// Probably invalid
+	("html", "svg"),
+	("xml", "html"),
+	// Probably bug in external library
+	("msi", "ppt"), // Not sure why ppt is not recognized
+	("msi", "doc"), // Not sure why doc is not recognized
+	("exe", "xls"), // Not sure why xls is not recognized
+];
+
+const PREHASH_SIZE: usize = 4 * 1024;
+const HASH_BUFFER_SIZE: usize = 256 * 1024;
+const MAX_STAGE_IDX: u8 = 3;
+
+#[derive(Serialize, Clone)]
+struct ScanResult<T> {
+	cmd: &'static str,
+	list: Vec<T>,
+	message: String,
+}
+
+// Walks the included directories once and runs every requested tool on the
+// collected files. Results are emitted in the same shape as the single tool
+// scans, so the frontend shows them the usual way. There is no czkawka
+// scaner to save them with, so save_result refuses to save them to a file.
+pub fn sweep<S: ResultSink>(sink: &S, tools: &[Tool], settings: Settings) {
+	set_scan_directories(sink, tools, &settings);
+	sink.set_scaner_state(SweptTools(tools.to_vec()));
+	let sink = &PersistSink::new(sink.clone(), settings.clone());
+	let (stop_flag, progress_tx) = sink.stop_flag_and_progress_tx();
+	let filters = WalkFilters::from_settings(&settings);
+
+	let walk = with_progress(
+		&progress_tx,
+		CurrentStage::CollectingFiles,
+		0,
+		(0, 0),
+		|counters| walk(&filters, &stop_flag, &counters.entries),
+	)
+	.unwrap_or_default();
+	let files = walk.files;
+	let mut warnings = walk.warnings;
+	let stopped = || stop_flag.load(Ordering::Relaxed);
+
+	for tool in SWEEP_TOOLS.iter().filter(|tool| tools.contains(tool)) {
+		match tool {
+			Tool::DuplicateFiles => {
+				let list = if stopped() {
+					vec![]
+				} else {
+					find_duplicates(
+						&files,
+						&filters,
+						&settings,
+						&stop_flag,
+						&progress_tx,
+						&mut warnings,
+						&mut HashCache::new(),
+					)
+				};
+				let message = format!(
+					"Found {} similar duplicates files\n{}",
+					list.len(),
+					warnings.join("\n")
+				);
+				store_duplicate_groups(sink, settings.clone(), &list);
+				sink.emit_result(ScanResult {
+					cmd: "scan_duplicate_files",
+					list,
+					message,
+				});
+			}
+			Tool::BigFiles => {
+				let list = find_big_files(&files, &filters, &settings);
+				let message = format!(
+					"Found {} files\n{}",
+					list.len(),
+					warnings.join("\n")
+				);
+				sink.emit_result(ScanResult {
+					cmd: "scan_big_files",
+					list,
+					message,
+				});
+			}
+			Tool::EmptyFiles => {
+				let mut list: Vec<_> = files
+					.iter()
+					.filter(|file| {
+						file.size == 0 && filters.matches_extension(&file.path)
+					})
+					.cloned()
+					.collect();
+				sort_by_path(&mut list, |file| &file.path);
+				let message = format!(
+					"Found {} empty files\n{}",
+					list.len(),
+					warnings.join("\n")
+				);
+				sink.emit_result(ScanResult {
+					cmd: "scan_empty_files",
+					list,
+					message,
+				});
+			}
+			Tool::TemporaryFiles => {
+				let mut list: Vec<_> = files
+					.iter()
+					.filter(|file| is_temporary(&file.path))
+					.map(|file| TemporaryFileEntry {
+						path: file.path.clone(),
+						modified_date: file.modified_date,
+						size: file.size,
+					})
+					.collect();
+				sort_by_path(&mut list, |file| &file.path);
+				let message = format!(
+					"Found {} files\n{}",
+					list.len(),
+					warnings.join("\n")
+				);
+				sink.emit_result(ScanResult {
+					cmd: "scan_temporary_files",
+					list,
+					message,
+				});
+			}
+			Tool::BadExtensions => {
+				let list = if stopped() {
+					vec![]
+				} else {
+					find_bad_extensions(
+						&files,
+						&filters,
+						&stop_flag,
+						&progress_tx,
+					)
+				};
+				let message = format!(
+					"Found {} files with bad extensions\n{}",
+					list.len(),
+					warnings.join("\n")
+				);
+				sink.emit_result(ScanResult {
+					cmd: "scan_bad_extensions",
+					list,
+					message,
+				});
+			}
+			_ => unreachable!("Tool is not supported by sweep."),
+		}
+	}
+}
+
+// Drops the scaners of the single tool scans a sweep replaces the results of,
+// save_result would save those older results otherwise, and marks the tools
+// so save_result can tell why there is nothing to save
+struct SweptTools(Vec<Tool>);
+
+impl ScanerState for SweptTools {
+	fn store(self, state: &mut AppState) {
+		for tool in self.0 {
+			state.swept_tools.insert(tool);
+			match tool {
+				Tool::DuplicateFiles => state.duplication_state = None,
+				Tool::BigFiles => state.big_files_state = None,
+				Tool::EmptyFiles => state.empty_files_state = None,
+				Tool::TemporaryFiles => state.temporary_files_state = None,
+				Tool::BadExtensions => state.bad_extensions_state = None,
+				_ => {}
+			}
+		}
+	}
+}
+
+// Hashes of earlier runs, valid while size and modification date match
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CachedHash {
+	pub size: u64,
+	pub modified_date: u64,
+	pub hash: String,
+}
+
+pub type HashCache = HashMap<PathBuf, CachedHash>;
+
+pub fn find_duplicates(
+	files: &[FileEntry],
+	filters: &WalkFilters,
+	settings: &Settings,
+	stop_flag: &Arc<AtomicBool>,
+	progress_tx: &Sender<ProgressData>,
+	warnings: &mut Vec<String>,
+	hashes: &mut HashCache,
+) -> Vec<DuplicateGroup> {
+	let files: Vec<_> = files
+		.iter()
+		.filter(|file| {
+			filters.matches_size(file.size)
+				&& filters.matches_extension(&file.path)
+		})
+		.map(|file| DuplicateEntry {
+			path: file.path.clone(),
+			modified_date: file.modified_date,
+			size: file.size,
+			hash: String::new(),
+		})
+		.collect();
+
+	let case_sensitive = settings.duplicates_sub_name_case_sensitive;
+	let name_of = |entry: &DuplicateEntry| {
+		let name = entry
+			.path
+			.file_name()
+			.map(|name| name.to_string_lossy().to_string())
+			.unwrap_or_default();
+		if case_sensitive {
+			name
+		} else {
+			name.to_lowercase()
+		}
+	};
+
+	let mut groups: Vec<Vec<DuplicateEntry>> =
+		match settings.duplicates_sub_check_method.as_ref() {
+			"Name" => group_by(files, name_of),
+			"Size" => group_by(files, |entry| entry.size),
+			"SizeName" => group_by(files, |entry| (entry.size, name_of(entry))),
+			_ => {
+				let mut groups = group_by(files, |entry| entry.size);
+				if settings.duplicate_hide_hard_links {
+					groups = groups
+						.into_iter()
+						.map(filter_hard_links)
+						.filter(|group| group.len() > 1)
+						.collect();
+				}
+				if settings.duplicate_use_prehash {
+					// Groups already hashed completely need no reads at all
+					let (cached, uncached): (Vec<_>, Vec<_>) =
+						groups.into_iter().partition(|group| {
+							group.iter().all(|entry| {
+								cached_hash(hashes, entry).is_some()
+							})
+						});
+					groups = prehash_groups(uncached, stop_flag, progress_tx);
+					groups.extend(cached);
+				}
+				hash_groups(
+					groups,
+					settings,
+					stop_flag,
+					progress_tx,
+					warnings,
+					hashes,
+				)
+			}
+		};
+
+	for group in &mut groups {
+		sort_by_path(group, |entry| &entry.path);
+	}
+
+	if filters.has_referenced() {
+		groups
+			.into_iter()
+			.filter_map(|group| {
+				let (mut referenced, others): (Vec<_>, Vec<_>) = group
+					.into_iter()
+					.partition(|entry| filters.is_referenced(&entry.path));
+				if others.is_empty() {
+					return None;
+				}
+				referenced.pop().map(|original| (Some(original), others))
+			})
+			.collect()
+	} else {
+		groups.into_iter().map(|group| (None, group)).collect()
+	}
+}
+
+fn group_by<K: Ord>(
+	files: Vec<DuplicateEntry>,
+	key: impl Fn(&DuplicateEntry) -> K,
+) -> Vec<Vec<DuplicateEntry>> {
+	let mut groups: BTreeMap<K, Vec<DuplicateEntry>> = BTreeMap::new();
+	for file in files {
+		groups.entry(key(&file)).or_default().push(file);
+	}
+	groups
+		.into_values()
+		.filter(|group| group.len() > 1)
+		.collect()
+}
+
+#[cfg(target_family = "unix")]
+fn filter_hard_links(group: Vec<DuplicateEntry>) -> Vec<DuplicateEntry> {
+	use std::{collections::HashSet, os::unix::fs::MetadataExt};
+
+	let mut inodes = HashSet::new();
+	group
+		.into_iter()
+		.filter(|entry| match std::fs::metadata(&entry.path) {
+			Ok(metadata) => inodes.insert((metadata.dev(), metadata.ino())),
+			Err(_) => true,
+		})
+		.collect()
+}
+
+#[cfg(not(target_family = "unix"))]
+fn filter_hard_links(group: Vec<DuplicateEntry>) -> Vec<DuplicateEntry> {
+	group
+}
+
+// Splits same size groups by their first bytes, so files that differ early
+// are never read completely
+fn prehash_groups(
+	groups: Vec<Vec<DuplicateEntry>>,
+	stop_flag: &Arc<AtomicBool>,
+	progress_tx: &Sender<ProgressData>,
+) -> Vec<Vec<DuplicateEntry>> {
+	let entries_to_check = groups.iter().map(Vec::len).sum();
+	let bytes_to_check = groups
+		.iter()
+		.flatten()
+		.map(|entry| entry.size.min(PREHASH_SIZE as u64))
+		.sum();
+
+	with_progress(
+		progress_tx,
+		CurrentStage::DuplicatePreHashing,
+		1,
+		(entries_to_check, bytes_to_check),
+		|counters| {
+			groups
+				.into_par_iter()
+				.flat_map(|group| {
+					if stop_flag.load(Ordering::Relaxed) {
+						return vec![];
+					}
+					let mut by_prehash: HashMap<u64, Vec<DuplicateEntry>> =
+						HashMap::new();
+					for entry in group {
+						let Some(prehash) = prehash(&entry.path) else {
+							continue;
+						};
+						counters.entries.fetch_add(1, Ordering::Relaxed);
+						counters.bytes.fetch_add(
+							entry.size.min(PREHASH_SIZE as u64),
+							Ordering::Relaxed,
+						);
+						by_prehash.entry(prehash).or_default().push(entry);
+					}
+					by_prehash
+						.into_values()
+						.filter(|group| group.len() > 1)
+						.collect()
+				})
+				.collect()
+		},
+	)
+}
+
+fn prehash(path: &Path) -> Option<u64> {
+	let mut buffer = [0; PREHASH_SIZE];
+	let mut file = File::open(path).ok()?;
+	let mut read = 0;
+	while read < PREHASH_SIZE {
+		match file.read(&mut buffer[read..]) {
+			Ok(0) => break,
+			Ok(n) => read += n,
+			Err(_) => return None,
+		}
+	}
+	let mut hasher = DefaultHasher::new();
+	hasher.write(&buffer[..read]);
+	Some(hasher.finish())
+}
+
+fn hash_groups(
+	groups: Vec<Vec<DuplicateEntry>>,
+	settings: &Settings,
+	stop_flag: &Arc<AtomicBool>,
+	progress_tx: &Sender<ProgressData>,
+	warnings: &mut Vec<String>,
+	hashes: &mut HashCache,
+) -> Vec<Vec<DuplicateEntry>> {
+	let hash_type = hash_type(settings);
+	let entries_to_check = groups.iter().map(Vec::len).sum();
+	let bytes_to_check = groups.iter().flatten().map(|entry| entry.size).sum();
+	let known_hashes = &*hashes;
+
+	let segments: Vec<_> = with_progress(
+		progress_tx,
+		CurrentStage::DuplicateFullHashing,
+		2,
+		(entries_to_check, bytes_to_check),
+		|counters| {
+			let size_counter = Arc::new(AtomicU64::new(0));
+			groups
+				.into_par_iter()
+				.map_init(
+					|| vec![0; HASH_BUFFER_SIZE],
+					|buffer, group| {
+						let mut by_hash: HashMap<String, Vec<DuplicateEntry>> =
+							HashMap::new();
+						let mut errors = vec![];
+						let mut computed = vec![];
+						for mut entry in group {
+							let hash = match cached_hash(known_hashes, &entry) {
+								Some(hash) => Ok(Some(hash)),
+								None => hash_calculation(
+									buffer,
+									&entry,
+									hash_type,
+									&size_counter,
+									stop_flag,
+								),
+							};
+							counters.entries.fetch_add(1, Ordering::Relaxed);
+							counters.bytes.store(
+								size_counter.load(Ordering::Relaxed),
+								Ordering::Relaxed,
+							);
+							match hash {
+								Ok(Some(hash)) => {
+									entry.hash = hash.clone();
+									computed.push(entry.clone());
+									by_hash
+										.entry(hash)
+										.or_default()
+										.push(entry);
+								}
+								Ok(None) => return (vec![], errors, vec![]),
+								Err(e) => errors.push(e),
+							}
+						}
+						let groups = by_hash
+							.into_values()
+							.filter(|group| group.len() > 1)
+							.collect::<Vec<_>>();
+						(groups, errors, computed)
+					},
+				)
+				.collect()
+		},
+	);
+
+	let mut result = vec![];
+	for (groups, errors, computed) in segments {
+		result.extend(groups);
+		warnings.extend(errors);
+		for entry in computed {
+			hashes.insert(
+				entry.path,
+				CachedHash {
+					size: entry.size,
+					modified_date: entry.modified_date,
+					hash: entry.hash,
+				},
+			);
+		}
+	}
+	result
+}
+
+fn hash_type(settings: &Settings) -> HashType {
+	match settings.duplicates_sub_available_hash_type.as_ref() {
+		"CRC32" => HashType::Crc32,
+		"XXH3" => HashType::Xxh3,
+		_ => HashType::Blake3,
+	}
+}
+
+fn cached_hash(hashes: &HashCache, entry: &DuplicateEntry) -> Option<String> {
+	hashes
+		.get(&entry.path)
+		.filter(|cached| {
+			cached.size == entry.size
+				&& cached.modified_date == entry.modified_date
+		})
+		.map(|cached| cached.hash.clone())
+}
+
+fn find_big_files(
+	files: &[FileEntry],
+	filters: &WalkFilters,
+	settings: &Settings,
+) -> Vec<FileEntry> {
+	let mut list: Vec<_> = files
+		.iter()
+		.filter(|file| {
+			file.size > 0
+				&& filters.matches_size(file.size)
+				&& filters.matches_extension(&file.path)
+		})
+		.cloned()
+		.collect();
+
+	if settings.biggest_files_sub_method == "SmallestFiles" {
+		list.par_sort_unstable_by_key(|fe| fe.size);
+	} else {
+		list.par_sort_unstable_by_key(|fe| u64::MAX - fe.size);
+	}
+	list.truncate(settings.biggest_files_sub_number_of_files.max(0) as usize);
+	list
+}
+
+fn is_temporary(path: &Path) -> bool {
+	let Some(name) = path.file_name() else {
+		return false;
+	};
+	let name = name.to_string_lossy().to_ascii_lowercase();
+	TEMP_EXTENSIONS.iter().any(|ext| name.ends_with(ext))
+}
+
+fn find_bad_extensions(
+	files: &[FileEntry],
+	filters: &WalkFilters,
+	stop_flag: &Arc<AtomicBool>,
+	progress_tx: &Sender<ProgressData>,
+) -> Vec<BadFileEntry> {
+	let files: Vec<_> = files
+		.iter()
+		.filter(|file| {
+			filters.matches_size(file.size)
+				&& filters.matches_extension(&file.path)
+		})
+		.collect();
+
+	let mut workarounds: HashMap<&str, Vec<&str>> = HashMap::new();
+	for (content, extension) in BAD_EXTENSIONS_WORKAROUNDS {
+		workarounds.entry(extension).or_default().push(content);
+	}
+
+	let mut list: Vec<_> = with_progress(
+		progress_tx,
+		CurrentStage::BadExtensionsChecking,
+		3,
+		(files.len(), 0),
+		|counters| {
+			files
+				.into_par_iter()
+				.filter_map(|file| {
+					if stop_flag.load(Ordering::Relaxed) {
+						return None;
+					}
+					counters.entries.fetch_add(1, Ordering::Relaxed);
+					check_extension(file, &workarounds)
+				})
+				.collect()
+		},
+	);
+
+	sort_by_path(&mut list, |file| &file.path);
+	list
+}
+
+// The same checks as czkawka's bad extensions tool
+fn check_extension(
+	file: &FileEntry,
+	workarounds: &HashMap<&str, Vec<&str>>,
+) -> Option<BadFileEntry> {
+	// Files without extension are never reported, czkawka finds no valid
+	// extensions for them
+	let current_extension =
+		file.path.extension()?.to_string_lossy().to_lowercase();
+	if current_extension.len() > 10
+		|| BAD_EXTENSIONS_DISABLED.contains(&current_extension.as_str())
+	{
+		return None;
+	}
+
+	let kind = infer::get_from_path(&file.path).ok()??;
+	let proper_extension = kind.extension();
+	if current_extension == proper_extension {
+		return None;
+	}
+
+	let mut valid_extensions = BTreeSet::new();
+	for mime in mime_guess::from_ext(proper_extension) {
+		if let Some(extensions) = mime_guess::get_mime_extensions(&mime) {
+			valid_extensions
+				.extend(extensions.iter().map(|ext| ext.to_string()));
+		}
+	}
+	if let Some(contents) = workarounds.get(current_extension.as_str()) {
+		if contents.iter().any(|ext| valid_extensions.contains(*ext)) {
+			valid_extensions.insert(current_extension.clone());
+		}
+	}
+	if valid_extensions.is_empty()
+		|| valid_extensions.contains(&current_extension)
+	{
+		return None;
+	}
+
+	Some(BadFileEntry {
+		path: file.path.clone(),
+		modified_date: file.modified_date,
+		size: file.size,
+		proper_extensions_group: format!(
+			"({}) - {}",
+			proper_extension,
+			valid_extensions.into_iter().collect::<Vec<_>>().join(",")
+		),
+		current_extension,
+		proper_extension: proper_extension.to_string(),
+	})
+}
+
+fn sort_by_path<T: Send>(list: &mut [T], path: impl Fn(&T) -> &PathBuf + Sync) {
+	list.par_sort_unstable_by(|a, b| split_path_compare(path(a), path(b)));
+}
+
+// Reports the counters of a stage of the sweep until `f` returns
+pub(crate) fn with_progress<R>(
+	progress_tx: &Sender<ProgressData>,
+	stage: CurrentStage,
+	stage_idx: u8,
+	totals: (usize, u64),
+	f: impl FnOnce(&Counters) -> R,
+) -> R {
+	with_stage_progress(
+		progress_tx,
+		stage,
+		(stage_idx, MAX_STAGE_IDX),
+		totals,
+		f,
+	)
+}
@@ -1,9 +1,19 @@
-use std::path::PathBuf;
+use std::{
+	path::PathBuf,
+	time::{SystemTime, UNIX_EPOCH},
+};
 
 pub fn convert_strs_to_path_bufs(strs: Vec<String>) -> Vec<PathBuf> {
 	strs.into_iter().map(PathBuf::from).collect()
 }
 
+pub fn unix_now() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs())
+		.unwrap_or_default()
+}
+
 pub fn split_str_with_comma(s: String) -> Vec<String> {
 	s.split(',').map(|s| s.to_string()).collect()
 }
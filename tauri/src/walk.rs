@@ -0,0 +1,460 @@
+use std::{
+	collections::{HashMap, HashSet},
+	fs::{self, Metadata},
+	path::{Path, PathBuf},
+	sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+	time::UNIX_EPOCH,
+};
+
+use czkawka_core::common::{
+	items::{DEFAULT_EXCLUDED_ITEMS, SingleExcludedItem, new_excluded_item},
+	model::FileEntry,
+	regex_check,
+};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{settings::Settings, utils::split_str_with_comma};
+
+// The same filters `set_scaner_common_settings` hands to czkawka, for code
+// that walks the directories itself
+pub struct WalkFilters {
+	included: Vec<PathBuf>,
+	referenced: Vec<PathBuf>,
+	excluded: Vec<PathBuf>,
+	excluded_items: Vec<SingleExcludedItem>,
+	allowed_extensions: HashSet<String>,
+	excluded_extensions: HashSet<String>,
+	recursive: bool,
+	minimum_size: u64,
+	maximum_size: u64,
+}
+
+impl WalkFilters {
+	pub fn from_settings(settings: &Settings) -> Self {
+		let all_referenced = settings.included_directories.len()
+			== settings.included_directories_referenced.len();
+
+		let mut included: Vec<PathBuf> = settings
+			.included_directories
+			.iter()
+			.map(PathBuf::from)
+			.collect();
+		included.sort();
+		included.dedup();
+		// Nested directories would be walked twice
+		if settings.recursive_search {
+			let all = included.clone();
+			included.retain(|dir| {
+				!all.iter()
+					.any(|other| other != dir && dir.starts_with(other))
+			});
+		}
+
+		let excluded_items =
+			split_str_with_comma(settings.excluded_items.clone())
+				.iter()
+				.map(|item| item.trim())
+				.filter(|item| !item.is_empty())
+				.flat_map(|item| {
+					if item == "DEFAULT" {
+						DEFAULT_EXCLUDED_ITEMS.split(',').collect()
+					} else {
+						vec![item]
+					}
+				})
+				.filter(|item| item.contains('*'))
+				.map(new_excluded_item)
+				.collect();
+
+		WalkFilters {
+			included,
+			referenced: if all_referenced {
+				vec![]
+			} else {
+				settings
+					.included_directories_referenced
+					.iter()
+					.map(PathBuf::from)
+					.collect()
+			},
+			excluded: settings
+				.excluded_directories
+				.iter()
+				.map(PathBuf::from)
+				.collect(),
+			excluded_items,
+			allowed_extensions: parse_extensions(&settings.allowed_extensions),
+			excluded_extensions: parse_extensions(
+				&settings.excluded_extensions,
+			),
+			recursive: settings.recursive_search,
+			minimum_size: settings.minimum_file_size as u64 * 1000,
+			maximum_size: settings.maximum_file_size as u64 * 1000,
+		}
+	}
+
+	pub fn included(&self) -> &[PathBuf] {
+		&self.included
+	}
+
+	// Whether a walk would reach `path`, for paths that come from somewhere
+	// else than a walk, e.g. a filesystem watcher
+	pub fn covers(&self, path: &Path) -> bool {
+		self.included.iter().any(|dir| {
+			let Ok(relative) = path.strip_prefix(dir) else {
+				return false;
+			};
+			if !self.recursive && relative.components().count() > 1 {
+				return false;
+			}
+			let mut current = dir.clone();
+			if self.is_excluded(&current) {
+				return false;
+			}
+			relative.components().all(|component| {
+				current.push(component);
+				!self.is_excluded(&current)
+			})
+		})
+	}
+
+	pub fn is_excluded(&self, path: &Path) -> bool {
+		if self.excluded.iter().any(|dir| dir == path) {
+			return true;
+		}
+		let path = path.to_string_lossy();
+		self.excluded_items
+			.iter()
+			.any(|item| regex_check(item, &path))
+	}
+
+	pub fn is_referenced(&self, path: &Path) -> bool {
+		self.referenced.iter().any(|dir| path.starts_with(dir))
+	}
+
+	pub fn has_referenced(&self) -> bool {
+		!self.referenced.is_empty()
+	}
+
+	pub fn matches_extension(&self, path: &Path) -> bool {
+		let extension = path
+			.extension()
+			.map(|ext| ext.to_string_lossy().to_lowercase())
+			.unwrap_or_default();
+		if self.excluded_extensions.contains(&extension) {
+			return false;
+		}
+		self.allowed_extensions.is_empty()
+			|| self.allowed_extensions.contains(&extension)
+	}
+
+	pub fn matches_size(&self, size: u64) -> bool {
+		size >= self.minimum_size && size <= self.maximum_size
+	}
+}
+
+fn parse_extensions(extensions: &str) -> HashSet<String> {
+	extensions
+		.split(',')
+		.map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+		.filter(|ext| !ext.is_empty())
+		.collect()
+}
+
+#[derive(Default)]
+pub struct Walk {
+	pub files: Vec<FileEntry>,
+	pub warnings: Vec<String>,
+}
+
+// Collects every regular file below the included directories. Size and
+// extension filters are left to the caller, because some tools (e.g. empty
+// and temporary files) ignore them. Returns `None` when stopped.
+pub fn walk(
+	filters: &WalkFilters,
+	stop_flag: &AtomicBool,
+	files_counter: &AtomicUsize,
+) -> Option<Walk> {
+	let mut result = Walk::default();
+	let folders = root_folders(filters, &mut result.warnings);
+	walk_folders(filters, folders, stop_flag, files_counter, result)
+}
+
+// Like `walk`, but starts at a single folder below the included directories
+pub fn walk_folder(
+	filters: &WalkFilters,
+	folder: &Path,
+	stop_flag: &AtomicBool,
+	files_counter: &AtomicUsize,
+) -> Option<Walk> {
+	walk_folders(
+		filters,
+		vec![folder.to_path_buf()],
+		stop_flag,
+		files_counter,
+		Walk::default(),
+	)
+}
+
+fn walk_folders(
+	filters: &WalkFilters,
+	mut folders: Vec<PathBuf>,
+	stop_flag: &AtomicBool,
+	files_counter: &AtomicUsize,
+	mut result: Walk,
+) -> Option<Walk> {
+	while !folders.is_empty() {
+		if stop_flag.load(Ordering::Relaxed) {
+			return None;
+		}
+
+		let segments: Vec<_> = folders
+			.into_par_iter()
+			.map(|folder| read_folder(filters, &folder, files_counter))
+			.collect();
+
+		folders = vec![];
+		for (sub_folders, files, warnings) in segments {
+			folders.extend(sub_folders);
+			result.files.extend(files);
+			result.warnings.extend(warnings);
+		}
+	}
+
+	Some(result)
+}
+
+// What a folder contained when it was last read. A folder's mtime changes
+// when entries are added, removed or renamed in it, so as long as it and the
+// inode match, the listing can be reused without reading the folder again.
+// Editing a file in place leaves the folder's mtime alone, so the metadata of
+// the files is always read again.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FolderSnapshot {
+	modified: (u64, u32),
+	inode: u64,
+	folders: Vec<PathBuf>,
+	files: Vec<FileEntry>,
+}
+
+pub type WalkSnapshot = HashMap<PathBuf, FolderSnapshot>;
+
+pub struct IncrementalWalk {
+	pub walk: Walk,
+	pub snapshot: WalkSnapshot,
+	pub read_folders: usize,
+	pub reused_folders: usize,
+}
+
+// Like `walk`, but only reads the folders that changed since `previous` was
+// taken. The snapshot must come from a walk with the same filters.
+pub fn walk_incremental(
+	filters: &WalkFilters,
+	previous: &WalkSnapshot,
+	stop_flag: &AtomicBool,
+	files_counter: &AtomicUsize,
+) -> Option<IncrementalWalk> {
+	let mut result = IncrementalWalk {
+		walk: Walk::default(),
+		snapshot: WalkSnapshot::new(),
+		read_folders: 0,
+		reused_folders: 0,
+	};
+	let mut folders = root_folders(filters, &mut result.walk.warnings);
+
+	while !folders.is_empty() {
+		if stop_flag.load(Ordering::Relaxed) {
+			return None;
+		}
+
+		let segments: Vec<_> = folders
+			.into_par_iter()
+			.filter_map(|folder| {
+				let mut warnings = vec![];
+				let metadata = match fs::metadata(&folder) {
+					Ok(metadata) => metadata,
+					Err(e) => {
+						warnings.push(format!(
+							"Cannot open {}: {}",
+							folder.display(),
+							e
+						));
+						return Some((folder, None, warnings, false));
+					}
+				};
+				let (modified, inode) = folder_stamp(&metadata);
+
+				if let Some(snapshot) =
+					previous.get(&folder).filter(|snapshot| {
+						snapshot.modified == modified && snapshot.inode == inode
+					}) {
+					files_counter
+						.fetch_add(snapshot.files.len(), Ordering::Relaxed);
+					let snapshot = FolderSnapshot {
+						files: refresh_files(&snapshot.files, &mut warnings),
+						..snapshot.clone()
+					};
+					return Some((folder, Some(snapshot), warnings, true));
+				}
+
+				let (folders, files, read_warnings) =
+					read_folder(filters, &folder, files_counter);
+				warnings.extend(read_warnings);
+				let snapshot = FolderSnapshot {
+					modified,
+					inode,
+					folders,
+					files,
+				};
+				Some((folder, Some(snapshot), warnings, false))
+			})
+			.collect();
+
+		folders = vec![];
+		for (folder, snapshot, warnings, reused) in segments {
+			result.walk.warnings.extend(warnings);
+			let Some(snapshot) = snapshot else {
+				continue;
+			};
+			if reused {
+				result.reused_folders += 1;
+			} else {
+				result.read_folders += 1;
+			}
+			folders.extend(snapshot.folders.iter().cloned());
+			result.walk.files.extend(snapshot.files.iter().cloned());
+			result.snapshot.insert(folder, snapshot);
+		}
+	}
+
+	Some(result)
+}
+
+// The files of a reused listing with their current size and mtime
+fn refresh_files(
+	files: &[FileEntry],
+	warnings: &mut Vec<String>,
+) -> Vec<FileEntry> {
+	files
+		.iter()
+		.filter_map(|file| match fs::symlink_metadata(&file.path) {
+			Ok(metadata) if metadata.is_file() => Some(FileEntry {
+				size: metadata.len(),
+				modified_date: modified_date(&metadata),
+				path: file.path.clone(),
+			}),
+			Ok(_) => None,
+			Err(e) => {
+				warnings.push(format!(
+					"Cannot read metadata of {}: {}",
+					file.path.display(),
+					e
+				));
+				None
+			}
+		})
+		.collect()
+}
+
+fn root_folders(
+	filters: &WalkFilters,
+	warnings: &mut Vec<String>,
+) -> Vec<PathBuf> {
+	let mut folders = vec![];
+	for dir in &filters.included {
+		if filters.is_excluded(dir) {
+			continue;
+		}
+		match fs::metadata(dir) {
+			Ok(metadata) if metadata.is_dir() => folders.push(dir.clone()),
+			Ok(_) => {
+				warnings.push(format!("{} is not a directory", dir.display()))
+			}
+			Err(e) => {
+				warnings.push(format!("Cannot open {}: {}", dir.display(), e))
+			}
+		}
+	}
+	folders
+}
+
+#[cfg(target_family = "unix")]
+fn folder_stamp(metadata: &Metadata) -> ((u64, u32), u64) {
+	use std::os::unix::fs::MetadataExt;
+
+	(
+		(metadata.mtime() as u64, metadata.mtime_nsec() as u32),
+		metadata.ino(),
+	)
+}
+
+#[cfg(not(target_family = "unix"))]
+fn folder_stamp(metadata: &Metadata) -> ((u64, u32), u64) {
+	let modified = metadata
+		.modified()
+		.ok()
+		.and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+		.map(|d| (d.as_secs(), d.subsec_nanos()))
+		.unwrap_or_default();
+	(modified, 0)
+}
+
+fn read_folder(
+	filters: &WalkFilters,
+	folder: &Path,
+	files_counter: &AtomicUsize,
+) -> (Vec<PathBuf>, Vec<FileEntry>, Vec<String>) {
+	let mut folders = vec![];
+	let mut files = vec![];
+	let mut warnings = vec![];
+
+	let read_dir = match fs::read_dir(folder) {
+		Ok(read_dir) => read_dir,
+		Err(e) => {
+			warnings.push(format!("Cannot open {}: {}", folder.display(), e));
+			return (folders, files, warnings);
+		}
+	};
+
+	for entry in read_dir.flatten() {
+		let Ok(file_type) = entry.file_type() else {
+			continue;
+		};
+		let path = entry.path();
+
+		if file_type.is_dir() {
+			if filters.recursive && !filters.is_excluded(&path) {
+				folders.push(path);
+			}
+		} else if file_type.is_file() {
+			files_counter.fetch_add(1, Ordering::Relaxed);
+			if filters.is_excluded(&path) {
+				continue;
+			}
+			match entry.metadata() {
+				Ok(metadata) => files.push(FileEntry {
+					size: metadata.len(),
+					modified_date: modified_date(&metadata),
+					path,
+				}),
+				Err(e) => warnings.push(format!(
+					"Cannot read metadata of {}: {}",
+					path.display(),
+					e
+				)),
+			}
+		}
+	}
+
+	(folders, files, warnings)
+}
+
+pub fn modified_date(metadata: &Metadata) -> u64 {
+	metadata
+		.modified()
+		.ok()
+		.and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+		.map(|d| d.as_secs())
+		.unwrap_or_default()
+}
@@ -0,0 +1,415 @@
+use std::{
+	collections::{HashMap, HashSet},
+	fs, mem,
+	path::{Path, PathBuf},
+	sync::{
+		Arc,
+		atomic::{AtomicBool, AtomicUsize, Ordering},
+		mpsc::{self, RecvTimeoutError},
+	},
+	thread,
+	time::{Duration, Instant},
+};
+
+use crossbeam_channel::unbounded;
+use czkawka_core::{
+	common::{model::FileEntry, split_path_compare},
+	tools::duplicate::DuplicateEntry,
+};
+use notify::{
+	Event, EventKind, RecursiveMode, Watcher,
+	event::{AccessKind, AccessMode},
+};
+use serde::Serialize;
+
+use crate::{
+	duplicate_files::DuplicateGroup,
+	settings::Settings,
+	sink::ResultSink,
+	sweep::{CachedHash, HashCache, find_duplicates},
+	walk::{WalkFilters, modified_date, walk, walk_folder},
+};
+
+// Changes are applied once the watcher has been quiet for this long, or at
+// the latest after `MAX_DELAY` while files keep changing
+const DEBOUNCE: Duration = Duration::from_millis(300);
+const MAX_DELAY: Duration = Duration::from_secs(2);
+
+// Groups that changed since the last delta. A group is identified by the
+// paths of its entries, the frontend drops every group in `removed` and shows
+// the ones in `added` instead.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct DuplicateGroupsDelta {
+	removed: Vec<Vec<PathBuf>>,
+	added: Vec<DuplicateGroup>,
+}
+
+// Watches the included directories of the last Duplicate Files scan and keeps
+// its groups up to date. Only the files that share a size (or name, depending
+// on the check method) with a changed file are checked again. Starting a new
+// watch or a new Duplicate Files scan stops the running one.
+pub fn watch_duplicates<S: ResultSink>(sink: &S) -> Result<(), String> {
+	let (settings, groups) = sink
+		.with_state(|state| {
+			state
+				.duplicate_groups
+				.as_ref()
+				.map(|stored| (stored.settings.clone(), stored.groups.clone()))
+		})
+		.ok_or("No Duplicate Files scan to watch")?;
+	let filters = WalkFilters::from_settings(&settings);
+
+	let (events_tx, events_rx) = mpsc::channel();
+	let mut watcher =
+		notify::recommended_watcher(events_tx).map_err(|e| e.to_string())?;
+	let mode = if settings.recursive_search {
+		RecursiveMode::Recursive
+	} else {
+		RecursiveMode::NonRecursive
+	};
+	for dir in filters.included() {
+		watcher
+			.watch(dir, mode)
+			.map_err(|e| format!("Cannot watch {}: {}", dir.display(), e))?;
+	}
+
+	let stop_flag = Arc::new(AtomicBool::new(false));
+	sink.with_state(|state| {
+		if let Some(previous) = state.duplicate_watch.replace(stop_flag.clone())
+		{
+			previous.store(true, Ordering::Relaxed);
+		}
+	});
+
+	let sink = sink.clone();
+	thread::spawn(move || {
+		// Dropping the watcher ends the subscription
+		let _watcher = watcher;
+		let Some(mut watch) =
+			DuplicateWatch::new(settings, filters, groups, stop_flag.clone())
+		else {
+			return;
+		};
+
+		// Files that changed between the scan and the start of the watch
+		let stale = watch.stale_paths();
+		if !stale.is_empty() {
+			watch.apply(&sink, stale);
+		}
+
+		let mut changed = HashSet::new();
+		let mut first_change = None;
+		while !stop_flag.load(Ordering::Relaxed) {
+			let quiet = match events_rx.recv_timeout(DEBOUNCE) {
+				Ok(Ok(event)) => {
+					if is_relevant(&event) {
+						changed.extend(event.paths);
+						first_change.get_or_insert_with(Instant::now);
+					}
+					false
+				}
+				Ok(Err(_)) => false,
+				Err(RecvTimeoutError::Timeout) => true,
+				Err(RecvTimeoutError::Disconnected) => break,
+			};
+			let overdue = first_change
+				.is_some_and(|first: Instant| first.elapsed() >= MAX_DELAY);
+			if !changed.is_empty() && (quiet || overdue) {
+				watch.apply(&sink, mem::take(&mut changed));
+				first_change = None;
+			}
+		}
+	});
+
+	Ok(())
+}
+
+// Returns whether a watch was running
+pub fn unwatch_duplicates<S: ResultSink>(sink: &S) -> bool {
+	sink.with_state(|state| state.duplicate_watch.take())
+		.map(|stop_flag| stop_flag.store(true, Ordering::Relaxed))
+		.is_some()
+}
+
+// Reading a file is reported too, including the reads of the watch itself
+fn is_relevant(event: &Event) -> bool {
+	match event.kind {
+		EventKind::Access(kind) => kind == AccessKind::Close(AccessMode::Write),
+		_ => true,
+	}
+}
+
+struct DuplicateWatch {
+	settings: Settings,
+	filters: WalkFilters,
+	stop_flag: Arc<AtomicBool>,
+	files: HashMap<PathBuf, FileEntry>,
+	hashes: HashCache,
+	groups: Vec<DuplicateGroup>,
+}
+
+// Files can only be duplicates of each other if they share this key
+#[derive(PartialEq, Eq, Hash)]
+struct Bucket {
+	size: Option<u64>,
+	name: Option<String>,
+}
+
+impl DuplicateWatch {
+	fn new(
+		settings: Settings,
+		filters: WalkFilters,
+		groups: Vec<DuplicateGroup>,
+		stop_flag: Arc<AtomicBool>,
+	) -> Option<Self> {
+		let walk = walk(&filters, &stop_flag, &AtomicUsize::new(0))?;
+		let files = walk
+			.files
+			.into_iter()
+			.map(|file| (file.path.clone(), file))
+			.collect();
+
+		// Hashes of the scan stay valid until their file changes
+		let mut hashes = HashCache::new();
+		if settings.duplicates_sub_check_method == "Hash" {
+			for entry in groups.iter().flat_map(group_entries) {
+				if !entry.hash.is_empty() {
+					hashes.insert(
+						entry.path.clone(),
+						CachedHash {
+							size: entry.size,
+							modified_date: entry.modified_date,
+							hash: entry.hash.clone(),
+						},
+					);
+				}
+			}
+		}
+
+		Some(DuplicateWatch {
+			settings,
+			filters,
+			stop_flag,
+			files,
+			hashes,
+			groups,
+		})
+	}
+
+	fn stale_paths(&self) -> HashSet<PathBuf> {
+		self.groups
+			.iter()
+			.flat_map(group_entries)
+			.filter(|entry| {
+				self.files.get(&entry.path).is_none_or(|file| {
+					file.size != entry.size
+						|| file.modified_date != entry.modified_date
+				})
+			})
+			.map(|entry| entry.path.clone())
+			.collect()
+	}
+
+	fn apply<S: ResultSink>(&mut self, sink: &S, changed: HashSet<PathBuf>) {
+		let Some(delta) = self.update(changed) else {
+			return;
+		};
+		let stop_flag = &self.stop_flag;
+		let groups = &self.groups;
+		let is_current = sink.with_state(|state| {
+			let is_current = state
+				.duplicate_watch
+				.as_ref()
+				.is_some_and(|current| Arc::ptr_eq(current, stop_flag));
+			if let Some(stored) =
+				state.duplicate_groups.as_mut().filter(|_| is_current)
+			{
+				stored.groups = groups.clone();
+			}
+			is_current
+		});
+		if is_current && (!delta.removed.is_empty() || !delta.added.is_empty())
+		{
+			sink.emit_event("duplicate-groups-delta", delta);
+		}
+	}
+
+	fn update(
+		&mut self,
+		changed: HashSet<PathBuf>,
+	) -> Option<DuplicateGroupsDelta> {
+		let mut buckets = HashSet::new();
+		for path in changed {
+			// Whatever is there now replaces everything known at or below
+			// the path, which also covers removed and renamed folders
+			let old: HashMap<PathBuf, FileEntry> = self
+				.files
+				.extract_if(|file_path, _| file_path.starts_with(&path))
+				.collect();
+			let new: HashMap<PathBuf, FileEntry> = self
+				.read(&path)?
+				.into_iter()
+				.map(|file| (file.path.clone(), file))
+				.collect();
+
+			// The reported path itself may have changed without its size or
+			// modification date changing
+			let unchanged =
+				|file: &FileEntry, others: &HashMap<PathBuf, FileEntry>| {
+					file.path != path
+						&& others.get(&file.path).is_some_and(|other| {
+							other.size == file.size
+								&& other.modified_date == file.modified_date
+						})
+				};
+			for file in old.values() {
+				if !unchanged(file, &new) {
+					self.hashes.remove(&file.path);
+					buckets.insert(self.bucket(file));
+				}
+			}
+			for file in new.values() {
+				if !unchanged(file, &old) {
+					buckets.insert(self.bucket(file));
+				}
+			}
+			self.files.extend(new);
+		}
+
+		if buckets.is_empty() {
+			return None;
+		}
+
+		let files: Vec<_> = self
+			.files
+			.values()
+			.filter(|file| buckets.contains(&self.bucket(file)))
+			.cloned()
+			.collect();
+		// Nobody follows the progress of a watch
+		let (progress_tx, _) = unbounded();
+		let current = find_duplicates(
+			&files,
+			&self.filters,
+			&self.settings,
+			&self.stop_flag,
+			&progress_tx,
+			&mut vec![],
+			&mut self.hashes,
+		);
+		if self.stop_flag.load(Ordering::Relaxed) {
+			return None;
+		}
+
+		let (affected, mut groups): (Vec<_>, Vec<_>) =
+			mem::take(&mut self.groups).into_iter().partition(|group| {
+				group.1.first().is_none_or(|entry| {
+					buckets.contains(&self.bucket_of(&entry.path, entry.size))
+				})
+			});
+		let removed = affected
+			.iter()
+			.filter(|group| {
+				!current.iter().any(|other| same_group(group, other))
+			})
+			.map(|group| {
+				group_entries(group)
+					.map(|entry| entry.path.clone())
+					.collect()
+			})
+			.collect();
+		let added = current
+			.iter()
+			.filter(|group| {
+				!affected.iter().any(|other| same_group(group, other))
+			})
+			.cloned()
+			.collect();
+
+		groups.extend(current);
+		groups.sort_by(|(_, a), (_, b)| {
+			split_path_compare(a[0].path.as_path(), b[0].path.as_path())
+		});
+		self.groups = groups;
+
+		Some(DuplicateGroupsDelta { removed, added })
+	}
+
+	// The files at or below `path` that a scan would see now. `None` when
+	// stopped.
+	fn read(&self, path: &Path) -> Option<Vec<FileEntry>> {
+		if !self.filters.covers(path) {
+			return Some(vec![]);
+		}
+		let Ok(metadata) = fs::symlink_metadata(path) else {
+			return Some(vec![]);
+		};
+		if metadata.is_file() {
+			Some(vec![FileEntry {
+				path: path.to_path_buf(),
+				size: metadata.len(),
+				modified_date: modified_date(&metadata),
+			}])
+		} else if metadata.is_dir() && self.settings.recursive_search {
+			walk_folder(
+				&self.filters,
+				path,
+				&self.stop_flag,
+				&AtomicUsize::new(0),
+			)
+			.map(|walk| walk.files)
+		} else {
+			Some(vec![])
+		}
+	}
+
+	fn bucket(&self, file: &FileEntry) -> Bucket {
+		self.bucket_of(&file.path, file.size)
+	}
+
+	fn bucket_of(&self, path: &Path, size: u64) -> Bucket {
+		let name = || {
+			let name = path
+				.file_name()
+				.map(|name| name.to_string_lossy().to_string())
+				.unwrap_or_default();
+			if self.settings.duplicates_sub_name_case_sensitive {
+				name
+			} else {
+				name.to_lowercase()
+			}
+		};
+		match self.settings.duplicates_sub_check_method.as_ref() {
+			"Name" => Bucket {
+				size: None,
+				name: Some(name()),
+			},
+			"SizeName" => Bucket {
+				size: Some(size),
+				name: Some(name()),
+			},
+			_ => Bucket {
+				size: Some(size),
+				name: None,
+			},
+		}
+	}
+}
+
+fn group_entries(
+	group: &DuplicateGroup,
+) -> impl Iterator<Item = &DuplicateEntry> {
+	group.0.iter().chain(group.1.iter())
+}
+
+fn same_group(a: &DuplicateGroup, b: &DuplicateGroup) -> bool {
+	let key = |group| {
+		group_entries(group)
+			.map(|entry| (&entry.path, entry.size, entry.modified_date))
+			.collect::<Vec<_>>()
+	};
+	a.0.as_ref().map(|entry| &entry.path)
+		== b.0.as_ref().map(|entry| &entry.path)
+		&& key(a) == key(b)
+}
@@ -0,0 +1,134 @@
+#![allow(dead_code)]
+
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	sync::Once,
+	thread,
+	time::{Duration, Instant},
+};
+
+use czkawka_core::common::{
+	config_cache_path::set_config_cache_path, set_number_of_threads,
+};
+use czkawka_tauri::{
+	scaner::{Tool, run_tool},
+	settings::Settings,
+	sink::MemorySink,
+};
+use serde_json::Value;
+use tempfile::TempDir;
+
+pub fn setup() {
+	static INIT: Once = Once::new();
+	INIT.call_once(|| {
+		// Keep czkawka's cache and config out of the user's directories
+		let dir = std::env::temp_dir().join("czkawka-tauri-tests");
+		fs::create_dir_all(&dir).unwrap();
+		unsafe {
+			std::env::set_var("CZKAWKA_CACHE_PATH", &dir);
+			std::env::set_var("CZKAWKA_CONFIG_PATH", &dir);
+		}
+		let _ = set_config_cache_path("czkawka", "czkawka");
+		set_number_of_threads(2);
+	});
+}
+
+pub fn fixture_dir() -> (TempDir, PathBuf) {
+	let dir = tempfile::tempdir().unwrap();
+	let root = dir.path().canonicalize().unwrap();
+	(dir, root)
+}
+
+pub fn write(path: &Path, content: &[u8]) {
+	fs::create_dir_all(path.parent().unwrap()).unwrap();
+	fs::write(path, content).unwrap();
+}
+
+pub fn png_bytes(seed: u8) -> Vec<u8> {
+	let img = image::RgbImage::from_fn(64, 64, |x, y| {
+		image::Rgb([(x * 4) as u8, (y * 4) as u8, seed])
+	});
+	let mut data = Vec::new();
+	img.write_to(
+		&mut std::io::Cursor::new(&mut data),
+		image::ImageFormat::Png,
+	)
+	.unwrap();
+	data
+}
+
+pub fn settings_for(root: &Path) -> Settings {
+	Settings {
+		included_directories: vec![root.to_string_lossy().to_string()],
+		minimum_file_size: 0,
+		use_cache: false,
+		..Settings::default()
+	}
+}
+
+pub fn scan(tool: Tool, settings: Settings) -> Value {
+	setup();
+	let sink = MemorySink::new();
+	run_tool(&sink, tool, settings);
+	let mut results = sink.results();
+	assert_eq!(results.len(), 1, "expected exactly one scan result");
+	let result = results.remove(0);
+	assert_eq!(result["cmd"], tool.cmd());
+	result
+}
+
+pub fn path_of(entry: &Value) -> String {
+	entry["path"].as_str().unwrap().to_string()
+}
+
+pub fn paths(result: &Value) -> Vec<String> {
+	let mut paths: Vec<_> = result["list"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(path_of)
+		.collect();
+	paths.sort();
+	paths
+}
+
+pub fn groups(result: &Value) -> Vec<Vec<String>> {
+	result["list"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|group| {
+			let mut paths: Vec<_> =
+				group[1].as_array().unwrap().iter().map(path_of).collect();
+			paths.sort();
+			paths
+		})
+		.collect()
+}
+
+pub fn p(root: &Path, relative: &str) -> String {
+	root.join(relative).to_string_lossy().to_string()
+}
+
+// Waits until `event` was emitted `count` times and returns the last payload
+pub fn wait_for_event(sink: &MemorySink, event: &str, count: usize) -> Value {
+	let started = Instant::now();
+	loop {
+		let payloads: Vec<_> = sink
+			.events()
+			.into_iter()
+			.filter(|(name, _)| name == event)
+			.map(|(_, payload)| payload)
+			.collect();
+		if payloads.len() >= count {
+			return payloads[count - 1].clone();
+		}
+		assert!(
+			started.elapsed() < Duration::from_secs(10),
+			"no {} event",
+			event
+		);
+		thread::sleep(Duration::from_millis(50));
+	}
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use std::fs;
+
+use common::{fixture_dir, p, settings_for, setup, wait_for_event, write};
+use czkawka_tauri::{
+	delete_files::delete_files,
+	scaner::{Tool, run_tool},
+	sink::MemorySink,
+};
+use serde_json::json;
+
+fn delete_verified(sink: &MemorySink, paths: &[String]) -> serde_json::Value {
+	let options = json!({
+		"paths": paths,
+		"moveDeletedFilesToTrash": false,
+		"isEmptyFoldersTool": false,
+		"verifyDuplicates": true,
+	});
+	delete_files(sink, serde_json::from_value(options).unwrap());
+	wait_for_event(sink, "delete-files-result", 1)
+}
+
+#[test]
+fn files_differing_from_the_kept_file_are_not_deleted() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("one.bin"), b"the same content");
+	write(&root.join("two.bin"), b"the same content");
+	write(&root.join("three.bin"), b"the same content");
+
+	setup();
+	let sink = MemorySink::new();
+	run_tool(&sink, Tool::DuplicateFiles, settings_for(&root));
+	// Same size, as with a hash collision
+	fs::write(root.join("three.bin"), b"the other conten").unwrap();
+
+	let result =
+		delete_verified(&sink, &[p(&root, "two.bin"), p(&root, "three.bin")]);
+	assert_eq!(result["successPaths"], json!([p(&root, "two.bin")]));
+	assert_eq!(result["mismatchedPaths"], json!([p(&root, "three.bin")]));
+	assert_eq!(result["errors"], json!([]));
+	assert!(!root.join("two.bin").exists());
+	assert!(root.join("three.bin").exists());
+}
+
+#[test]
+fn a_group_is_never_deleted_entirely() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("one.bin"), b"the same content");
+	write(&root.join("two.bin"), b"the same content");
+	write(&root.join("other.bin"), b"not a duplicate");
+
+	setup();
+	let sink = MemorySink::new();
+	run_tool(&sink, Tool::DuplicateFiles, settings_for(&root));
+
+	let result = delete_verified(
+		&sink,
+		&[
+			p(&root, "one.bin"),
+			p(&root, "two.bin"),
+			p(&root, "other.bin"),
+		],
+	);
+	assert_eq!(result["successPaths"], json!([]));
+	assert_eq!(result["errors"].as_array().unwrap().len(), 3);
+	assert!(root.join("one.bin").exists());
+	assert!(root.join("two.bin").exists());
+	assert!(root.join("other.bin").exists());
+}
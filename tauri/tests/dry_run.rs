@@ -0,0 +1,199 @@
+mod common;
+
+use std::fs::{self, File};
+
+use common::{fixture_dir, p, wait_for_event, write};
+use czkawka_tauri::{
+	delete_files::delete_files, journal::list_operations,
+	move_files::move_files, rename_ext::rename_ext, sink::MemorySink,
+};
+use serde_json::{Value, json};
+
+fn planned(result: &Value) -> Vec<(String, String, Value)> {
+	let mut planned: Vec<_> = result["planned"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|action| {
+			(
+				action["action"].as_str().unwrap().to_string(),
+				action["source"].as_str().unwrap().to_string(),
+				action["destination"].clone(),
+			)
+		})
+		.collect();
+	planned.sort_by(|a, b| a.1.cmp(&b.1));
+	planned
+}
+
+#[test]
+fn dry_run_move_plans_without_moving() {
+	let (_dir, root) = fixture_dir();
+	let data_dir = tempfile::tempdir().unwrap();
+	write(&root.join("src/one.txt"), b"one");
+	write(&root.join("src/two.txt"), b"two");
+	write(&root.join("dest/two.txt"), b"already there");
+	let sink = MemorySink::with_data_dir(data_dir.path().to_path_buf());
+
+	let options = json!({
+		"paths": [
+			p(&root, "src/one.txt"),
+			p(&root, "src/two.txt"),
+			p(&root, "src/missing.txt"),
+		],
+		"destination": p(&root, "dest"),
+		"copyMode": false,
+		"preserveStructure": false,
+		"overrideMode": false,
+		"dryRun": true,
+	});
+	move_files(&sink, serde_json::from_value(options).unwrap());
+	let result = wait_for_event(&sink, "move-files-result", 1);
+
+	assert_eq!(
+		planned(&result),
+		[(
+			"move".to_string(),
+			p(&root, "src/one.txt"),
+			json!(p(&root, "dest/one.txt"))
+		)]
+	);
+	assert_eq!(result["successPaths"], json!([p(&root, "src/one.txt")]));
+	// Two.txt already exists, missing.txt does not
+	assert_eq!(result["errors"].as_array().unwrap().len(), 2);
+	assert_eq!(result["operationId"], Value::Null);
+
+	let options = json!({
+		"paths": [p(&root, "src/two.txt")],
+		"destination": p(&root, "dest"),
+		"copyMode": true,
+		"preserveStructure": false,
+		"overrideMode": false,
+		"dryRun": true,
+	});
+	move_files(&sink, serde_json::from_value(options).unwrap());
+	let result = wait_for_event(&sink, "move-files-result", 2);
+	assert_eq!(result["planned"], json!([]));
+	assert!(
+		result["errors"][0]
+			.as_str()
+			.unwrap()
+			.contains("already exists")
+	);
+
+	assert!(root.join("src/one.txt").exists());
+	assert!(!root.join("dest/one.txt").exists());
+	assert!(list_operations(&sink, None).unwrap().is_empty());
+}
+
+#[test]
+fn dry_run_delete_and_rename_leave_files_alone() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("one.txt"), b"one");
+	write(&root.join("image.jpg"), b"not really a png");
+	let sink = MemorySink::new();
+
+	let options = json!({
+		"paths": [p(&root, "one.txt"), p(&root, "missing.txt")],
+		"moveDeletedFilesToTrash": false,
+		"isEmptyFoldersTool": false,
+		"dryRun": true,
+	});
+	delete_files(&sink, serde_json::from_value(options).unwrap());
+	let result = wait_for_event(&sink, "delete-files-result", 1);
+	assert_eq!(
+		planned(&result),
+		[("delete".to_string(), p(&root, "one.txt"), Value::Null)]
+	);
+	assert_eq!(
+		result["errors"],
+		json!([format!("`{}` not found", p(&root, "missing.txt"))])
+	);
+
+	let options = json!({
+		"items": [{ "path": p(&root, "image.jpg"), "ext": "png" }],
+		"dryRun": true,
+	});
+	rename_ext(&sink, serde_json::from_value(options).unwrap());
+	let result = wait_for_event(&sink, "rename-ext-result", 1);
+	assert_eq!(
+		planned(&result),
+		[(
+			"rename".to_string(),
+			p(&root, "image.jpg"),
+			json!(p(&root, "image.png"))
+		)]
+	);
+
+	assert!(root.join("one.txt").exists());
+	assert!(root.join("image.jpg").exists());
+	assert!(!root.join("image.png").exists());
+	assert_eq!(fs::read_dir(&root).unwrap().count(), 2);
+}
+
+#[test]
+fn dry_run_reports_the_refusal_of_the_real_run() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("small.txt"), b"small");
+	// Sparse, so it takes no space itself
+	let huge = File::create(root.join("huge.bin")).unwrap();
+	huge.set_len(8 << 40).unwrap();
+	let sink = MemorySink::new();
+
+	let run = |dry_run: bool, count| {
+		let options = json!({
+			"paths": [p(&root, "small.txt"), p(&root, "huge.bin")],
+			"destination": p(&root, "dest"),
+			"copyMode": true,
+			"preserveStructure": false,
+			"overrideMode": false,
+			"dryRun": dry_run,
+		});
+		move_files(&sink, serde_json::from_value(options).unwrap());
+		wait_for_event(&sink, "move-files-result", count)
+	};
+	let planned = run(true, 1);
+	let refused = run(false, 2);
+
+	assert_eq!(planned["planned"], json!([]));
+	assert_eq!(planned["successPaths"], json!([]));
+	assert_eq!(refused["successPaths"], json!([]));
+	// The free space may change a little between both runs
+	for result in [&planned, &refused] {
+		let space = &result["refusal"]["space"];
+		assert_eq!(space.as_array().unwrap().len(), 1);
+		assert_eq!(space[0]["destination"], p(&root, "dest"));
+		assert_eq!(space[0]["needed"], (8u64 << 40) + 5);
+		assert_eq!(result["errors"].as_array().unwrap().len(), 1);
+	}
+	assert!(!root.join("dest").exists());
+}
+
+#[test]
+fn renames_never_replace_existing_files() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("photo.png"), b"png");
+	write(&root.join("photo.jpg"), b"jpg");
+	write(&root.join("other.png"), b"other");
+	let sink = MemorySink::new();
+
+	for (dry_run, count) in [(true, 1), (false, 2)] {
+		let options = json!({
+			"items": [
+				{ "path": p(&root, "photo.png"), "ext": "jpg" },
+				{ "path": p(&root, "other.png"), "ext": "jpg" },
+			],
+			"dryRun": dry_run,
+		});
+		rename_ext(&sink, serde_json::from_value(options).unwrap());
+		let result = wait_for_event(&sink, "rename-ext-result", count);
+		assert_eq!(result["successPaths"], json!([p(&root, "other.png")]));
+		let errors = result["errors"].as_array().unwrap();
+		assert_eq!(errors.len(), 1);
+		assert!(errors[0].as_str().unwrap().contains("already exists"));
+	}
+
+	assert_eq!(fs::read(root.join("photo.png")).unwrap(), b"png");
+	assert_eq!(fs::read(root.join("photo.jpg")).unwrap(), b"jpg");
+	assert_eq!(fs::read(root.join("other.jpg")).unwrap(), b"other");
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use std::fs;
+
+use common::{fixture_dir, p, wait_for_event, write};
+use czkawka_core::common::progress_data::CurrentStage;
+use czkawka_tauri::{
+	delete_files::run_delete_files,
+	job::JobStatus,
+	move_files::{move_files, run_move_files},
+	sink::{MemorySink, ResultSink},
+};
+use serde_json::json;
+
+#[test]
+fn moving_reports_files_and_bytes() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("a.bin"), &[1; 3000]);
+	write(&root.join("b.bin"), &[2; 2000]);
+
+	let sink = MemorySink::new();
+	let progress_rx = sink.progress_rx();
+	let options = json!({
+		"paths": [p(&root, "a.bin"), p(&root, "b.bin")],
+		"destination": p(&root, "dest"),
+		"copyMode": true,
+		"preserveStructure": false,
+		"overrideMode": false,
+	});
+	let job_id = move_files(&sink, serde_json::from_value(options).unwrap());
+	let result = wait_for_event(&sink, "move-files-result", 1);
+	assert_eq!(result["jobId"], job_id);
+	assert_eq!(result["cancelled"], false);
+	assert_eq!(result["errors"], json!([]));
+
+	let last = progress_rx
+		.try_iter()
+		.filter(|progress| progress.job_id == Some(job_id))
+		.last()
+		.unwrap();
+	assert_eq!(last.data.sstage, CurrentStage::MovingFiles);
+	assert_eq!(last.data.entries_checked, 2);
+	assert_eq!(last.data.entries_to_check, 2);
+	assert_eq!(last.data.bytes_checked, 5000);
+	assert_eq!(last.data.bytes_to_check, 5000);
+
+	let info = sink.with_state(|state| state.jobs.info(job_id)).unwrap();
+	assert_eq!(info.operation, Some("move_files"));
+	assert_eq!(info.status, JobStatus::Finished);
+}
+
+#[test]
+fn cancelled_operations_leave_files_alone() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("a.txt"), b"a");
+	write(&root.join("b.txt"), b"b");
+
+	let sink = MemorySink::new();
+	let job = sink.with_state(|state| state.jobs.start_operation("move_files"));
+	let move_id = job.id;
+	assert!(sink.with_state(|state| state.jobs.cancel(move_id)));
+	let options = json!({
+		"paths": [p(&root, "a.txt")],
+		"destination": p(&root, "dest"),
+		"copyMode": false,
+		"preserveStructure": false,
+		"overrideMode": false,
+	});
+	run_move_files(&sink, job, serde_json::from_value(options).unwrap());
+
+	let job =
+		sink.with_state(|state| state.jobs.start_operation("delete_files"));
+	let delete_id = job.id;
+	sink.with_state(|state| state.jobs.cancel_all());
+	assert!(sink.with_state(|state| state.jobs.cancel(delete_id)));
+	let options = json!({
+		"paths": [p(&root, "b.txt")],
+		"moveDeletedFilesToTrash": false,
+		"isEmptyFoldersTool": false,
+	});
+	run_delete_files(&sink, job, serde_json::from_value(options).unwrap());
+
+	let moved = wait_for_event(&sink, "move-files-result", 1);
+	assert_eq!(moved["cancelled"], true);
+	assert_eq!(moved["successPaths"], json!([]));
+	let deleted = wait_for_event(&sink, "delete-files-result", 1);
+	assert_eq!(deleted["cancelled"], true);
+	assert_eq!(deleted["successPaths"], json!([]));
+
+	assert!(root.join("a.txt").exists());
+	assert!(root.join("b.txt").exists());
+	assert!(!root.join("dest").exists());
+	for id in [move_id, delete_id] {
+		let info = sink.with_state(|state| state.jobs.info(id)).unwrap();
+		assert_eq!(info.status, JobStatus::Cancelled);
+	}
+	assert_eq!(fs::read(root.join("a.txt")).unwrap(), b"a");
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::{fixture_dir, settings_for, setup, write};
+use czkawka_tauri::{
+	history::{delete_history_entries, get_history_entry, list_history},
+	scaner::{Tool, run_tool},
+	sink::MemorySink,
+};
+
+#[test]
+fn scans_are_recorded_in_history() {
+	let (_dir, root) = fixture_dir();
+	let data_dir = tempfile::tempdir().unwrap();
+	write(&root.join("a/one.bin"), b"the same content");
+	write(&root.join("b/two.bin"), b"the same content");
+	write(&root.join("c/three.bin"), b"the same content");
+	write(&root.join("d/four.bin"), b"other content");
+	write(&root.join("d/five.bin"), b"other content");
+	write(&root.join("e/empty.bin"), b"");
+
+	setup();
+	let sink = MemorySink::with_data_dir(data_dir.path().to_path_buf());
+	run_tool(&sink, Tool::DuplicateFiles, settings_for(&root));
+	run_tool(&sink, Tool::EmptyFiles, settings_for(&root));
+
+	let history = list_history(&sink, None, None).unwrap();
+	assert_eq!(history.len(), 2);
+	// Newest first
+	assert_eq!(history[0].tool, Tool::EmptyFiles);
+	assert_eq!(history[0].groups_found, 1);
+	assert_eq!(history[0].wasted_bytes, 0);
+
+	let duplicates = &history[1];
+	assert_eq!(duplicates.tool, Tool::DuplicateFiles);
+	assert_eq!(duplicates.groups_found, 2);
+	assert_eq!(duplicates.wasted_bytes, 16 * 2 + 13);
+	assert!(duplicates.finished_at >= duplicates.started_at);
+
+	let only_duplicates =
+		list_history(&sink, Some(Tool::DuplicateFiles), None).unwrap();
+	assert_eq!(only_duplicates.len(), 1);
+	assert_eq!(list_history(&sink, None, Some(1)).unwrap().len(), 1);
+
+	let details = get_history_entry(&sink, duplicates.id).unwrap().unwrap();
+	assert_eq!(
+		details.settings.included_directories,
+		vec![root.to_string_lossy().to_string()]
+	);
+	assert!(get_history_entry(&sink, 1000).unwrap().is_none());
+
+	assert_eq!(
+		delete_history_entries(&sink, &[duplicates.id, 1000]).unwrap(),
+		1
+	);
+	let history = list_history(&sink, None, None).unwrap();
+	assert_eq!(history.len(), 1);
+	assert_eq!(history[0].tool, Tool::EmptyFiles);
+}
+
+#[test]
+fn history_needs_a_data_dir() {
+	assert!(list_history(&MemorySink::new(), None, None).is_err());
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{fixture_dir, groups, p, settings_for, setup, write};
+use czkawka_tauri::{
+	incremental::rescan_duplicate_files, settings::Settings, sink::MemorySink,
+};
+use serde_json::Value;
+
+fn rescan(sink: &MemorySink, settings: Settings) -> Value {
+	rescan_duplicate_files(sink, settings);
+	sink.results().pop().unwrap()
+}
+
+fn folder_counts(result: &Value) -> (usize, usize) {
+	let message = result["message"].as_str().unwrap();
+	let line = message.lines().nth(1).unwrap();
+	let numbers: Vec<usize> = line
+		.split_whitespace()
+		.filter_map(|word| word.parse().ok())
+		.collect();
+	(numbers[0], numbers[1])
+}
+
+fn write_fixture(root: &Path) {
+	write(&root.join("a/one.bin"), b"the same content");
+	write(&root.join("b/two.bin"), b"the same content");
+	write(&root.join("c/other.bin"), b"another content!");
+}
+
+#[test]
+fn rescan_only_reads_changed_folders() {
+	let (_dir, root) = fixture_dir();
+	let data_dir = tempfile::tempdir().unwrap();
+	write_fixture(&root);
+
+	setup();
+	let sink = MemorySink::with_data_dir(data_dir.path().to_path_buf());
+
+	let first = rescan(&sink, settings_for(&root));
+	assert_eq!(folder_counts(&first), (4, 0));
+	assert_eq!(
+		groups(&first),
+		vec![vec![p(&root, "a/one.bin"), p(&root, "b/two.bin")]]
+	);
+
+	let second = rescan(&sink, settings_for(&root));
+	assert_eq!(folder_counts(&second), (0, 4));
+	assert_eq!(groups(&second), groups(&first));
+
+	write(&root.join("c/three.bin"), b"the same content");
+	fs::remove_file(root.join("a/one.bin")).unwrap();
+
+	let third = rescan(&sink, settings_for(&root));
+	assert_eq!(folder_counts(&third), (2, 2));
+	assert_eq!(
+		groups(&third),
+		vec![vec![p(&root, "b/two.bin"), p(&root, "c/three.bin")]]
+	);
+}
+
+#[test]
+fn rescan_starts_over_when_directories_change() {
+	let (_dir, root) = fixture_dir();
+	let data_dir = tempfile::tempdir().unwrap();
+	write_fixture(&root);
+
+	setup();
+	let sink = MemorySink::with_data_dir(data_dir.path().to_path_buf());
+	rescan(&sink, settings_for(&root));
+
+	let settings = Settings {
+		excluded_directories: vec![p(&root, "c")],
+		..settings_for(&root)
+	};
+	let result = rescan(&sink, settings);
+	assert_eq!(folder_counts(&result), (3, 0));
+}
+
+#[test]
+fn rescan_without_data_dir_is_a_full_scan() {
+	let (_dir, root) = fixture_dir();
+	write_fixture(&root);
+
+	setup();
+	let sink = MemorySink::new();
+	rescan(&sink, settings_for(&root));
+	let result = rescan(&sink, settings_for(&root));
+
+	assert_eq!(folder_counts(&result), (4, 0));
+	assert_eq!(groups(&result).len(), 1);
+}
+
+#[test]
+fn rescan_notices_files_edited_in_place() {
+	let (_dir, root) = fixture_dir();
+	let data_dir = tempfile::tempdir().unwrap();
+	write_fixture(&root);
+
+	setup();
+	let sink = MemorySink::with_data_dir(data_dir.path().to_path_buf());
+	let first = rescan(&sink, settings_for(&root));
+	assert_eq!(groups(&first).len(), 1);
+
+	// Overwriting keeps the folder's mtime, so its listing is reused
+	fs::write(root.join("a/one.bin"), b"edited since the last rescan").unwrap();
+	let second = rescan(&sink, settings_for(&root));
+	assert_eq!(folder_counts(&second), (0, 4));
+	assert!(groups(&second).is_empty());
+}
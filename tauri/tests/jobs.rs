@@ -0,0 +1,72 @@
+mod common;
+
+use common::{fixture_dir, settings_for, setup, write};
+use czkawka_tauri::{
+	job::{JobStatus, run_job, start_job},
+	scaner::Tool,
+	sink::{MemorySink, ResultSink},
+};
+
+#[test]
+fn job_result_is_tagged_with_job_id() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("zero.txt"), b"");
+
+	setup();
+	let sink = MemorySink::new();
+	let job = start_job(&sink, vec![Tool::EmptyFiles]);
+	let job_id = job.id;
+	run_job(&sink, job, settings_for(&root));
+
+	let results = sink.results();
+	assert_eq!(results.len(), 1);
+	assert_eq!(results[0]["jobId"], job_id);
+	assert_eq!(results[0]["cmd"], "scan_empty_files");
+
+	let info = sink.with_state(|state| state.jobs.info(job_id)).unwrap();
+	assert_eq!(info.status, JobStatus::Finished);
+	assert!(info.finished_at.is_some());
+}
+
+#[test]
+fn cancelling_one_job_leaves_others_running() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("zero.txt"), b"");
+
+	setup();
+	let sink = MemorySink::new();
+	let first = start_job(&sink, vec![Tool::EmptyFiles]);
+	let second = start_job(&sink, vec![Tool::EmptyFiles]);
+	assert_ne!(first.id, second.id);
+
+	assert!(sink.with_state(|state| state.jobs.cancel(first.id)));
+	assert!(!second.stop_flag.load(std::sync::atomic::Ordering::Relaxed));
+
+	let (first_id, second_id) = (first.id, second.id);
+	run_job(&sink, first, settings_for(&root));
+	run_job(&sink, second, settings_for(&root));
+
+	let jobs = sink.with_state(|state| state.jobs.list());
+	let status = |id| jobs.iter().find(|job| job.id == id).unwrap().status;
+	assert_eq!(status(first_id), JobStatus::Cancelled);
+	assert_eq!(status(second_id), JobStatus::Finished);
+	assert!(!sink.with_state(|state| state.jobs.cancel(second_id)));
+}
+
+#[test]
+fn job_progress_is_tagged_with_job_id() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("zero.txt"), b"");
+
+	setup();
+	let sink = MemorySink::new();
+	let progress_rx = sink.progress_rx();
+	let job = start_job(&sink, vec![Tool::EmptyFiles]);
+	let job_id = job.id;
+	run_job(&sink, job, settings_for(&root));
+
+	let progress = progress_rx
+		.recv_timeout(std::time::Duration::from_secs(5))
+		.unwrap();
+	assert_eq!(progress.job_id, Some(job_id));
+}
@@ -0,0 +1,243 @@
+mod common;
+
+use std::fs;
+
+use common::{fixture_dir, p, wait_for_event, write};
+use czkawka_tauri::{
+	delete_files::delete_files,
+	journal::{Action, list_operations, operation_entries, undo_operation},
+	move_files::move_files,
+	rename_ext::rename_ext,
+	scaner::Tool,
+	sink::{MemorySink, ResultSink},
+};
+use rusqlite::Connection;
+use serde_json::{Value, json};
+
+fn operation_id(result: &Value) -> i64 {
+	assert_eq!(result["errors"], json!([]));
+	result["operationId"].as_i64().unwrap()
+}
+
+#[test]
+fn move_and_rename_are_undone() {
+	let (_dir, root) = fixture_dir();
+	let data_dir = tempfile::tempdir().unwrap();
+	write(&root.join("src/one.txt"), b"one");
+	write(&root.join("src/two.txt"), b"two");
+	write(&root.join("image.jpg"), b"not really a png");
+	let sink = MemorySink::with_data_dir(data_dir.path().to_path_buf());
+
+	let options = json!({
+		"paths": [p(&root, "src/one.txt"), p(&root, "src/two.txt")],
+		"destination": p(&root, "dest"),
+		"copyMode": false,
+		"preserveStructure": false,
+		"overrideMode": false,
+	});
+	move_files(&sink, serde_json::from_value(options).unwrap());
+	let moved = operation_id(&wait_for_event(&sink, "move-files-result", 1));
+	assert!(root.join("dest/one.txt").exists());
+	assert!(!root.join("src/one.txt").exists());
+
+	let options = json!({
+		"items": [{ "path": p(&root, "image.jpg"), "ext": "png" }],
+	});
+	rename_ext(&sink, serde_json::from_value(options).unwrap());
+	let renamed = operation_id(&wait_for_event(&sink, "rename-ext-result", 1));
+	assert!(root.join("image.png").exists());
+
+	let operations = list_operations(&sink, None).unwrap();
+	let kinds: Vec<_> = operations.iter().map(|op| op.kind.as_str()).collect();
+	assert_eq!(kinds, ["rename_ext", "move_files"]);
+	assert_eq!(operations[1].entries, 2);
+	let entries = operation_entries(&sink, moved).unwrap();
+	assert!(entries.iter().all(|entry| entry.action == Action::Move));
+
+	undo_operation(&sink, moved);
+	let undone = wait_for_event(&sink, "undo-result", 1);
+	assert_eq!(undone["errors"], json!([]));
+	assert_eq!(fs::read(root.join("src/one.txt")).unwrap(), b"one");
+	assert_eq!(fs::read(root.join("src/two.txt")).unwrap(), b"two");
+	assert!(!root.join("dest/one.txt").exists());
+
+	undo_operation(&sink, renamed);
+	wait_for_event(&sink, "undo-result", 2);
+	assert!(root.join("image.jpg").exists());
+	assert!(!root.join("image.png").exists());
+
+	let operations = list_operations(&sink, Some(1)).unwrap();
+	assert_eq!(operations.len(), 1);
+	assert!(operations[0].undone_at.is_some());
+}
+
+#[test]
+fn undo_keeps_the_steps_done_before_a_journal_failure() {
+	let (_dir, root) = fixture_dir();
+	let data_dir = tempfile::tempdir().unwrap();
+	write(&root.join("src/one.txt"), b"one");
+	write(&root.join("src/two.txt"), b"two");
+	let sink = MemorySink::with_data_dir(data_dir.path().to_path_buf());
+
+	let options = json!({
+		"paths": [p(&root, "src/one.txt"), p(&root, "src/two.txt")],
+		"destination": p(&root, "dest"),
+		"copyMode": false,
+		"preserveStructure": false,
+		"overrideMode": false,
+	});
+	move_files(&sink, serde_json::from_value(options).unwrap());
+	let moved = operation_id(&wait_for_event(&sink, "move-files-result", 1));
+
+	// Fails the last write of the undo, after every file is back
+	let journal = Connection::open(data_dir.path().join("journal.db")).unwrap();
+	journal
+		.execute_batch(
+			"CREATE TRIGGER fail_undone_at BEFORE UPDATE ON operations
+				BEGIN SELECT RAISE(ABORT, 'journal is full'); END;",
+		)
+		.unwrap();
+	undo_operation(&sink, moved);
+	let undone = wait_for_event(&sink, "undo-result", 1);
+	assert_eq!(undone["errors"].as_array().unwrap().len(), 1);
+	assert_eq!(undone["successPaths"].as_array().unwrap().len(), 2);
+	assert!(root.join("src/one.txt").exists());
+	assert!(root.join("src/two.txt").exists());
+
+	// The retry only has to mark the operation as undone
+	journal
+		.execute_batch("DROP TRIGGER fail_undone_at;")
+		.unwrap();
+	undo_operation(&sink, moved);
+	let retried = wait_for_event(&sink, "undo-result", 2);
+	assert_eq!(retried["errors"], json!([]));
+	assert_eq!(retried["successPaths"], json!([]));
+	let operations = list_operations(&sink, None).unwrap();
+	assert!(operations[0].undone_at.is_some());
+}
+
+#[test]
+fn copy_undo_removes_the_copy_only_once() {
+	let (_dir, root) = fixture_dir();
+	let data_dir = tempfile::tempdir().unwrap();
+	write(&root.join("one.txt"), b"one");
+	let sink = MemorySink::with_data_dir(data_dir.path().to_path_buf());
+
+	let options = json!({
+		"paths": [p(&root, "one.txt")],
+		"destination": p(&root, "copies"),
+		"copyMode": true,
+		"preserveStructure": false,
+		"overrideMode": false,
+	});
+	move_files(&sink, serde_json::from_value(options).unwrap());
+	let copied = operation_id(&wait_for_event(&sink, "move-files-result", 1));
+	assert!(root.join("copies/one.txt").exists());
+
+	undo_operation(&sink, copied);
+	assert_eq!(wait_for_event(&sink, "undo-result", 1)["errors"], json!([]));
+	assert!(root.join("one.txt").exists());
+	assert!(!root.join("copies/one.txt").exists());
+
+	undo_operation(&sink, copied);
+	let again = wait_for_event(&sink, "undo-result", 2);
+	assert_eq!(again["errors"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn copy_undo_keeps_copies_changed_since() {
+	let (_dir, root) = fixture_dir();
+	let data_dir = tempfile::tempdir().unwrap();
+	write(&root.join("one.txt"), b"one");
+	let sink = MemorySink::with_data_dir(data_dir.path().to_path_buf());
+
+	let options = json!({
+		"paths": [p(&root, "one.txt")],
+		"destination": p(&root, "copies"),
+		"copyMode": true,
+		"preserveStructure": false,
+		"overrideMode": false,
+	});
+	move_files(&sink, serde_json::from_value(options).unwrap());
+	let copied = operation_id(&wait_for_event(&sink, "move-files-result", 1));
+	fs::write(root.join("copies/one.txt"), b"edited").unwrap();
+
+	undo_operation(&sink, copied);
+	let errors = wait_for_event(&sink, "undo-result", 1)["errors"].clone();
+	assert_eq!(errors.as_array().unwrap().len(), 1);
+	assert!(errors[0].as_str().unwrap().contains("changed"));
+	assert_eq!(fs::read(root.join("copies/one.txt")).unwrap(), b"edited");
+}
+
+#[test]
+fn copy_undo_keeps_copies_in_reference_folders() {
+	let (_dir, root) = fixture_dir();
+	let data_dir = tempfile::tempdir().unwrap();
+	write(&root.join("src/one.txt"), b"one");
+	let sink = MemorySink::with_data_dir(data_dir.path().to_path_buf());
+
+	let options = json!({
+		"paths": [p(&root, "src/one.txt")],
+		"destination": p(&root, "copies"),
+		"copyMode": true,
+		"preserveStructure": false,
+		"overrideMode": false,
+	});
+	move_files(&sink, serde_json::from_value(options).unwrap());
+	let copied = operation_id(&wait_for_event(&sink, "move-files-result", 1));
+
+	// As if the last Empty Files scan used the copies as reference
+	sink.with_state(|state| {
+		state
+			.referenced_directories
+			.insert(Tool::EmptyFiles, vec![root.join("copies")])
+	});
+
+	undo_operation(&sink, copied);
+	let errors = wait_for_event(&sink, "undo-result", 1)["errors"].clone();
+	assert_eq!(errors.as_array().unwrap().len(), 1);
+	assert!(errors[0].as_str().unwrap().contains("reference folder"));
+	assert_eq!(fs::read(root.join("copies/one.txt")).unwrap(), b"one");
+}
+
+#[test]
+fn permanent_delete_cannot_be_undone() {
+	let (_dir, root) = fixture_dir();
+	let data_dir = tempfile::tempdir().unwrap();
+	write(&root.join("one.txt"), b"one");
+	let sink = MemorySink::with_data_dir(data_dir.path().to_path_buf());
+
+	let options = json!({
+		"paths": [p(&root, "one.txt")],
+		"moveDeletedFilesToTrash": false,
+		"isEmptyFoldersTool": false,
+	});
+	delete_files(&sink, serde_json::from_value(options).unwrap());
+	let deleted =
+		operation_id(&wait_for_event(&sink, "delete-files-result", 1));
+	assert_eq!(
+		operation_entries(&sink, deleted).unwrap()[0].action,
+		Action::Delete
+	);
+
+	undo_operation(&sink, deleted);
+	let undone = wait_for_event(&sink, "undo-result", 1);
+	assert_eq!(undone["successPaths"], json!([]));
+	assert_eq!(undone["errors"].as_array().unwrap().len(), 1);
+	assert!(list_operations(&sink, None).unwrap()[0].undone_at.is_none());
+}
+
+#[test]
+fn nothing_is_journaled_without_data_dir() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("one.txt"), b"one");
+	let sink = MemorySink::new();
+
+	let options = json!({
+		"items": [{ "path": p(&root, "one.txt"), "ext": "md" }],
+	});
+	rename_ext(&sink, serde_json::from_value(options).unwrap());
+	let result = wait_for_event(&sink, "rename-ext-result", 1);
+	assert_eq!(result["successPaths"].as_array().unwrap().len(), 1);
+	assert_eq!(result["operationId"], Value::Null);
+}
@@ -0,0 +1,176 @@
+mod common;
+
+use std::fs;
+
+use common::{fixture_dir, p, settings_for, setup, wait_for_event, write};
+use czkawka_tauri::{
+	link_duplicates::link_duplicates,
+	scaner::{Tool, run_tool},
+	sink::MemorySink,
+};
+use serde_json::json;
+
+#[cfg(unix)]
+fn inode(path: &std::path::Path) -> u64 {
+	use std::os::unix::fs::MetadataExt;
+	fs::metadata(path).unwrap().ino()
+}
+
+#[test]
+fn duplicates_are_replaced_with_hard_links() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("a/one.bin"), b"the same content");
+	write(&root.join("b/two.bin"), b"the same content");
+	write(&root.join("c/three.bin"), b"the same content");
+
+	setup();
+	let sink = MemorySink::new();
+	run_tool(&sink, Tool::DuplicateFiles, settings_for(&root));
+
+	let options = json!({ "keep": [p(&root, "a/one.bin")] });
+	link_duplicates(&sink, serde_json::from_value(options).unwrap());
+	let result = wait_for_event(&sink, "link-duplicates-result", 1);
+	assert_eq!(result["errors"], json!([]));
+	let mut linked: Vec<_> = result["successPaths"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|path| path.as_str().unwrap().to_string())
+		.collect();
+	linked.sort();
+	assert_eq!(linked, [p(&root, "b/two.bin"), p(&root, "c/three.bin")]);
+
+	assert_eq!(
+		fs::read(root.join("b/two.bin")).unwrap(),
+		b"the same content"
+	);
+	#[cfg(unix)]
+	{
+		let kept = inode(&root.join("a/one.bin"));
+		assert_eq!(inode(&root.join("b/two.bin")), kept);
+		assert_eq!(inode(&root.join("c/three.bin")), kept);
+	}
+	assert_eq!(fs::read_dir(root.join("b")).unwrap().count(), 1);
+}
+
+#[test]
+fn files_changed_since_the_scan_are_kept() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("one.bin"), b"the same content");
+	write(&root.join("two.bin"), b"the same content");
+
+	setup();
+	let sink = MemorySink::new();
+	run_tool(&sink, Tool::DuplicateFiles, settings_for(&root));
+	fs::write(root.join("two.bin"), b"now different").unwrap();
+
+	let options =
+		json!({ "keep": [p(&root, "one.bin"), p(&root, "unknown.bin")] });
+	link_duplicates(&sink, serde_json::from_value(options).unwrap());
+	let result = wait_for_event(&sink, "link-duplicates-result", 1);
+	assert_eq!(result["successPaths"], json!([]));
+	let errors = result["errors"].as_array().unwrap();
+	assert_eq!(errors.len(), 2);
+	assert!(errors[0].as_str().unwrap().contains("unknown.bin"));
+	assert!(errors[1].as_str().unwrap().contains("content differs"));
+	assert_eq!(fs::read(root.join("two.bin")).unwrap(), b"now different");
+}
+
+#[test]
+fn nothing_is_linked_without_a_scan() {
+	let sink = MemorySink::new();
+	let options = json!({ "keep": ["/nowhere/one.bin"] });
+	link_duplicates(&sink, serde_json::from_value(options).unwrap());
+	let result = wait_for_event(&sink, "link-duplicates-result", 1);
+	assert_eq!(result["errors"].as_array().unwrap().len(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn duplicates_are_replaced_with_symlinks() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("a/one.bin"), b"the same content");
+	write(&root.join("b/two.bin"), b"the same content");
+
+	setup();
+	let sink = MemorySink::new();
+	run_tool(&sink, Tool::DuplicateFiles, settings_for(&root));
+
+	let options = json!({ "keep": [p(&root, "a/one.bin")], "mode": "symlink" });
+	link_duplicates(&sink, serde_json::from_value(options).unwrap());
+	let result = wait_for_event(&sink, "link-duplicates-result", 1);
+	assert_eq!(result["successPaths"], json!([p(&root, "b/two.bin")]));
+	assert_eq!(
+		fs::read_link(root.join("b/two.bin")).unwrap(),
+		root.join("a/one.bin")
+	);
+}
+
+#[test]
+fn reflinks_fail_per_file_where_unsupported() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("one.bin"), b"the same content");
+	write(&root.join("two.bin"), b"the same content");
+
+	setup();
+	let sink = MemorySink::new();
+	run_tool(&sink, Tool::DuplicateFiles, settings_for(&root));
+
+	let options = json!({ "keep": [p(&root, "one.bin")], "mode": "reflink" });
+	link_duplicates(&sink, serde_json::from_value(options).unwrap());
+	let result = wait_for_event(&sink, "link-duplicates-result", 1);
+	// The temporary directory may or may not support reflinks, either way
+	// the duplicate is still there with its content
+	let replaced = result["successPaths"].as_array().unwrap().len();
+	let errors = result["errors"].as_array().unwrap();
+	assert_eq!(replaced + errors.len(), 1);
+	if let Some(error) = errors.first() {
+		assert!(error.as_str().unwrap().contains("reflink"));
+	}
+	assert_eq!(fs::read(root.join("two.bin")).unwrap(), b"the same content");
+	assert_eq!(fs::read_dir(&root).unwrap().count(), 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn only_the_first_kept_file_of_a_group_is_kept() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("a/one.bin"), b"the same content");
+	write(&root.join("b/two.bin"), b"the same content");
+	write(&root.join("c/three.bin"), b"the same content");
+
+	setup();
+	let sink = MemorySink::new();
+	run_tool(&sink, Tool::DuplicateFiles, settings_for(&root));
+
+	let options = json!({
+		"keep": [p(&root, "a/one.bin"), p(&root, "b/two.bin")],
+		"mode": "symlink",
+	});
+	link_duplicates(&sink, serde_json::from_value(options).unwrap());
+	let result = wait_for_event(&sink, "link-duplicates-result", 1);
+	let errors = result["errors"].as_array().unwrap();
+	assert_eq!(errors.len(), 1);
+	assert!(errors[0].as_str().unwrap().contains("b/two.bin"));
+	let mut linked: Vec<_> = result["successPaths"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|path| path.as_str().unwrap().to_string())
+		.collect();
+	linked.sort();
+	assert_eq!(linked, [p(&root, "b/two.bin"), p(&root, "c/three.bin")]);
+
+	// No link points back at another link
+	assert!(
+		!fs::symlink_metadata(root.join("a/one.bin"))
+			.unwrap()
+			.is_symlink()
+	);
+	for link in ["b/two.bin", "c/three.bin"] {
+		assert_eq!(
+			fs::read_link(root.join(link)).unwrap(),
+			root.join("a/one.bin")
+		);
+	}
+}
@@ -0,0 +1,278 @@
+mod common;
+
+use std::{
+	fs::{self, File},
+	path::Path,
+	time::{Duration, SystemTime},
+};
+
+use common::{fixture_dir, p, settings_for, setup, wait_for_event, write};
+use czkawka_tauri::{
+	move_files::move_files,
+	scaner::{Tool, run_tool},
+	sink::MemorySink,
+};
+use serde_json::{Value, json};
+
+fn move_with(
+	root: &Path,
+	paths: &[&str],
+	strategy: &str,
+	copy_mode: bool,
+) -> Value {
+	let sink = MemorySink::new();
+	let options = json!({
+		"paths": paths.iter().map(|path| p(root, path)).collect::<Vec<_>>(),
+		"destination": p(root, "dest"),
+		"copyMode": copy_mode,
+		"preserveStructure": false,
+		"overrideMode": false,
+		"conflictStrategy": strategy,
+	});
+	move_files(&sink, serde_json::from_value(options).unwrap());
+	wait_for_event(&sink, "move-files-result", 1)
+}
+
+fn age(path: &Path, days: u64) {
+	let time = SystemTime::now() - Duration::from_secs(days * 86400);
+	let file = File::options().write(true).open(path).unwrap();
+	file.set_modified(time).unwrap();
+}
+
+#[test]
+fn conflicting_names_get_a_number() {
+	let (_dir, root) = fixture_dir();
+	setup();
+	write(&root.join("a/photo.jpg"), b"a");
+	write(&root.join("b/photo.jpg"), b"b");
+	write(&root.join("dest/photo.jpg"), b"already there");
+
+	let result =
+		move_with(&root, &["a/photo.jpg", "b/photo.jpg"], "rename", false);
+	assert_eq!(result["errors"], json!([]));
+
+	assert_eq!(
+		fs::read(root.join("dest/photo.jpg")).unwrap(),
+		b"already there"
+	);
+	let mut moved = vec![
+		fs::read(root.join("dest/photo (1).jpg")).unwrap(),
+		fs::read(root.join("dest/photo (2).jpg")).unwrap(),
+	];
+	moved.sort();
+	assert_eq!(moved, [b"a", b"b"]);
+}
+
+#[test]
+fn newer_and_larger_files_win() {
+	let (_dir, root) = fixture_dir();
+	setup();
+	write(&root.join("src/new.txt"), b"new");
+	write(&root.join("src/old.txt"), b"old");
+	write(&root.join("dest/new.txt"), b"older version");
+	write(&root.join("dest/old.txt"), b"newer version");
+	age(&root.join("dest/new.txt"), 2);
+	age(&root.join("src/old.txt"), 2);
+
+	let result =
+		move_with(&root, &["src/new.txt", "src/old.txt"], "keepNewer", true);
+	assert_eq!(result["successPaths"], json!([p(&root, "src/new.txt")]));
+	assert_eq!(result["skippedPaths"], json!([p(&root, "src/old.txt")]));
+	assert_eq!(fs::read(root.join("dest/new.txt")).unwrap(), b"new");
+	assert_eq!(
+		fs::read(root.join("dest/old.txt")).unwrap(),
+		b"newer version"
+	);
+
+	write(&root.join("src/big.txt"), b"a bigger file");
+	write(&root.join("dest/big.txt"), b"small");
+	let result = move_with(&root, &["src/big.txt"], "keepLarger", false);
+	assert_eq!(result["successPaths"], json!([p(&root, "src/big.txt")]));
+	assert_eq!(
+		fs::read(root.join("dest/big.txt")).unwrap(),
+		b"a bigger file"
+	);
+	assert!(!root.join("src/big.txt").exists());
+}
+
+#[test]
+fn identical_files_are_skipped() {
+	let (_dir, root) = fixture_dir();
+	setup();
+	write(&root.join("src/same.txt"), b"content");
+	write(&root.join("src/other.txt"), b"content");
+	write(&root.join("dest/same.txt"), b"content");
+	write(&root.join("dest/other.txt"), b"changed");
+
+	let result = move_with(
+		&root,
+		&["src/same.txt", "src/other.txt"],
+		"skipIdentical",
+		false,
+	);
+	assert_eq!(result["successPaths"], json!([]));
+	assert_eq!(result["skippedPaths"], json!([p(&root, "src/same.txt")]));
+	assert_eq!(result["errors"].as_array().unwrap().len(), 1);
+	assert!(root.join("src/same.txt").exists());
+	assert_eq!(fs::read(root.join("dest/other.txt")).unwrap(), b"changed");
+}
+
+#[test]
+fn directories_are_copied_and_verified_when_renaming_fails() {
+	let (_dir, root) = fixture_dir();
+	setup();
+	write(&root.join("src/album/a.jpg"), b"a");
+	write(&root.join("src/album/disc 2/b.jpg"), b"b");
+	write(&root.join("dest/album/cover.jpg"), b"cover");
+	age(&root.join("src/album/a.jpg"), 3);
+
+	// Renaming onto a directory that is not empty fails, so the album is
+	// merged into it by copying
+	let result = move_with(&root, &["src/album"], "overwrite", false);
+	assert_eq!(result["errors"], json!([]));
+	assert_eq!(result["successPaths"], json!([p(&root, "src/album")]));
+
+	assert!(!root.join("src/album").exists());
+	assert_eq!(fs::read(root.join("dest/album/a.jpg")).unwrap(), b"a");
+	assert_eq!(
+		fs::read(root.join("dest/album/disc 2/b.jpg")).unwrap(),
+		b"b"
+	);
+	assert_eq!(
+		fs::read(root.join("dest/album/cover.jpg")).unwrap(),
+		b"cover"
+	);
+	let modified = fs::metadata(root.join("dest/album/a.jpg"))
+		.unwrap()
+		.modified()
+		.unwrap();
+	assert!(modified < SystemTime::now() - Duration::from_secs(2 * 86400));
+}
+
+#[test]
+fn structure_is_kept_relative_to_the_scan_root() {
+	let (_dir, root) = fixture_dir();
+	setup();
+	write(&root.join("photos/2024/a.jpg"), b"a");
+	write(&root.join("photos/2025/trip/a.jpg"), b"b");
+	write(&root.join("other/c.jpg"), b"c");
+
+	let sink = MemorySink::new();
+	let mut settings = settings_for(&root);
+	// Resolved like the paths of the files
+	settings
+		.included_directories
+		.push(p(&root, "other/../photos"));
+	run_tool(&sink, Tool::EmptyFiles, settings);
+	let options = json!({
+		"paths": [
+			p(&root, "photos/2024/a.jpg"),
+			p(&root, "photos/2025/trip/a.jpg"),
+			p(&root, "other/c.jpg"),
+		],
+		"destination": p(&root, "dest"),
+		"copyMode": true,
+		"preserveStructure": true,
+		"overrideMode": false,
+		"layout": "relativeToRoot",
+		"tool": "Empty Files",
+	});
+	move_files(&sink, serde_json::from_value(options).unwrap());
+	let result = wait_for_event(&sink, "move-files-result", 1);
+	assert_eq!(result["errors"], json!([]));
+
+	assert_eq!(fs::read(root.join("dest/2024/a.jpg")).unwrap(), b"a");
+	assert_eq!(fs::read(root.join("dest/2025/trip/a.jpg")).unwrap(), b"b");
+	assert_eq!(fs::read(root.join("dest/other/c.jpg")).unwrap(), b"c");
+}
+
+#[test]
+fn flattening_numbers_taken_names() {
+	let (_dir, root) = fixture_dir();
+	setup();
+	write(&root.join("a/photo.jpg"), b"a");
+	write(&root.join("b/photo.jpg"), b"b");
+
+	let sink = MemorySink::new();
+	let options = json!({
+		"paths": [p(&root, "a/photo.jpg"), p(&root, "b/photo.jpg")],
+		"destination": p(&root, "dest"),
+		"copyMode": false,
+		"preserveStructure": true,
+		"overrideMode": false,
+		"layout": "flatten",
+	});
+	move_files(&sink, serde_json::from_value(options).unwrap());
+	let result = wait_for_event(&sink, "move-files-result", 1);
+	assert_eq!(result["errors"], json!([]));
+
+	let mut names: Vec<_> = fs::read_dir(root.join("dest"))
+		.unwrap()
+		.map(|entry| entry.unwrap().file_name().into_string().unwrap())
+		.collect();
+	names.sort();
+	assert_eq!(names, ["photo (1).jpg", "photo.jpg"]);
+}
+
+#[test]
+fn flattening_refuses_other_conflict_strategies() {
+	let (_dir, root) = fixture_dir();
+	setup();
+	write(&root.join("a/photo.jpg"), b"a");
+
+	let sink = MemorySink::new();
+	let options = json!({
+		"paths": [p(&root, "a/photo.jpg")],
+		"destination": p(&root, "dest"),
+		"copyMode": false,
+		"preserveStructure": true,
+		"overrideMode": false,
+		"conflictStrategy": "overwrite",
+		"layout": "flatten",
+	});
+	move_files(&sink, serde_json::from_value(options).unwrap());
+	let result = wait_for_event(&sink, "move-files-result", 1);
+	assert_eq!(result["successPaths"], json!([]));
+	assert_eq!(result["errors"].as_array().unwrap().len(), 1);
+	assert!(root.join("a/photo.jpg").exists());
+}
+
+#[test]
+fn copies_that_do_not_fit_are_refused_up_front() {
+	let (_dir, root) = fixture_dir();
+	setup();
+	write(&root.join("small.txt"), b"small");
+	// Sparse, so it takes no space itself
+	let huge = File::create(root.join("huge.bin")).unwrap();
+	huge.set_len(8 << 40).unwrap();
+
+	let result = move_with(&root, &["small.txt", "huge.bin"], "fail", true);
+	assert_eq!(result["successPaths"], json!([]));
+	let space = &result["refusal"]["space"];
+	assert_eq!(space.as_array().unwrap().len(), 1);
+	assert_eq!(space[0]["destination"], p(&root, "dest"));
+	assert_eq!(space[0]["needed"], (8u64 << 40) + 5);
+	assert_eq!(result["refusal"]["notWritable"], json!([]));
+	assert_eq!(result["errors"].as_array().unwrap().len(), 1);
+	assert!(!root.join("dest").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn fifos_are_not_copied() {
+	use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+	let (_dir, root) = fixture_dir();
+	setup();
+	write(&root.join("a.txt"), b"a");
+	let fifo = CString::new(root.join("fifo").as_os_str().as_bytes()).unwrap();
+	assert_eq!(unsafe { libc::mkfifo(fifo.as_ptr(), 0o644) }, 0);
+
+	// Reading the FIFO would block without a writer
+	let result = move_with(&root, &["a.txt", "fifo"], "fail", true);
+	assert_eq!(result["successPaths"], json!([p(&root, "a.txt")]));
+	let errors = result["errors"].as_array().unwrap();
+	assert_eq!(errors.len(), 1);
+	assert!(errors[0].as_str().unwrap().contains("FIFO"));
+	assert!(!root.join("dest/fifo").exists());
+}
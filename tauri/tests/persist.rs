@@ -0,0 +1,67 @@
+mod common;
+
+use std::fs;
+
+use common::{fixture_dir, p, settings_for, setup, write};
+use czkawka_tauri::{
+	persist::{list_last_results, load_last_result},
+	scaner::{Tool, run_tool},
+	sink::MemorySink,
+};
+
+#[test]
+fn last_result_is_restored_with_changed_entries_marked() {
+	let (_dir, root) = fixture_dir();
+	let data_dir = tempfile::tempdir().unwrap();
+	write(&root.join("a/one.bin"), b"the same content");
+	write(&root.join("b/two.bin"), b"the same content");
+	write(&root.join("c/three.bin"), b"the same content");
+
+	setup();
+	let sink = MemorySink::with_data_dir(data_dir.path().to_path_buf());
+	run_tool(&sink, Tool::DuplicateFiles, settings_for(&root));
+
+	let saved = list_last_results(&sink);
+	assert_eq!(saved.len(), 1);
+	assert_eq!(saved[0].tool, Tool::DuplicateFiles);
+
+	fs::remove_file(root.join("b/two.bin")).unwrap();
+	fs::write(root.join("c/three.bin"), b"now different").unwrap();
+
+	let restored = load_last_result(&sink, Tool::DuplicateFiles)
+		.unwrap()
+		.unwrap();
+	assert_eq!(restored.missing, 1);
+	assert_eq!(restored.changed, 1);
+	assert_eq!(
+		restored.settings.included_directories,
+		vec![root.to_string_lossy().to_string()]
+	);
+
+	let group = &restored.result["list"][0][1];
+	let state_of = |path: String| {
+		group
+			.as_array()
+			.unwrap()
+			.iter()
+			.find(|entry| entry["path"] == path)
+			.unwrap()["state"]
+			.clone()
+	};
+	assert_eq!(state_of(p(&root, "a/one.bin")), serde_json::Value::Null);
+	assert_eq!(state_of(p(&root, "b/two.bin")), "missing");
+	assert_eq!(state_of(p(&root, "c/three.bin")), "changed");
+}
+
+#[test]
+fn nothing_is_saved_without_data_dir() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("zero.txt"), b"");
+
+	setup();
+	let sink = MemorySink::new();
+	run_tool(&sink, Tool::EmptyFiles, settings_for(&root));
+
+	assert!(list_last_results(&sink).is_empty());
+	assert!(load_last_result(&sink, Tool::EmptyFiles).unwrap().is_none());
+}
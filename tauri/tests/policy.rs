@@ -0,0 +1,83 @@
+mod common;
+
+use std::{
+	fs::File,
+	time::{Duration, SystemTime},
+};
+
+use common::{fixture_dir, p, settings_for, setup, write};
+use czkawka_tauri::{
+	policy::{
+		Policy, apply_policy, delete_policy, list_policies, load_policy,
+		save_policy,
+	},
+	scaner::{Tool, run_tool},
+	select::SelectRule,
+	sink::MemorySink,
+};
+
+fn age(path: &std::path::Path, days: u64) {
+	let time = SystemTime::now() - Duration::from_secs(days * 86400);
+	let file = File::options().write(true).open(path).unwrap();
+	file.set_modified(time).unwrap();
+}
+
+#[test]
+fn policy_rules_break_ties_in_order() {
+	let (_dir, root) = fixture_dir();
+	let config_dir = tempfile::tempdir().unwrap();
+	// Two groups: one with a copy in the archive, one without
+	write(&root.join("archive/one.bin"), b"first content");
+	write(&root.join("new/one.bin"), b"first content");
+	write(&root.join("x/two.bin"), b"second content");
+	write(&root.join("y/two.bin"), b"second content");
+	age(&root.join("archive/one.bin"), 1);
+	age(&root.join("new/one.bin"), 5);
+	age(&root.join("x/two.bin"), 2);
+	age(&root.join("y/two.bin"), 4);
+
+	setup();
+	let sink = MemorySink::with_config_dir(config_dir.path().to_path_buf());
+	run_tool(&sink, Tool::DuplicateFiles, settings_for(&root));
+
+	let policy = Policy {
+		name: "archive".to_string(),
+		rules: vec![
+			SelectRule::KeepInDirectories {
+				directories: vec![root.join("archive")],
+			},
+			SelectRule::KeepLargest,
+			SelectRule::KeepOldest,
+		],
+	};
+	save_policy(&sink, &policy).unwrap();
+	assert_eq!(load_policy(&sink, "archive").unwrap(), policy);
+	assert_eq!(list_policies(&sink).unwrap(), [policy]);
+
+	let mut selected =
+		apply_policy(&sink, Tool::DuplicateFiles, "archive").unwrap();
+	selected.sort();
+	assert_eq!(selected, [p(&root, "new/one.bin"), p(&root, "x/two.bin")]);
+
+	assert!(delete_policy(&sink, "archive").unwrap());
+	assert!(!delete_policy(&sink, "archive").unwrap());
+	assert!(apply_policy(&sink, Tool::DuplicateFiles, "archive").is_err());
+}
+
+#[test]
+fn invalid_policies_are_refused() {
+	let config_dir = tempfile::tempdir().unwrap();
+	let sink = MemorySink::with_config_dir(config_dir.path().to_path_buf());
+
+	let empty = Policy {
+		name: "empty".to_string(),
+		rules: vec![],
+	};
+	assert!(save_policy(&sink, &empty).is_err());
+	let escaping = Policy {
+		name: "../escaping".to_string(),
+		rules: vec![SelectRule::KeepNewest],
+	};
+	assert!(save_policy(&sink, &escaping).is_err());
+	assert!(list_policies(&sink).unwrap().is_empty());
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use common::{fixture_dir, p, settings_for, setup, wait_for_event, write};
+use czkawka_tauri::{
+	delete_files::delete_files,
+	move_files::move_files,
+	protected_paths::{
+		default_protected_paths, list_protected_paths, save_protected_paths,
+	},
+	scaner::{Tool, run_tool},
+	sink::MemorySink,
+	sweep::sweep,
+};
+use serde_json::json;
+
+#[test]
+fn protected_files_are_not_deleted() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("photos/a.jpg"), b"a");
+	write(&root.join("photos/.git/config"), b"config");
+
+	setup();
+	let sink = MemorySink::with_config_dir(root.join("config"));
+	assert_eq!(
+		list_protected_paths(&sink).unwrap(),
+		default_protected_paths()
+	);
+	assert!(save_protected_paths(&sink, &["[".to_string()]).is_err());
+	save_protected_paths(&sink, &["**/.git".to_string()]).unwrap();
+	assert_eq!(list_protected_paths(&sink).unwrap(), ["**/.git"]);
+
+	let options = json!({
+		"paths": [
+			p(&root, "photos/a.jpg"),
+			// `..` does not get around the pattern
+			p(&root, "photos/.git/../.git/config"),
+		],
+		"moveDeletedFilesToTrash": false,
+		"isEmptyFoldersTool": false,
+	});
+	delete_files(&sink, serde_json::from_value(options).unwrap());
+	let result = wait_for_event(&sink, "delete-files-result", 1);
+	assert_eq!(result["successPaths"], json!([p(&root, "photos/a.jpg")]));
+	let errors = result["errors"].as_array().unwrap();
+	assert_eq!(errors.len(), 1);
+	assert!(
+		errors[0]
+			.as_str()
+			.unwrap()
+			.contains("protected by `**/.git`")
+	);
+	assert!(root.join("photos/.git/config").exists());
+}
+
+#[test]
+fn reference_folders_of_a_scan_are_protected() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("ref/a.txt"), b"a");
+	write(&root.join("new/a.txt"), b"a");
+
+	setup();
+	let sink = MemorySink::new();
+	let mut settings = settings_for(&root.join("new"));
+	settings.included_directories.push(p(&root, "ref"));
+	settings.included_directories_referenced = vec![p(&root, "ref")];
+	run_tool(&sink, Tool::DuplicateFiles, settings);
+
+	let options = json!({
+		"paths": [p(&root, "ref/a.txt"), p(&root, "new/a.txt")],
+		"destination": p(&root, "dest"),
+		"copyMode": false,
+		"preserveStructure": false,
+		"overrideMode": false,
+		"conflictStrategy": "rename",
+	});
+	move_files(&sink, serde_json::from_value(options).unwrap());
+	let result = wait_for_event(&sink, "move-files-result", 1);
+	assert_eq!(result["successPaths"], json!([p(&root, "new/a.txt")]));
+	let errors = result["errors"].as_array().unwrap();
+	assert_eq!(errors.len(), 1);
+	assert!(errors[0].as_str().unwrap().contains("reference folder"));
+	assert!(root.join("ref/a.txt").exists());
+}
+
+#[test]
+fn reference_folders_of_a_sweep_are_protected_until_the_next_sweep() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("ref/a.txt"), b"a");
+	write(&root.join("new/a.txt"), b"a");
+
+	setup();
+	let sink = MemorySink::new();
+	let mut settings = settings_for(&root.join("new"));
+	settings.included_directories.push(p(&root, "ref"));
+	settings.included_directories_referenced = vec![p(&root, "ref")];
+	sweep(&sink, &[Tool::DuplicateFiles, Tool::BigFiles], settings);
+
+	let delete = |count| {
+		let options = json!({
+			"paths": [p(&root, "ref/a.txt")],
+			"moveDeletedFilesToTrash": false,
+			"isEmptyFoldersTool": false,
+		});
+		delete_files(&sink, serde_json::from_value(options).unwrap());
+		wait_for_event(&sink, "delete-files-result", count)
+	};
+	let result = delete(1);
+	let errors = result["errors"].as_array().unwrap();
+	assert_eq!(errors.len(), 1);
+	assert!(errors[0].as_str().unwrap().contains("reference folder"));
+	assert!(root.join("ref/a.txt").exists());
+
+	// The next sweep has no reference folders, so neither tool keeps the old
+	sweep(
+		&sink,
+		&[Tool::DuplicateFiles, Tool::BigFiles],
+		settings_for(&root),
+	);
+	assert_eq!(delete(2)["errors"], json!([]));
+	assert!(!root.join("ref/a.txt").exists());
+}
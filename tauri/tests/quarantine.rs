@@ -0,0 +1,141 @@
+mod common;
+
+use std::fs;
+
+use common::{fixture_dir, p, wait_for_event, write};
+use czkawka_tauri::{
+	delete_files::delete_files,
+	journal::undo_operation,
+	quarantine::{list_quarantine, purge_quarantine, restore_quarantined},
+	scaner::Tool,
+	sink::{MemorySink, ResultSink},
+};
+use serde_json::json;
+
+fn quarantine(sink: &MemorySink, paths: &[String], dir: &str) {
+	let options = json!({
+		"paths": paths,
+		"moveDeletedFilesToTrash": false,
+		"isEmptyFoldersTool": false,
+		"quarantine": true,
+		"quarantineDir": dir,
+	});
+	delete_files(sink, serde_json::from_value(options).unwrap());
+}
+
+#[test]
+fn quarantined_files_can_be_restored() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("photos/a.jpg"), b"a");
+	write(&root.join("photos/b.jpg"), b"b");
+	let dir = p(&root, "quarantine");
+
+	let sink = MemorySink::new();
+	let paths = [p(&root, "photos/a.jpg"), p(&root, "photos/b.jpg")];
+	quarantine(&sink, &paths, &dir);
+	let result = wait_for_event(&sink, "delete-files-result", 1);
+	assert_eq!(result["errors"], json!([]));
+	assert!(!root.join("photos/a.jpg").exists());
+
+	let entries = list_quarantine(&sink, Some(&dir)).unwrap();
+	assert_eq!(entries.len(), 2);
+	let a = entries
+		.iter()
+		.find(|entry| entry.original_path == root.join("photos/a.jpg"))
+		.unwrap();
+	assert!(a.quarantined_path.starts_with(&dir));
+	assert!(a.quarantined_path.ends_with("photos/a.jpg"));
+	assert_eq!(fs::read(&a.quarantined_path).unwrap(), b"a");
+	assert_eq!(a.size, 1);
+
+	restore_quarantined(&sink, vec![a.id], Some(dir.clone()));
+	let result = wait_for_event(&sink, "quarantine-restore-result", 1);
+	assert_eq!(result["successPaths"], json!([p(&root, "photos/a.jpg")]));
+	assert_eq!(fs::read(root.join("photos/a.jpg")).unwrap(), b"a");
+	assert!(!root.join("photos/b.jpg").exists());
+	assert_eq!(list_quarantine(&sink, Some(&dir)).unwrap().len(), 1);
+}
+
+#[test]
+fn no_quarantine_in_reference_folders() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("photos/a.jpg"), b"a");
+	let dir = p(&root, "reference/quarantine");
+
+	let sink = MemorySink::new();
+	sink.with_state(|state| {
+		state
+			.referenced_directories
+			.insert(Tool::DuplicateFiles, vec![root.join("reference")])
+	});
+	quarantine(&sink, &[p(&root, "photos/a.jpg")], &dir);
+	let result = wait_for_event(&sink, "delete-files-result", 1);
+	assert_eq!(result["errors"].as_array().unwrap().len(), 1);
+	assert!(root.join("photos/a.jpg").exists());
+	assert!(!root.join("reference").exists());
+}
+
+#[test]
+fn purging_only_removes_old_entries() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("a.txt"), b"a");
+	let dir = p(&root, "quarantine");
+
+	let sink = MemorySink::new();
+	quarantine(&sink, &[p(&root, "a.txt")], &dir);
+	wait_for_event(&sink, "delete-files-result", 1);
+	let quarantined = list_quarantine(&sink, Some(&dir)).unwrap()[0]
+		.quarantined_path
+		.clone();
+
+	purge_quarantine(&sink, 1, Some(dir.clone()));
+	let result = wait_for_event(&sink, "quarantine-purge-result", 1);
+	assert_eq!(result["successPaths"], json!([]));
+	assert!(quarantined.exists());
+
+	purge_quarantine(&sink, 0, Some(dir.clone()));
+	let result = wait_for_event(&sink, "quarantine-purge-result", 2);
+	assert_eq!(result["successPaths"], json!([p(&root, "a.txt")]));
+	assert!(!quarantined.exists());
+	assert!(list_quarantine(&sink, Some(&dir)).unwrap().is_empty());
+	// Only the manifest is left
+	let left: Vec<_> = fs::read_dir(&dir).unwrap().collect();
+	assert_eq!(left.len(), 1);
+}
+
+#[test]
+fn undoing_a_quarantine_restores_its_files() {
+	let (_dir, root) = fixture_dir();
+	let data_dir = tempfile::tempdir().unwrap();
+	write(&root.join("photos/a.jpg"), b"a");
+	let dir = p(&root, "quarantine");
+
+	let sink = MemorySink::with_data_dir(data_dir.path().to_path_buf());
+	quarantine(&sink, &[p(&root, "photos/a.jpg")], &dir);
+	let result = wait_for_event(&sink, "delete-files-result", 1);
+	assert_eq!(list_quarantine(&sink, Some(&dir)).unwrap().len(), 1);
+
+	undo_operation(&sink, result["operationId"].as_i64().unwrap());
+	let undone = wait_for_event(&sink, "undo-result", 1);
+	assert_eq!(undone["errors"], json!([]));
+	assert_eq!(fs::read(root.join("photos/a.jpg")).unwrap(), b"a");
+	assert!(list_quarantine(&sink, Some(&dir)).unwrap().is_empty());
+}
+
+#[test]
+fn files_missing_from_the_manifest_are_not_quarantined() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("a.txt"), b"a");
+	let dir = p(&root, "quarantine");
+	// A manifest that cannot be opened
+	fs::create_dir_all(root.join("quarantine/manifest.db")).unwrap();
+
+	let sink = MemorySink::new();
+	quarantine(&sink, &[p(&root, "a.txt")], &dir);
+	let result = wait_for_event(&sink, "delete-files-result", 1);
+	assert_eq!(result["successPaths"], json!([]));
+	let errors = result["errors"].as_array().unwrap();
+	assert_eq!(errors.len(), 1);
+	assert!(errors[0].as_str().unwrap().contains("a.txt"));
+	assert_eq!(fs::read(root.join("a.txt")).unwrap(), b"a");
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{fixture_dir, settings_for, setup, write};
+use czkawka_tauri::{
+	queue::{QueueItem, cancel_queue, run_queue, start_queue},
+	scaner::Tool,
+	sink::{MemorySink, ResultSink},
+};
+
+fn queue_summary(sink: &MemorySink) -> serde_json::Value {
+	let events = sink.events();
+	let (event, summary) = events.last().unwrap();
+	assert_eq!(event, "queue-summary");
+	summary.clone()
+}
+
+#[test]
+fn queue_runs_tools_in_order() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("zero.txt"), b"");
+	write(&root.join("backup.bak"), b"old");
+
+	setup();
+	let sink = MemorySink::new();
+	let queue = start_queue(&sink);
+	let tools = [Tool::EmptyFiles, Tool::TemporaryFiles, Tool::EmptyFolders];
+	let items = tools
+		.iter()
+		.map(|tool| QueueItem {
+			tool: *tool,
+			settings: settings_for(&root),
+		})
+		.collect();
+	run_queue(&sink, queue, items);
+
+	let cmds: Vec<_> = sink
+		.results()
+		.iter()
+		.map(|result| result["cmd"].as_str().unwrap().to_string())
+		.collect();
+	assert_eq!(
+		cmds,
+		vec![
+			"scan_empty_files",
+			"scan_temporary_files",
+			"scan_empty_folders"
+		]
+	);
+
+	let summary = queue_summary(&sink);
+	assert_eq!(summary["cancelled"], false);
+	assert_eq!(summary["jobs"].as_array().unwrap().len(), 3);
+	assert!(
+		summary["jobs"]
+			.as_array()
+			.unwrap()
+			.iter()
+			.all(|job| job["status"] == "finished")
+	);
+	assert!(summary["skipped"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn cancelled_queue_skips_remaining_tools() {
+	let (_dir, root) = fixture_dir();
+
+	setup();
+	let sink = MemorySink::new();
+	let queue = start_queue(&sink);
+	let queue_id = queue.id;
+	assert!(sink.with_state(|state| cancel_queue(state, queue_id)));
+
+	let items = vec![
+		QueueItem {
+			tool: Tool::EmptyFiles,
+			settings: settings_for(&root),
+		},
+		QueueItem {
+			tool: Tool::BigFiles,
+			settings: settings_for(&root),
+		},
+	];
+	run_queue(&sink, queue, items);
+
+	assert!(sink.results().is_empty());
+	let summary = queue_summary(&sink);
+	assert_eq!(summary["cancelled"], true);
+	assert_eq!(
+		summary["skipped"],
+		serde_json::json!(["Empty Files", "Big Files"])
+	);
+	assert!(!sink.with_state(|state| cancel_queue(state, queue_id)));
+}
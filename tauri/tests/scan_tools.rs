@@ -0,0 +1,181 @@
+mod common;
+
+use std::fs;
+
+use common::{
+	fixture_dir, groups, p, path_of, paths, png_bytes, scan, settings_for,
+	setup, write,
+};
+use czkawka_tauri::{
+	scaner::{Tool, run_tool},
+	settings::Settings,
+	sink::{MemorySink, ResultSink},
+};
+
+#[test]
+fn duplicate_files_groups_identical_content() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("a/one.bin"), b"the same content");
+	write(&root.join("b/two.bin"), b"the same content");
+	write(&root.join("c/other.bin"), b"another content!");
+
+	let result = scan(Tool::DuplicateFiles, settings_for(&root));
+
+	assert_eq!(
+		groups(&result),
+		vec![vec![p(&root, "a/one.bin"), p(&root, "b/two.bin")]]
+	);
+}
+
+#[test]
+fn duplicate_files_by_name_ignores_content() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("a/report.txt"), b"first");
+	write(&root.join("b/report.txt"), b"second version");
+	write(&root.join("c/unrelated.txt"), b"first");
+
+	let settings = Settings {
+		duplicates_sub_check_method: "Name".to_string(),
+		..settings_for(&root)
+	};
+	let result = scan(Tool::DuplicateFiles, settings);
+
+	assert_eq!(
+		groups(&result),
+		vec![vec![p(&root, "a/report.txt"), p(&root, "b/report.txt")]]
+	);
+}
+
+#[test]
+fn empty_folders_skips_folders_with_files() {
+	let (_dir, root) = fixture_dir();
+	fs::create_dir_all(root.join("empty/nested/deeper")).unwrap();
+	write(&root.join("full/file.txt"), b"content");
+
+	let result = scan(Tool::EmptyFolders, settings_for(&root));
+	let found = paths(&result);
+
+	assert!(found.contains(&p(&root, "empty")));
+	assert!(!found.contains(&p(&root, "full")));
+}
+
+#[test]
+fn empty_files_finds_zero_byte_files() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("zero.txt"), b"");
+	write(&root.join("sub/zero.log"), b"");
+	write(&root.join("data.txt"), b"content");
+
+	let result = scan(Tool::EmptyFiles, settings_for(&root));
+
+	assert_eq!(
+		paths(&result),
+		vec![p(&root, "sub/zero.log"), p(&root, "zero.txt")]
+	);
+}
+
+#[test]
+fn big_files_returns_largest_first() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("small.bin"), &[0; 10]);
+	write(&root.join("medium.bin"), &[0; 20]);
+	write(&root.join("large.bin"), &[0; 30]);
+
+	let settings = Settings {
+		biggest_files_sub_number_of_files: 2,
+		..settings_for(&root)
+	};
+	let result = scan(Tool::BigFiles, settings);
+	let found: Vec<_> = result["list"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(path_of)
+		.collect();
+
+	assert_eq!(found, vec![p(&root, "large.bin"), p(&root, "medium.bin")]);
+}
+
+#[test]
+fn temporary_files_matches_known_extensions() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("build.tmp"), b"temp");
+	write(&root.join("download.part"), b"partial");
+	write(&root.join("notes.txt"), b"keep");
+
+	let result = scan(Tool::TemporaryFiles, settings_for(&root));
+
+	assert_eq!(
+		paths(&result),
+		vec![p(&root, "build.tmp"), p(&root, "download.part")]
+	);
+}
+
+#[cfg(unix)]
+#[test]
+fn invalid_symlinks_reports_dangling_links() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("target.txt"), b"content");
+	std::os::unix::fs::symlink(root.join("target.txt"), root.join("ok.lnk"))
+		.unwrap();
+	std::os::unix::fs::symlink(root.join("missing.txt"), root.join("bad.lnk"))
+		.unwrap();
+
+	let result = scan(Tool::InvalidSymlinks, settings_for(&root));
+
+	assert_eq!(paths(&result), vec![p(&root, "bad.lnk")]);
+}
+
+#[test]
+fn bad_extensions_detects_mismatched_content() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("picture.txt"), &png_bytes(0));
+	write(&root.join("real.png"), &png_bytes(0));
+
+	let result = scan(Tool::BadExtensions, settings_for(&root));
+
+	assert_eq!(paths(&result), vec![p(&root, "picture.txt")]);
+}
+
+#[test]
+fn broken_files_detects_corrupted_images() {
+	let (_dir, root) = fixture_dir();
+	let valid = png_bytes(0);
+	write(&root.join("valid.png"), &valid);
+	write(&root.join("truncated.png"), &valid[..valid.len() / 3]);
+
+	let settings = Settings {
+		broken_files_sub_audio: false,
+		broken_files_sub_image: true,
+		..settings_for(&root)
+	};
+	let result = scan(Tool::BrokenFiles, settings);
+
+	assert_eq!(paths(&result), vec![p(&root, "truncated.png")]);
+}
+
+#[test]
+fn similar_images_groups_identical_pictures() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("a/photo.png"), &png_bytes(10));
+	write(&root.join("b/photo copy.png"), &png_bytes(10));
+
+	let result = scan(Tool::SimilarImages, settings_for(&root));
+
+	assert_eq!(
+		groups(&result),
+		vec![vec![p(&root, "a/photo.png"), p(&root, "b/photo copy.png")]]
+	);
+}
+
+#[test]
+fn scan_stores_scaner_state() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("zero.txt"), b"");
+
+	setup();
+	let sink = MemorySink::new();
+	run_tool(&sink, Tool::EmptyFiles, settings_for(&root));
+
+	assert!(sink.with_state(|state| state.empty_files_state.is_some()));
+}
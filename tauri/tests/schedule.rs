@@ -0,0 +1,183 @@
+mod common;
+
+use std::{
+	thread,
+	time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local, TimeZone};
+use common::{fixture_dir, settings_for, setup, write};
+use czkawka_tauri::{
+	cron::Cron,
+	history::list_history,
+	job::JobStatus,
+	persist::load_last_result,
+	scaner::{Tool, run_tool},
+	schedule::{
+		Schedule, Scheduler, delete_schedule, list_schedules, save_schedule,
+		schedule_history,
+	},
+	settings::Settings,
+	sink::{MemorySink, ResultSink},
+};
+
+fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+	Local
+		.with_ymd_and_hms(2026, 3, day, hour, minute, 0)
+		.unwrap()
+}
+
+#[test]
+fn cron_finds_next_run() {
+	let cron: Cron = "30 2 * * *".parse().unwrap();
+	assert_eq!(cron.next_after(at(10, 10, 0)), Some(at(11, 2, 30)));
+	assert_eq!(cron.next_after(at(10, 1, 0)), Some(at(10, 2, 30)));
+
+	let cron: Cron = "*/15 * * * *".parse().unwrap();
+	assert_eq!(cron.next_after(at(10, 10, 7)), Some(at(10, 10, 15)));
+	assert_eq!(cron.next_after(at(10, 10, 45)), Some(at(10, 11, 0)));
+
+	// 2026-03-10 is a Tuesday
+	let cron: Cron = "0 0 * * 5".parse().unwrap();
+	assert_eq!(cron.next_after(at(10, 12, 0)), Some(at(13, 0, 0)));
+	let cron: Cron = "0 0 12 * 5".parse().unwrap();
+	assert_eq!(cron.next_after(at(10, 12, 0)), Some(at(12, 0, 0)));
+
+	assert_eq!(
+		"@daily".parse::<Cron>().unwrap(),
+		"0 0 * * *".parse().unwrap()
+	);
+	assert_eq!(
+		"0 0 * * 7".parse::<Cron>().unwrap(),
+		"0 0 * * 0".parse().unwrap()
+	);
+	assert!("61 * * * *".parse::<Cron>().is_err());
+	assert!("* * *".parse::<Cron>().is_err());
+	assert!("*/0 * * * *".parse::<Cron>().is_err());
+}
+
+fn schedule(name: &str, settings: Settings) -> Schedule {
+	Schedule {
+		id: 0,
+		name: name.to_string(),
+		tools: vec![Tool::EmptyFiles],
+		settings,
+		cron: "* * * * *".to_string(),
+		enabled: true,
+	}
+}
+
+#[test]
+fn schedules_are_saved_and_deleted() {
+	let config_dir = tempfile::tempdir().unwrap();
+	let sink = MemorySink::with_config_dir(config_dir.path().to_path_buf());
+	let (_dir, root) = fixture_dir();
+
+	let first =
+		save_schedule(&sink, schedule("first", settings_for(&root))).unwrap();
+	let mut second =
+		save_schedule(&sink, schedule("second", settings_for(&root))).unwrap();
+	assert_eq!((first.id, second.id), (1, 2));
+
+	second.enabled = false;
+	save_schedule(&sink, second).unwrap();
+	let mut invalid = schedule("invalid", settings_for(&root));
+	invalid.cron = "every night".to_string();
+	assert!(save_schedule(&sink, invalid).is_err());
+
+	let schedules = list_schedules(&sink).unwrap();
+	assert_eq!(schedules.len(), 2);
+	assert!(schedules[0].next_run_at.is_some());
+	assert!(schedules[1].next_run_at.is_none());
+
+	assert!(delete_schedule(&sink, 1).unwrap());
+	assert!(!delete_schedule(&sink, 1).unwrap());
+	let schedules = list_schedules(&sink).unwrap();
+	assert_eq!(schedules.len(), 1);
+	assert_eq!(schedules[0].schedule.name, "second");
+}
+
+#[test]
+fn due_schedules_run_and_are_recorded() {
+	let config_dir = tempfile::tempdir().unwrap();
+	let (_dir, root) = fixture_dir();
+	write(&root.join("empty.txt"), b"");
+	write(&root.join("full.txt"), b"content");
+
+	setup();
+	let sink = MemorySink::with_config_dir(config_dir.path().to_path_buf());
+	let saved =
+		save_schedule(&sink, schedule("nightly", settings_for(&root))).unwrap();
+	let mut disabled = schedule("disabled", settings_for(&root));
+	disabled.enabled = false;
+	save_schedule(&sink, disabled).unwrap();
+
+	let now = Local::now();
+	let mut scheduler = Scheduler::new(now - chrono::Duration::minutes(2));
+	assert_eq!(scheduler.tick(&sink, now), vec![saved.id]);
+	// Nothing became due since the last tick
+	assert!(scheduler.tick(&sink, now).is_empty());
+
+	let started = Instant::now();
+	let history = loop {
+		let history = schedule_history(&sink, None).unwrap();
+		if !history.is_empty() {
+			break history;
+		}
+		assert!(started.elapsed() < Duration::from_secs(10));
+		thread::sleep(Duration::from_millis(50));
+	};
+
+	assert_eq!(history.len(), 1);
+	let run = &history[0];
+	assert_eq!(run.schedule_id, saved.id);
+	assert_eq!(run.status, JobStatus::Finished);
+	assert_eq!(run.results.len(), 1);
+	assert_eq!(run.results[0].tool, Tool::EmptyFiles);
+	assert_eq!(run.results[0].items, 1);
+
+	assert!(
+		sink.events()
+			.iter()
+			.any(|(event, _)| event == "schedule-run")
+	);
+	// The results of the scans shown in the app are left alone
+	assert!(sink.results().is_empty());
+	assert!(sink.with_state(|state| state.empty_files_state.is_none()));
+}
+
+#[test]
+fn scheduled_runs_keep_the_last_scans_in_the_app() {
+	let data_dir = tempfile::tempdir().unwrap();
+	let config_dir = tempfile::tempdir().unwrap();
+	let (_dir, root) = fixture_dir();
+	write(&root.join("empty.txt"), b"");
+	let (_other, other) = fixture_dir();
+
+	setup();
+	let sink = MemorySink::with_dirs(
+		data_dir.path().to_path_buf(),
+		config_dir.path().to_path_buf(),
+	);
+	run_tool(&sink, Tool::EmptyFiles, settings_for(&other));
+	save_schedule(&sink, schedule("nightly", settings_for(&root))).unwrap();
+
+	let now = Local::now();
+	let mut scheduler = Scheduler::new(now - chrono::Duration::minutes(2));
+	scheduler.tick(&sink, now);
+	let started = Instant::now();
+	while schedule_history(&sink, None).unwrap().is_empty() {
+		assert!(started.elapsed() < Duration::from_secs(10));
+		thread::sleep(Duration::from_millis(50));
+	}
+
+	let last = load_last_result(&sink, Tool::EmptyFiles).unwrap().unwrap();
+	assert_eq!(last.result["list"], serde_json::json!([]));
+	assert_eq!(list_history(&sink, None, None).unwrap().len(), 1);
+	assert!(
+		config_dir
+			.path()
+			.join("schedule_results/last_results/scan_empty_files.json")
+			.exists()
+	);
+}
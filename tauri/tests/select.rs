@@ -0,0 +1,109 @@
+mod common;
+
+use std::{
+	fs::File,
+	path::Path,
+	time::{Duration, SystemTime},
+};
+
+use common::{fixture_dir, p, png_bytes, settings_for, setup, write};
+use czkawka_tauri::{
+	scaner::{Tool, run_tool},
+	select::{SelectRule, select_in_groups},
+	sink::MemorySink,
+};
+
+fn set_modified(path: &Path, days_ago: u64) {
+	let time = SystemTime::now() - Duration::from_secs(days_ago * 86400);
+	File::options()
+		.write(true)
+		.open(path)
+		.unwrap()
+		.set_modified(time)
+		.unwrap();
+}
+
+fn sorted(mut paths: Vec<String>) -> Vec<String> {
+	paths.sort();
+	paths
+}
+
+#[test]
+fn rules_pick_the_file_to_keep() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("a/one.bin"), b"the same content");
+	write(&root.join("bb/two.bin"), b"the same content");
+	write(&root.join("archive/old/three.bin"), b"the same content");
+	set_modified(&root.join("a/one.bin"), 2);
+	set_modified(&root.join("bb/two.bin"), 1);
+	set_modified(&root.join("archive/old/three.bin"), 3);
+
+	setup();
+	let sink = MemorySink::new();
+	run_tool(&sink, Tool::DuplicateFiles, settings_for(&root));
+	let select = |rule| {
+		sorted(select_in_groups(&sink, Tool::DuplicateFiles, rule).unwrap())
+	};
+	let one = p(&root, "a/one.bin");
+	let two = p(&root, "bb/two.bin");
+	let three = p(&root, "archive/old/three.bin");
+
+	assert_eq!(
+		select(SelectRule::KeepNewest),
+		sorted(vec![three.clone(), one.clone()])
+	);
+	assert_eq!(
+		select(SelectRule::KeepOldest),
+		sorted(vec![one.clone(), two.clone()])
+	);
+	assert_eq!(
+		select(SelectRule::KeepShortestPath),
+		sorted(vec![three.clone(), two.clone()])
+	);
+	assert_eq!(
+		select(SelectRule::KeepLongestPath),
+		sorted(vec![one.clone(), two.clone()])
+	);
+	assert_eq!(
+		select(SelectRule::KeepInDirectories {
+			directories: vec![root.join("missing"), root.join("bb")],
+		}),
+		sorted(vec![three.clone(), one.clone()])
+	);
+
+	assert!(
+		select_in_groups(
+			&sink,
+			Tool::DuplicateFiles,
+			SelectRule::KeepHighestBitrate
+		)
+		.is_err()
+	);
+	assert!(
+		select_in_groups(&sink, Tool::SimilarImages, SelectRule::KeepNewest)
+			.is_err()
+	);
+}
+
+#[test]
+fn highest_resolution_image_is_kept() {
+	let (_dir, root) = fixture_dir();
+	let small = image::load_from_memory(&png_bytes(10)).unwrap();
+	small.save(root.join("small.png")).unwrap();
+	small
+		.resize_exact(128, 128, image::imageops::FilterType::Triangle)
+		.save(root.join("large.png"))
+		.unwrap();
+
+	setup();
+	let sink = MemorySink::new();
+	run_tool(&sink, Tool::SimilarImages, settings_for(&root));
+
+	let selected = select_in_groups(
+		&sink,
+		Tool::SimilarImages,
+		SelectRule::KeepHighestResolution,
+	)
+	.unwrap();
+	assert_eq!(selected, [p(&root, "small.png")]);
+}
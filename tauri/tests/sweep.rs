@@ -0,0 +1,203 @@
+mod common;
+
+use std::path::Path;
+
+use common::{
+	fixture_dir, groups, p, paths, png_bytes, scan, settings_for, setup, write,
+};
+use czkawka_tauri::{
+	save_result::save_result,
+	scaner::Tool,
+	scaner::run_tool,
+	settings::Settings,
+	sink::{MemorySink, ResultSink},
+	sweep::{SWEEP_TOOLS, sweep},
+};
+use serde_json::{Value, json};
+
+fn write_fixture(root: &Path) {
+	write(&root.join("a/one.bin"), b"the same content");
+	write(&root.join("b/two.bin"), b"the same content");
+	write(&root.join("c/other.bin"), b"another content!");
+	write(&root.join("zero.txt"), b"");
+	write(&root.join("notes.tmp"), b"scratch");
+	write(&root.join("picture.txt"), &png_bytes(0));
+	write(&root.join("big.dat"), &[7; 4096]);
+}
+
+fn run_sweep(tools: &[Tool], settings: Settings) -> Vec<Value> {
+	setup();
+	let sink = MemorySink::new();
+	sweep(&sink, tools, settings);
+	sink.results()
+}
+
+fn result_of(results: &[Value], tool: Tool) -> &Value {
+	results
+		.iter()
+		.find(|result| result["cmd"] == tool.cmd())
+		.unwrap()
+}
+
+#[test]
+fn sweep_matches_single_tool_scans() {
+	let (_dir, root) = fixture_dir();
+	write_fixture(&root);
+
+	let results = run_sweep(&SWEEP_TOOLS, settings_for(&root));
+	assert_eq!(results.len(), SWEEP_TOOLS.len());
+
+	for tool in [Tool::EmptyFiles, Tool::TemporaryFiles, Tool::BadExtensions] {
+		assert_eq!(
+			paths(result_of(&results, tool)),
+			paths(&scan(tool, settings_for(&root))),
+			"{:?}",
+			tool
+		);
+	}
+	assert_eq!(
+		groups(result_of(&results, Tool::DuplicateFiles)),
+		groups(&scan(Tool::DuplicateFiles, settings_for(&root)))
+	);
+	assert_eq!(
+		result_of(&results, Tool::BigFiles)["list"][0]["path"],
+		p(&root, "big.dat")
+	);
+}
+
+#[test]
+fn sweep_finds_duplicates_without_prehash() {
+	let (_dir, root) = fixture_dir();
+	write_fixture(&root);
+
+	let settings = Settings {
+		duplicate_use_prehash: false,
+		..settings_for(&root)
+	};
+	let results = run_sweep(&[Tool::DuplicateFiles], settings);
+
+	assert_eq!(results.len(), 1);
+	assert_eq!(
+		groups(&results[0]),
+		vec![vec![p(&root, "a/one.bin"), p(&root, "b/two.bin")]]
+	);
+}
+
+#[test]
+fn sweep_respects_excluded_directories_and_extensions() {
+	let (_dir, root) = fixture_dir();
+	write_fixture(&root);
+
+	let settings = Settings {
+		excluded_directories: vec![p(&root, "b")],
+		excluded_extensions: "tmp".to_string(),
+		..settings_for(&root)
+	};
+	let results =
+		run_sweep(&[Tool::DuplicateFiles, Tool::EmptyFiles], settings);
+
+	assert!(groups(result_of(&results, Tool::DuplicateFiles)).is_empty());
+	assert_eq!(
+		paths(result_of(&results, Tool::EmptyFiles)),
+		vec![p(&root, "zero.txt")]
+	);
+}
+
+#[test]
+fn sweep_splits_referenced_directories() {
+	let (_dir, root) = fixture_dir();
+	write_fixture(&root);
+
+	let settings = Settings {
+		included_directories: vec![p(&root, "a"), p(&root, "b")],
+		included_directories_referenced: vec![p(&root, "a")],
+		..settings_for(&root)
+	};
+	let results = run_sweep(&[Tool::DuplicateFiles], settings);
+
+	let list = &results[0]["list"];
+	assert_eq!(list.as_array().unwrap().len(), 1);
+	assert_eq!(list[0][0]["path"], p(&root, "a/one.bin"));
+	assert_eq!(list[0][1][0]["path"], p(&root, "b/two.bin"));
+}
+
+#[test]
+fn sweep_drops_the_scaners_of_older_scans() {
+	let (_dir, root) = fixture_dir();
+	write_fixture(&root);
+
+	setup();
+	let sink = MemorySink::new();
+	run_tool(&sink, Tool::BigFiles, settings_for(&root));
+	run_tool(&sink, Tool::EmptyFiles, settings_for(&root));
+	sweep(&sink, &[Tool::BigFiles], settings_for(&root));
+
+	// Saving the Big Files results would save those of the older scan
+	assert!(sink.with_state(|state| state.big_files_state.is_none()));
+	assert!(sink.with_state(|state| state.empty_files_state.is_some()));
+}
+
+#[test]
+fn sweep_results_are_refused_by_save_result() {
+	let (_dir, root) = fixture_dir();
+	write_fixture(&root);
+	let (_out, destination) = fixture_dir();
+	let save = |sink: &MemorySink, tool: &str| {
+		let options = json!({
+			"currentTool": tool,
+			"destination": destination.to_string_lossy(),
+		});
+		save_result(sink, serde_json::from_value(options).unwrap());
+		sink.take_events()
+			.into_iter()
+			.find(|(name, _)| name == "save-result-done")
+			.map(|(_, message)| message.as_str().unwrap().to_string())
+			.unwrap()
+	};
+
+	setup();
+	let sink = MemorySink::new();
+	run_tool(&sink, Tool::EmptyFiles, settings_for(&root));
+	sweep(&sink, &[Tool::BigFiles], settings_for(&root));
+	let message = save(&sink, "Big Files");
+	assert!(
+		message.contains("found by a sweep cannot be saved"),
+		"{message}"
+	);
+	let message = save(&sink, "Empty Files");
+	assert!(message.starts_with("Successfully saved"), "{message}");
+
+	// A later scan of the tool can be saved again
+	run_tool(&sink, Tool::BigFiles, settings_for(&root));
+	let message = save(&sink, "Big Files");
+	assert!(message.starts_with("Successfully saved"), "{message}");
+}
+
+#[test]
+fn sweep_flags_the_same_bad_extensions_as_the_scan() {
+	let (_dir, root) = fixture_dir();
+	let exe = b"MZ\x90\x00\x03\x00\x00\x00\x04\x00\x00\x00\xff\xff\x00\x00";
+	let zip = b"PK\x03\x04\x14\x00\x00\x00\x08\x00\x00\x00\x00\x00\x00\x00";
+	let gzip = b"\x1f\x8b\x08\x00\x00\x00\x00\x00\x00\x03\x00\x00\x00\x00";
+	for name in ["a.exe", "a.dll", "a.sys", "a.msi", "a.txt", "a.pyd"] {
+		write(&root.join(name), exe);
+	}
+	for name in ["b.zip", "b.jar", "b.docx", "b.wmz", "b.txt", "b.png"] {
+		write(&root.join(name), zip);
+	}
+	for name in ["c.gz", "c.tgz", "c.crate", "c.blend", "c.jpg"] {
+		write(&root.join(name), gzip);
+	}
+	write(&root.join("d.txt"), &png_bytes(1));
+	write(&root.join("d.png"), &png_bytes(2));
+	write(&root.join("d"), &png_bytes(3));
+
+	let results = run_sweep(&[Tool::BadExtensions], settings_for(&root));
+	let swept = paths(&results[0]);
+	assert_eq!(
+		swept,
+		paths(&scan(Tool::BadExtensions, settings_for(&root)))
+	);
+	assert!(swept.contains(&p(&root, "d.txt")));
+	assert!(!swept.contains(&p(&root, "a.dll")));
+}
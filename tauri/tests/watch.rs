@@ -0,0 +1,92 @@
+mod common;
+
+use std::{fs, thread, time::Duration};
+
+use common::{
+	fixture_dir, groups, p, settings_for, setup, wait_for_event, write,
+};
+use czkawka_tauri::{
+	scaner::{Tool, run_tool},
+	sink::{MemorySink, ResultSink},
+	watch::{unwatch_duplicates, watch_duplicates},
+};
+use serde_json::Value;
+
+fn wait_for_delta(sink: &MemorySink, count: usize) -> Value {
+	wait_for_event(sink, "duplicate-groups-delta", count)
+}
+
+fn removed(delta: &Value) -> Vec<Vec<String>> {
+	delta["removed"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|group| {
+			let mut paths: Vec<_> = group
+				.as_array()
+				.unwrap()
+				.iter()
+				.map(|path| path.as_str().unwrap().to_string())
+				.collect();
+			paths.sort();
+			paths
+		})
+		.collect()
+}
+
+fn added(delta: &Value) -> Vec<Vec<String>> {
+	groups(&serde_json::json!({ "list": delta["added"] }))
+}
+
+#[test]
+fn watch_requires_a_duplicate_scan() {
+	let sink = MemorySink::new();
+	assert!(watch_duplicates(&sink).is_err());
+	assert!(!unwatch_duplicates(&sink));
+}
+
+#[test]
+fn watch_updates_duplicate_groups() {
+	let (_dir, root) = fixture_dir();
+	write(&root.join("a/one.bin"), b"the same content");
+	write(&root.join("b/two.bin"), b"the same content");
+	write(&root.join("c/other.bin"), b"another content!");
+
+	setup();
+	let sink = MemorySink::new();
+	run_tool(&sink, Tool::DuplicateFiles, settings_for(&root));
+	let scanned = groups(&sink.results()[0]);
+	assert_eq!(
+		scanned,
+		vec![vec![p(&root, "a/one.bin"), p(&root, "b/two.bin")]]
+	);
+
+	watch_duplicates(&sink).unwrap();
+	// Let the watch index the directories before changing them
+	thread::sleep(Duration::from_millis(500));
+
+	write(&root.join("c/three.bin"), b"the same content");
+	let delta = wait_for_delta(&sink, 1);
+	assert_eq!(removed(&delta), scanned);
+	assert_eq!(
+		added(&delta),
+		vec![vec![
+			p(&root, "a/one.bin"),
+			p(&root, "b/two.bin"),
+			p(&root, "c/three.bin")
+		]]
+	);
+
+	fs::remove_file(root.join("a/one.bin")).unwrap();
+	fs::remove_file(root.join("b/two.bin")).unwrap();
+	let delta = wait_for_delta(&sink, 2);
+	assert_eq!(removed(&delta).len(), 1);
+	assert!(added(&delta).is_empty());
+
+	let stored = sink.with_state(|state| {
+		state.duplicate_groups.as_ref().unwrap().groups.len()
+	});
+	assert_eq!(stored, 0);
+
+	assert!(unwatch_duplicates(&sink));
+}